@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
-use crate::schema::{ConfigurationSchema, GetEnumOptions, GetSchema};
+use crate::{
+    point::Point,
+    schema::{ConfigurationSchema, GetEnumOptions, GetSchema},
+};
 
 #[derive(Serialize, Deserialize, Debug, thiserror::Error)]
 #[error("animation error: {message}")]
@@ -15,6 +18,16 @@ pub trait Animation {
 
     fn new(points: Vec<(f64, f64, f64)>) -> Self;
 
+    /// Creates the animation for lights with their metadata, like strands and
+    /// tags. Only animations targeting groups of lights or following strands
+    /// need to implement it, others get just the positions.
+    fn with_points(points: Vec<Point>) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(points.iter().map(Point::position).collect())
+    }
+
     fn new_wrapped(points: Vec<(f64, f64, f64)>) -> Self::Wrapped {
         Self::Wrapped::new(points)
     }
@@ -46,5 +59,15 @@ pub trait Animation {
 
     fn on_event(&mut self, _event: crate::event::Event) {}
 
+    /// Returns state to preserve across restarts of the server and reloads of
+    /// the plugin, like the contents of a canvas. The format is up to the animation.
+    fn save_state(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Restores state previously returned by `save_state`, possibly by an older
+    /// version of the animation.
+    fn load_state(&mut self, _state: &[u8]) {}
+
     fn render(&self) -> lightfx::Frame;
 }
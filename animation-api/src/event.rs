@@ -1,7 +1,7 @@
 use midi_msg::MidiMsg;
 use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[non_exhaustive]
 pub enum Event {
     BeatEvent {
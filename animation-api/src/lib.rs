@@ -1,6 +1,7 @@
 mod api;
 pub mod event;
 pub mod plugin_config;
+pub mod point;
 pub mod schema;
 
 pub use api::Animation;
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{fmt, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 
@@ -6,6 +6,31 @@ use serde::{Deserialize, Serialize};
 pub enum PluginApiVersion {
     #[serde(rename = "0.9")]
     V0_9,
+    /// Typed records in place of JSON strings in the WIT interface
+    #[serde(rename = "0.10")]
+    V0_10,
+    /// Points with metadata, more kinds of parameters and plugin state
+    #[serde(rename = "0.11")]
+    V0_11,
+    /// Version newer than any known to this crate
+    #[serde(rename = "unknown", other)]
+    Unknown,
+}
+
+impl PluginApiVersion {
+    /// Version of the interface implemented by plugins built against this crate.
+    pub const CURRENT: Self = Self::V0_11;
+}
+
+impl fmt::Display for PluginApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::V0_9 => write!(f, "0.9"),
+            Self::V0_10 => write!(f, "0.10"),
+            Self::V0_11 => write!(f, "0.11"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,3 +48,16 @@ pub struct PluginConfig {
     pub manifest: PluginManifest,
     pub path: PathBuf,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_api_versions_are_parsed() {
+        let version: PluginApiVersion = serde_json::from_str("\"0.10\"").unwrap();
+        assert_eq!(version, PluginApiVersion::V0_10);
+        let version: PluginApiVersion = serde_json::from_str("\"0.12\"").unwrap();
+        assert_eq!(version, PluginApiVersion::Unknown);
+    }
+}
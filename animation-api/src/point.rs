@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+/// A light, with its position and what else is known about it from the setup.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    /// Strand the light is on, for setups with multiple strands.
+    pub strand: Option<u32>,
+    /// Position of the light along its strand.
+    pub index: Option<u32>,
+    /// Named groups the light belongs to, like "star" or "trunk".
+    pub tags: Vec<String>,
+    /// Confidence of the configurator in the position, from 0.0 to 1.0.
+    pub confidence: Option<f64>,
+}
+
+impl Point {
+    pub fn position(&self) -> (f64, f64, f64) {
+        (self.x, self.y, self.z)
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+}
+
+impl From<(f64, f64, f64)> for Point {
+    fn from((x, y, z): (f64, f64, f64)) -> Self {
+        Self {
+            x,
+            y,
+            z,
+            ..Default::default()
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
-use lightfx::Color;
+use lightfx::{Color, GradientStop};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
@@ -13,11 +13,35 @@ pub struct EnumOption {
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ValueSchema {
-    Number { min: f64, max: f64, step: f64 },
+    Number {
+        min: f64,
+        max: f64,
+        step: f64,
+    },
     Color,
-    Enum { values: Vec<EnumOption> },
+    Enum {
+        values: Vec<EnumOption>,
+    },
     Speed,
     Percentage,
+    Boolean,
+    Text {
+        max_length: usize,
+    },
+    /// List of color stops, sorted by position.
+    Gradient,
+    /// List of colors.
+    Palette,
+    /// Three coordinates, each between `min` and `max`, for directions and points.
+    Vector3 {
+        min: f64,
+        max: f64,
+    },
+    /// Number of seconds between `min` and `max`.
+    Duration {
+        min: f64,
+        max: f64,
+    },
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
@@ -25,10 +49,43 @@ pub struct ParameterSchema {
     pub id: String,
     pub name: String,
     pub description: Option<String>,
+    /// Section of related parameters this parameter is displayed in.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visible_when: Option<VisibilityCondition>,
     #[serde(flatten)]
     pub value: ValueSchema,
 }
 
+impl ParameterSchema {
+    /// Tells whether the parameter is relevant given the values of the other
+    /// parameters. Parameters are visible when their condition depends on a
+    /// parameter without a value.
+    pub fn is_visible(&self, values: &HashMap<String, ParameterValue>) -> bool {
+        self.visible_when.as_ref().is_none_or(|condition| {
+            values
+                .get(&condition.parameter)
+                .is_none_or(|value| match (value, &condition.equals) {
+                    // Conditions are written the same way for text and enum parameters
+                    (ParameterValue::Text(text), ParameterValue::EnumOption(option)) => {
+                        text == option
+                    }
+                    (value, equals) => value == equals,
+                })
+        })
+    }
+}
+
+/// Shows a parameter only while another parameter has the given value.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct VisibilityCondition {
+    pub parameter: String,
+    pub equals: ParameterValue,
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
 pub struct ConfigurationSchema {
     #[serde(default)]
@@ -39,6 +96,22 @@ pub struct ConfigurationSchema {
     pub custom_triggers: Vec<EnumOption>,
 }
 
+impl ConfigurationSchema {
+    /// Converts values to the kinds their parameters expect, see
+    /// [`ParameterValue::for_schema`].
+    pub fn typed_values(
+        &self,
+        mut values: HashMap<String, ParameterValue>,
+    ) -> HashMap<String, ParameterValue> {
+        for parameter in &self.parameters {
+            if let Some(value) = values.remove(&parameter.id) {
+                values.insert(parameter.id.clone(), value.for_schema(&parameter.value));
+            }
+        }
+        values
+    }
+}
+
 pub trait GetSchema {
     fn schema() -> Vec<ParameterSchema>;
 }
@@ -63,8 +136,17 @@ impl GetEnumOptions for () {
 #[serde(untagged)]
 pub enum ParameterValue {
     Number(f64),
+    Boolean(bool),
+    /// Comes before `Color`, which also deserializes from a sequence of numbers.
+    Vector3([f64; 3]),
     Color(Color),
+    /// Value of enum parameters. Values of text parameters deserialize as enum
+    /// options too, since both are strings, see [`ParameterValue::for_schema`].
     EnumOption(String),
+    /// Value of text parameters.
+    Text(String),
+    Palette(Vec<Color>),
+    Gradient(Vec<GradientStop>),
 }
 
 #[cfg(feature = "yew")]
@@ -94,12 +176,176 @@ impl ParameterValue {
             None
         }
     }
+
+    pub fn text(&self) -> Option<&str> {
+        if let Self::Text(s) = self {
+            Some(s)
+        } else {
+            None
+        }
+    }
+
+    pub fn boolean(&self) -> Option<bool> {
+        if let Self::Boolean(b) = self {
+            Some(*b)
+        } else {
+            None
+        }
+    }
+
+    pub fn vector3(&self) -> Option<[f64; 3]> {
+        if let Self::Vector3(v) = self {
+            Some(*v)
+        } else {
+            None
+        }
+    }
+
+    pub fn palette(&self) -> Option<&[Color]> {
+        if let Self::Palette(colors) = self {
+            Some(colors)
+        } else {
+            None
+        }
+    }
+
+    pub fn gradient(&self) -> Option<&[GradientStop]> {
+        if let Self::Gradient(stops) = self {
+            Some(stops)
+        } else {
+            None
+        }
+    }
+
+    /// Value of duration parameters, which are stored as numbers of seconds.
+    pub fn duration(&self) -> Option<Duration> {
+        self.number()
+            .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+    }
+
+    /// Converts strings to the kind of value the schema expects, since texts
+    /// and enum options cannot be told apart once serialized.
+    pub fn for_schema(self, schema: &ValueSchema) -> Self {
+        match (self, schema) {
+            (Self::EnumOption(s), ValueSchema::Text { .. }) => Self::Text(s),
+            (Self::Text(s), ValueSchema::Enum { .. }) => Self::EnumOption(s),
+            (value, _) => value,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(from = "UntypedConfiguration")]
 pub struct Configuration {
     pub id: String,
     pub name: String,
     pub schema: ConfigurationSchema,
     pub values: HashMap<String, ParameterValue>,
 }
+
+/// Configuration as deserialized, before its values are typed by its schema.
+#[derive(Deserialize)]
+struct UntypedConfiguration {
+    id: String,
+    name: String,
+    schema: ConfigurationSchema,
+    values: HashMap<String, ParameterValue>,
+}
+
+impl From<UntypedConfiguration> for Configuration {
+    fn from(configuration: UntypedConfiguration) -> Self {
+        Self {
+            values: configuration.schema.typed_values(configuration.values),
+            id: configuration.id,
+            name: configuration.name,
+            schema: configuration.schema,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parameter_values_round_trip() {
+        let values = [
+            ParameterValue::Number(0.5),
+            ParameterValue::Boolean(true),
+            ParameterValue::Color(Color::rgb(1, 2, 3)),
+            ParameterValue::EnumOption("fast".to_owned()),
+            ParameterValue::Vector3([0.0, 1.0, 0.0]),
+            ParameterValue::Palette(vec![Color::rgb(255, 0, 0), Color::rgb(0, 0, 255)]),
+            ParameterValue::Gradient(vec![
+                GradientStop {
+                    position: 0.0,
+                    color: Color::rgb(0, 0, 0),
+                },
+                GradientStop {
+                    position: 1.0,
+                    color: Color::rgb(255, 255, 255),
+                },
+            ]),
+        ];
+        for value in values {
+            let json = serde_json::to_string(&value).unwrap();
+            assert_eq!(
+                serde_json::from_str::<ParameterValue>(&json).unwrap(),
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn strings_are_typed_by_the_schema() {
+        let text = ValueSchema::Text { max_length: 8 };
+        let options = ValueSchema::Enum { values: Vec::new() };
+        let value: ParameterValue = serde_json::from_str("\"fast\"").unwrap();
+
+        assert_eq!(value.enum_option(), Some("fast"));
+        assert_eq!(value.text(), None);
+        let value = value.for_schema(&text);
+        assert_eq!(value, ParameterValue::Text("fast".to_owned()));
+        assert_eq!(value.enum_option(), None);
+        assert_eq!(serde_json::to_string(&value).unwrap(), "\"fast\"");
+        assert_eq!(
+            value.for_schema(&options),
+            ParameterValue::EnumOption("fast".to_owned())
+        );
+    }
+
+    #[test]
+    fn configurations_keep_texts_across_serialization() {
+        let configuration = Configuration {
+            id: "banner".to_owned(),
+            name: "Banner".to_owned(),
+            schema: ConfigurationSchema {
+                parameters: vec![ParameterSchema {
+                    id: "message".to_owned(),
+                    name: "Message".to_owned(),
+                    description: None,
+                    group: None,
+                    visible_when: None,
+                    value: ValueSchema::Text { max_length: 32 },
+                }],
+                custom_triggers: Vec::new(),
+            },
+            values: HashMap::from([(
+                "message".to_owned(),
+                ParameterValue::Text("Merry Christmas".to_owned()),
+            )]),
+        };
+
+        let json = serde_json::to_string(&configuration).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Configuration>(&json).unwrap(),
+            configuration
+        );
+    }
+
+    #[test]
+    fn integer_vectors_are_not_colors() {
+        let value: ParameterValue = serde_json::from_str("[0, 1, 0]").unwrap();
+        assert_eq!(value, ParameterValue::Vector3([0.0, 1.0, 0.0]));
+    }
+}
@@ -26,16 +26,41 @@ struct Number {
     step: f64,
 }
 
+#[derive(Debug, Clone, FromMeta)]
+struct Range {
+    min: f64,
+    max: f64,
+}
+
+#[derive(Debug, Clone, FromMeta)]
+struct Text {
+    max_length: usize,
+}
+
+#[derive(Debug, Clone, FromMeta)]
+struct VisibleWhen {
+    parameter: String,
+    equals: syn::Lit,
+}
+
 #[derive(FromField, Default, Debug)]
 #[darling(default, attributes(schema_field))]
 struct FieldAttributes {
     name: String,
     description: Option<String>,
+    group: Option<String>,
+    visible_when: Option<VisibleWhen>,
     number: Option<Number>,
     color: bool,
     speed: bool,
     percentage: bool,
     enum_options: bool,
+    boolean: bool,
+    text: Option<Text>,
+    gradient: bool,
+    palette: bool,
+    vector3: Option<Range>,
+    duration: Option<Range>,
 }
 
 #[proc_macro_derive(Schema, attributes(schema_field, number))]
@@ -58,6 +83,29 @@ fn quote_potentially_negative_number(value: f64) -> proc_macro2::TokenStream {
     }
 }
 
+/// Strings compare to enum options and texts, other literals to booleans and numbers.
+fn quote_parameter_value(value: &syn::Lit) -> Result<proc_macro2::TokenStream, darling::Error> {
+    let value = match value {
+        syn::Lit::Str(s) => {
+            return Ok(quote! {
+                animation_api::schema::ParameterValue::EnumOption(#s.to_owned())
+            });
+        }
+        syn::Lit::Bool(b) => {
+            return Ok(quote! {
+                animation_api::schema::ParameterValue::Boolean(#b)
+            });
+        }
+        syn::Lit::Int(i) => i.base10_parse::<f64>()?,
+        syn::Lit::Float(f) => f.base10_parse::<f64>()?,
+        _ => return Err(darling::Error::unexpected_lit_type(value)),
+    };
+    let value = quote_potentially_negative_number(value);
+    Ok(quote! {
+        animation_api::schema::ParameterValue::Number(#value)
+    })
+}
+
 fn derive_schema_inner(
     input: proc_macro2::TokenStream,
 ) -> Result<proc_macro2::TokenStream, darling::Error> {
@@ -78,7 +126,9 @@ fn derive_schema_inner(
                 let max = quote_potentially_negative_number(number.max);
                 let step = number.step;
                 if step.is_infinite() || step.is_nan() || !step.is_sign_positive() {
-                    errors.push(darling::Error::custom("Step has to a finite positive number"));
+                    errors.push(darling::Error::custom(
+                        "Step has to a finite positive number",
+                    ));
                 }
                 quote! {
                     animation_api::schema::ValueSchema::Number {
@@ -106,8 +156,50 @@ fn derive_schema_inner(
                         values: <#ty as animation_api::schema::GetEnumOptions>::enum_options(),
                     }
                 }
+            } else if attrs.boolean {
+                quote! {
+                    animation_api::schema::ValueSchema::Boolean
+                }
+            } else if let Some(Text { max_length }) = attrs.text {
+                quote! {
+                    animation_api::schema::ValueSchema::Text {
+                        max_length: (#max_length),
+                    }
+                }
+            } else if attrs.gradient {
+                quote! {
+                    animation_api::schema::ValueSchema::Gradient
+                }
+            } else if attrs.palette {
+                quote! {
+                    animation_api::schema::ValueSchema::Palette
+                }
+            } else if let Some(range) = attrs.vector3 {
+                let min = quote_potentially_negative_number(range.min);
+                let max = quote_potentially_negative_number(range.max);
+                quote! {
+                    animation_api::schema::ValueSchema::Vector3 {
+                        min: (#min),
+                        max: (#max),
+                    }
+                }
+            } else if let Some(range) = attrs.duration {
+                if range.min < 0.0 {
+                    errors.push(darling::Error::custom("Duration cannot be negative"));
+                }
+                let min = range.min;
+                let max = range.max;
+                quote! {
+                    animation_api::schema::ValueSchema::Duration {
+                        min: (#min),
+                        max: (#max),
+                    }
+                }
             } else {
-                errors.push(darling::Error::custom("One of 'number', 'color', 'percentage', 'speed' or 'enum' required in 'schema'"));
+                errors.push(darling::Error::custom(
+                    "One of 'number', 'color', 'percentage', 'speed', 'enum', 'boolean', 'text', \
+                     'gradient', 'palette', 'vector3' or 'duration' required in 'schema'",
+                ));
                 return None;
             };
 
@@ -118,11 +210,30 @@ fn derive_schema_inner(
             } else {
                 quote! { None }
             };
+            let group = if let Some(group) = &attrs.group {
+                quote! { Some(#group.to_owned()) }
+            } else {
+                quote! { None }
+            };
+            let visible_when = if let Some(condition) = &attrs.visible_when {
+                let parameter = &condition.parameter;
+                let equals = errors.handle(quote_parameter_value(&condition.equals))?;
+                quote! {
+                    Some(animation_api::schema::VisibilityCondition {
+                        parameter: #parameter.to_owned(),
+                        equals: #equals,
+                    })
+                }
+            } else {
+                quote! { None }
+            };
             Some(quote! {
                 animation_api::schema::ParameterSchema {
                     id: stringify!(#ident).to_owned(),
                     name: #name.to_owned(),
                     description: #description,
+                    group: #group,
+                    visible_when: #visible_when,
                     value: #value,
                 },
             })
@@ -195,21 +306,79 @@ mod tests {
             output.to_string(),
             quote! {
                 impl animation_api::schema::GetSchema for Parameters {
-                    fn schema() -> animation_api::schema::ConfigurationSchema {
-                        animation_api::schema::ConfigurationSchema {
-                            parameters: vec![
-                                animation_api::schema::ParameterSchema {
-                                    id: stringify!(param).to_owned(),
-                                    name: "Param".to_owned(),
-                                    description: None,
-                                    value: animation_api::schema::ValueSchema::Number {
-                                        min: (-(1f64)),
-                                        max: (1f64),
-                                        step: (0.1f64),
-                                    },
+                    fn schema() -> Vec<animation_api::schema::ParameterSchema> {
+                        vec![
+                            animation_api::schema::ParameterSchema {
+                                id: stringify!(param).to_owned(),
+                                name: "Param".to_owned(),
+                                description: None,
+                                group: None,
+                                visible_when: None,
+                                value: animation_api::schema::ValueSchema::Number {
+                                    min: (-(1f64)),
+                                    max: (1f64),
+                                    step: (0.1f64),
+                                },
+                            },
+                        ]
+                    }
+                }
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn test_derive_schema_grouped_text_and_vector() {
+        let input = quote! {
+            pub struct Parameters {
+                #[schema_field(name = "Label", text(max_length = 16))]
+                label: String,
+                #[schema_field(
+                    name = "Direction",
+                    group = "Motion",
+                    visible_when(parameter = "mode", equals = "Directional"),
+                    vector3(min = "-1.0", max = 1.0),
+                )]
+                direction: [f64; 3],
+            }
+        };
+
+        let output = derive_schema_inner(input).unwrap();
+
+        assert_eq!(
+            output.to_string(),
+            quote! {
+                impl animation_api::schema::GetSchema for Parameters {
+                    fn schema() -> Vec<animation_api::schema::ParameterSchema> {
+                        vec![
+                            animation_api::schema::ParameterSchema {
+                                id: stringify!(label).to_owned(),
+                                name: "Label".to_owned(),
+                                description: None,
+                                group: None,
+                                visible_when: None,
+                                value: animation_api::schema::ValueSchema::Text {
+                                    max_length: (16usize),
+                                },
+                            },
+                            animation_api::schema::ParameterSchema {
+                                id: stringify!(direction).to_owned(),
+                                name: "Direction".to_owned(),
+                                description: None,
+                                group: Some("Motion".to_owned()),
+                                visible_when: Some(animation_api::schema::VisibilityCondition {
+                                    parameter: "mode".to_owned(),
+                                    equals: animation_api::schema::ParameterValue::EnumOption(
+                                        "Directional".to_owned()
+                                    ),
+                                }),
+                                value: animation_api::schema::ValueSchema::Vector3 {
+                                    min: (-(1f64)),
+                                    max: (1f64),
                                 },
-                            ],
-                        }
+                            },
+                        ]
                     }
                 }
             }
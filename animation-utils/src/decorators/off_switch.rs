@@ -1,4 +1,5 @@
 use animation_api::Animation;
+use animation_api::point::Point;
 use animation_api::schema::{GetEnumOptions, GetSchema, ParameterSchema, ValueSchema};
 use animation_macros::EnumSchema;
 use serde::de::DeserializeOwned;
@@ -30,6 +31,8 @@ impl<P: GetSchema> GetSchema for Parameters<P> {
                 id: "off_switch_state".to_owned(),
                 name: "State".to_owned(),
                 description: None,
+                group: Some("Off switch".to_owned()),
+                visible_when: None,
                 value: ValueSchema::Enum {
                     values: Switch::enum_options(),
                 },
@@ -38,6 +41,8 @@ impl<P: GetSchema> GetSchema for Parameters<P> {
                 id: "off_switch_delay".to_owned(),
                 name: "Switch delay".to_owned(),
                 description: None,
+                group: Some("Off switch".to_owned()),
+                visible_when: None,
                 value: ValueSchema::Number {
                     min: 0.0,
                     max: 5.0,
@@ -67,8 +72,12 @@ where
     type Wrapped = Self;
 
     fn new(points: Vec<(f64, f64, f64)>) -> Self {
+        Self::with_points(points.into_iter().map(Point::from).collect())
+    }
+
+    fn with_points(points: Vec<Point>) -> Self {
         Self {
-            animation: A::new(points),
+            animation: A::with_points(points),
             parameters: Parameters {
                 off_switch_delay: 1.0,
                 off_switch_state: Switch::On,
@@ -97,6 +106,14 @@ where
         self.animation.on_event(event)
     }
 
+    fn save_state(&self) -> Option<Vec<u8>> {
+        self.animation.save_state()
+    }
+
+    fn load_state(&mut self, state: &[u8]) {
+        self.animation.load_state(state)
+    }
+
     fn render(&self) -> lightfx::Frame {
         self.animation
             .render()
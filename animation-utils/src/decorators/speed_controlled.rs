@@ -1,4 +1,5 @@
 use animation_api::Animation;
+use animation_api::point::Point;
 use animation_api::schema::{GetEnumOptions, GetSchema, ParameterSchema, ValueSchema};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
@@ -17,6 +18,8 @@ impl<P: GetSchema> GetSchema for Parameters<P> {
             id: "speed_factor".to_owned(),
             name: "Speed Factor".to_owned(),
             description: None,
+            group: None,
+            visible_when: None,
             value: ValueSchema::Speed,
         }];
         parameters.extend(P::schema());
@@ -40,8 +43,12 @@ where
     type Wrapped = Self;
 
     fn new(points: Vec<(f64, f64, f64)>) -> Self {
+        Self::with_points(points.into_iter().map(Point::from).collect())
+    }
+
+    fn with_points(points: Vec<Point>) -> Self {
         Self {
-            animation: A::new(points),
+            animation: A::with_points(points),
             parameters: Parameters {
                 speed_factor: 1.0,
                 inner: Default::default(),
@@ -57,6 +64,14 @@ where
         self.animation.on_event(event)
     }
 
+    fn save_state(&self) -> Option<Vec<u8>> {
+        self.animation.save_state()
+    }
+
+    fn load_state(&mut self, state: &[u8]) {
+        self.animation.load_state(state)
+    }
+
     fn render(&self) -> lightfx::Frame {
         self.animation.render()
     }
@@ -0,0 +1,456 @@
+/// Implements conversions between types of the animation API and their
+/// counterparts generated from the WIT interface. Host and guest bindings each
+/// generate their own types of the same shape, so both invoke this macro,
+/// inside a dedicated module, with the path of the generated `plugin` interface.
+macro_rules! wit_conversions {
+    ($($wit:ident)::+) => {
+        use std::collections::HashMap;
+
+        use animation_api::{event::Event, point::Point, schema};
+        use $($wit)::+ as wit;
+
+        impl From<lightfx::Color> for wit::Color {
+            fn from(color: lightfx::Color) -> Self {
+                Self {
+                    r: color.r,
+                    g: color.g,
+                    b: color.b,
+                }
+            }
+        }
+
+        impl From<wit::Color> for lightfx::Color {
+            fn from(color: wit::Color) -> Self {
+                lightfx::Color::rgb(color.r, color.g, color.b)
+            }
+        }
+
+        impl From<Point> for wit::Point {
+            fn from(point: Point) -> Self {
+                Self {
+                    position: wit::Position {
+                        x: point.x,
+                        y: point.y,
+                        z: point.z,
+                    },
+                    strand: point.strand,
+                    index: point.index,
+                    tags: point.tags,
+                    confidence: point.confidence,
+                }
+            }
+        }
+
+        impl From<wit::Point> for Point {
+            fn from(point: wit::Point) -> Self {
+                Self {
+                    x: point.position.x,
+                    y: point.position.y,
+                    z: point.position.z,
+                    strand: point.strand,
+                    index: point.index,
+                    tags: point.tags,
+                    confidence: point.confidence,
+                }
+            }
+        }
+
+        impl From<schema::EnumOption> for wit::EnumOption {
+            fn from(option: schema::EnumOption) -> Self {
+                Self {
+                    name: option.name,
+                    description: option.description,
+                    value: option.value,
+                }
+            }
+        }
+
+        impl From<wit::EnumOption> for schema::EnumOption {
+            fn from(option: wit::EnumOption) -> Self {
+                Self {
+                    name: option.name,
+                    description: option.description,
+                    value: option.value,
+                }
+            }
+        }
+
+        impl From<schema::ValueSchema> for wit::ValueSchema {
+            fn from(value: schema::ValueSchema) -> Self {
+                match value {
+                    schema::ValueSchema::Number { min, max, step } => {
+                        Self::Number(wit::NumberSchema { min, max, step })
+                    }
+                    schema::ValueSchema::Color => Self::Color,
+                    schema::ValueSchema::Enum { values } => {
+                        Self::Enum(values.into_iter().map(Into::into).collect())
+                    }
+                    schema::ValueSchema::Speed => Self::Speed,
+                    schema::ValueSchema::Percentage => Self::Percentage,
+                    schema::ValueSchema::Boolean => Self::Boolean,
+                    schema::ValueSchema::Text { max_length } => {
+                        Self::Text(max_length.try_into().unwrap_or(u32::MAX))
+                    }
+                    schema::ValueSchema::Gradient => Self::Gradient,
+                    schema::ValueSchema::Palette => Self::Palette,
+                    schema::ValueSchema::Vector3 { min, max } => {
+                        Self::Vector3(wit::Range { min, max })
+                    }
+                    schema::ValueSchema::Duration { min, max } => {
+                        Self::Duration(wit::Range { min, max })
+                    }
+                }
+            }
+        }
+
+        impl From<wit::ValueSchema> for schema::ValueSchema {
+            fn from(value: wit::ValueSchema) -> Self {
+                match value {
+                    wit::ValueSchema::Number(wit::NumberSchema { min, max, step }) => {
+                        Self::Number { min, max, step }
+                    }
+                    wit::ValueSchema::Color => Self::Color,
+                    wit::ValueSchema::Enum(values) => Self::Enum {
+                        values: values.into_iter().map(Into::into).collect(),
+                    },
+                    wit::ValueSchema::Speed => Self::Speed,
+                    wit::ValueSchema::Percentage => Self::Percentage,
+                    wit::ValueSchema::Boolean => Self::Boolean,
+                    wit::ValueSchema::Text(max_length) => Self::Text {
+                        max_length: max_length as usize,
+                    },
+                    wit::ValueSchema::Gradient => Self::Gradient,
+                    wit::ValueSchema::Palette => Self::Palette,
+                    wit::ValueSchema::Vector3(wit::Range { min, max }) => {
+                        Self::Vector3 { min, max }
+                    }
+                    wit::ValueSchema::Duration(wit::Range { min, max }) => {
+                        Self::Duration { min, max }
+                    }
+                }
+            }
+        }
+
+        impl From<schema::VisibilityCondition> for wit::VisibilityCondition {
+            fn from(condition: schema::VisibilityCondition) -> Self {
+                Self {
+                    parameter: condition.parameter,
+                    equals: condition.equals.into(),
+                }
+            }
+        }
+
+        impl From<wit::VisibilityCondition> for schema::VisibilityCondition {
+            fn from(condition: wit::VisibilityCondition) -> Self {
+                Self {
+                    parameter: condition.parameter,
+                    equals: condition.equals.into(),
+                }
+            }
+        }
+
+        impl From<schema::ParameterSchema> for wit::ParameterSchema {
+            fn from(parameter: schema::ParameterSchema) -> Self {
+                Self {
+                    id: parameter.id,
+                    name: parameter.name,
+                    description: parameter.description,
+                    group: parameter.group,
+                    visible_when: parameter.visible_when.map(Into::into),
+                    value: parameter.value.into(),
+                }
+            }
+        }
+
+        impl From<wit::ParameterSchema> for schema::ParameterSchema {
+            fn from(parameter: wit::ParameterSchema) -> Self {
+                Self {
+                    id: parameter.id,
+                    name: parameter.name,
+                    description: parameter.description,
+                    group: parameter.group,
+                    visible_when: parameter.visible_when.map(Into::into),
+                    value: parameter.value.into(),
+                }
+            }
+        }
+
+        impl From<schema::ConfigurationSchema> for wit::ConfigurationSchema {
+            fn from(schema: schema::ConfigurationSchema) -> Self {
+                Self {
+                    parameters: schema.parameters.into_iter().map(Into::into).collect(),
+                    custom_triggers: schema.custom_triggers.into_iter().map(Into::into).collect(),
+                }
+            }
+        }
+
+        impl From<wit::ConfigurationSchema> for schema::ConfigurationSchema {
+            fn from(schema: wit::ConfigurationSchema) -> Self {
+                Self {
+                    parameters: schema.parameters.into_iter().map(Into::into).collect(),
+                    custom_triggers: schema.custom_triggers.into_iter().map(Into::into).collect(),
+                }
+            }
+        }
+
+        impl From<schema::ParameterValue> for wit::ParameterValue {
+            fn from(value: schema::ParameterValue) -> Self {
+                match value {
+                    schema::ParameterValue::Number(n) => Self::Number(n),
+                    schema::ParameterValue::Color(c) => Self::Color(c.into()),
+                    schema::ParameterValue::EnumOption(o) => Self::EnumOption(o),
+                    schema::ParameterValue::Text(t) => Self::Text(t),
+                    schema::ParameterValue::Boolean(b) => Self::Boolean(b),
+                    schema::ParameterValue::Vector3([x, y, z]) => Self::Vector3((x, y, z)),
+                    schema::ParameterValue::Palette(colors) => {
+                        Self::Palette(colors.into_iter().map(Into::into).collect())
+                    }
+                    schema::ParameterValue::Gradient(stops) => Self::Gradient(
+                        stops
+                            .into_iter()
+                            .map(|stop| wit::GradientStop {
+                                position: stop.position,
+                                color: stop.color.into(),
+                            })
+                            .collect(),
+                    ),
+                }
+            }
+        }
+
+        impl From<wit::ParameterValue> for schema::ParameterValue {
+            fn from(value: wit::ParameterValue) -> Self {
+                match value {
+                    wit::ParameterValue::Number(n) => Self::Number(n),
+                    wit::ParameterValue::Color(c) => Self::Color(c.into()),
+                    wit::ParameterValue::EnumOption(o) => Self::EnumOption(o),
+                    wit::ParameterValue::Text(t) => Self::Text(t),
+                    wit::ParameterValue::Boolean(b) => Self::Boolean(b),
+                    wit::ParameterValue::Vector3((x, y, z)) => Self::Vector3([x, y, z]),
+                    wit::ParameterValue::Palette(colors) => {
+                        Self::Palette(colors.into_iter().map(Into::into).collect())
+                    }
+                    wit::ParameterValue::Gradient(stops) => Self::Gradient(
+                        stops
+                            .into_iter()
+                            .map(|stop| lightfx::GradientStop {
+                                position: stop.position,
+                                color: stop.color.into(),
+                            })
+                            .collect(),
+                    ),
+                }
+            }
+        }
+
+        pub(super) fn values_to_wit(
+            values: HashMap<String, schema::ParameterValue>,
+        ) -> Vec<(String, wit::ParameterValue)> {
+            values.into_iter().map(|(k, v)| (k, v.into())).collect()
+        }
+
+        pub(super) fn values_from_wit(
+            values: Vec<(String, wit::ParameterValue)>,
+        ) -> HashMap<String, schema::ParameterValue> {
+            values.into_iter().map(|(k, v)| (k, v.into())).collect()
+        }
+
+        /// Fails for kinds of events the WIT interface does not know, returning them back.
+        impl TryFrom<Event> for wit::Event {
+            type Error = Event;
+
+            fn try_from(event: Event) -> Result<Self, Self::Error> {
+                Ok(match event {
+                    Event::BeatEvent { bpm } => Self::Beat(bpm),
+                    Event::FftEvent { bands, wave } => Self::Fft(wit::FftEvent { bands, wave }),
+                    Event::MidiEvent(message) => Self::Midi(message.to_midi()),
+                    Event::CustomTrigger { trigger_id } => Self::CustomTrigger(trigger_id),
+                    Event::MouseMove {
+                        ray_origin: [ox, oy, oz],
+                        ray_direction: [dx, dy, dz],
+                    } => Self::MouseMove(wit::MouseMoveEvent {
+                        ray_origin: (ox, oy, oz),
+                        ray_direction: (dx, dy, dz),
+                    }),
+                    Event::MouseUp => Self::MouseUp,
+                    Event::MouseDown => Self::MouseDown,
+                    event => return Err(event),
+                })
+            }
+        }
+
+        /// Fails for MIDI events carrying an invalid message.
+        impl TryFrom<wit::Event> for Event {
+            type Error = midi_msg::ParseError;
+
+            fn try_from(event: wit::Event) -> Result<Self, Self::Error> {
+                Ok(match event {
+                    wit::Event::Beat(bpm) => Self::BeatEvent { bpm },
+                    wit::Event::Fft(wit::FftEvent { bands, wave }) => {
+                        Self::FftEvent { bands, wave }
+                    }
+                    wit::Event::Midi(bytes) => {
+                        Self::MidiEvent(midi_msg::MidiMsg::from_midi(&bytes)?.0)
+                    }
+                    wit::Event::CustomTrigger(trigger_id) => Self::CustomTrigger { trigger_id },
+                    wit::Event::MouseMove(wit::MouseMoveEvent {
+                        ray_origin: (ox, oy, oz),
+                        ray_direction: (dx, dy, dz),
+                    }) => Self::MouseMove {
+                        ray_origin: [ox, oy, oz],
+                        ray_direction: [dx, dy, dz],
+                    },
+                    wit::Event::MouseUp => Self::MouseUp,
+                    wit::Event::MouseDown => Self::MouseDown,
+                })
+            }
+        }
+    };
+}
+
+pub(crate) use wit_conversions;
+
+#[cfg(all(test, feature = "host"))]
+mod tests {
+    use animation_api::{event::Event, schema::*};
+    use lightfx::{Color, GradientStop};
+    use midi_msg::{Channel, ChannelVoiceMsg, MidiMsg};
+
+    use crate::host::exports::guest::animation::plugin as wit;
+
+    fn option(value: &str) -> EnumOption {
+        EnumOption {
+            name: value.to_uppercase(),
+            description: Some(format!("{value} option")),
+            value: value.to_owned(),
+        }
+    }
+
+    fn values() -> Vec<ParameterValue> {
+        vec![
+            ParameterValue::Number(0.5),
+            ParameterValue::Boolean(true),
+            ParameterValue::Color(Color::rgb(1, 2, 3)),
+            ParameterValue::EnumOption("fast".to_owned()),
+            ParameterValue::Text("hello".to_owned()),
+            ParameterValue::Vector3([0.0, -1.0, 2.5]),
+            ParameterValue::Palette(vec![Color::rgb(255, 0, 0), Color::rgb(0, 0, 255)]),
+            ParameterValue::Gradient(vec![
+                GradientStop {
+                    position: 0.0,
+                    color: Color::rgb(0, 0, 0),
+                },
+                GradientStop {
+                    position: 1.0,
+                    color: Color::rgb(255, 255, 255),
+                },
+            ]),
+        ]
+    }
+
+    #[test]
+    fn parameter_values_round_trip() {
+        for value in values() {
+            let wit_value: wit::ParameterValue = value.clone().into();
+            assert_eq!(ParameterValue::from(wit_value), value);
+        }
+    }
+
+    #[test]
+    fn configuration_schema_round_trips() {
+        let value_schemas = [
+            ValueSchema::Number {
+                min: 0.0,
+                max: 10.0,
+                step: 0.5,
+            },
+            ValueSchema::Color,
+            ValueSchema::Enum {
+                values: vec![option("slow"), option("fast")],
+            },
+            ValueSchema::Speed,
+            ValueSchema::Percentage,
+            ValueSchema::Boolean,
+            ValueSchema::Text { max_length: 32 },
+            ValueSchema::Gradient,
+            ValueSchema::Palette,
+            ValueSchema::Vector3 {
+                min: -1.0,
+                max: 1.0,
+            },
+            ValueSchema::Duration {
+                min: 0.0,
+                max: 60.0,
+            },
+        ];
+        let schema = ConfigurationSchema {
+            parameters: value_schemas
+                .into_iter()
+                .zip(values().into_iter().cycle())
+                .enumerate()
+                .map(|(i, (value, equals))| ParameterSchema {
+                    id: format!("param{i}"),
+                    name: format!("Parameter {i}"),
+                    description: (i % 2 == 0).then(|| "Described".to_owned()),
+                    group: (i % 3 == 0).then(|| "Group".to_owned()),
+                    visible_when: (i > 0).then(|| VisibilityCondition {
+                        parameter: format!("param{}", i - 1),
+                        equals,
+                    }),
+                    value,
+                })
+                .collect(),
+            custom_triggers: vec![option("boom")],
+        };
+
+        let wit_schema: wit::ConfigurationSchema = schema.clone().into();
+        assert_eq!(ConfigurationSchema::from(wit_schema), schema);
+    }
+
+    #[test]
+    fn events_round_trip() {
+        let events = [
+            Event::BeatEvent { bpm: 120.0 },
+            Event::FftEvent {
+                bands: vec![0.1, 0.2],
+                wave: vec![-0.5, 0.5],
+            },
+            Event::MidiEvent(MidiMsg::ChannelVoice {
+                channel: Channel::Ch2,
+                msg: ChannelVoiceMsg::NoteOn {
+                    note: 60,
+                    velocity: 100,
+                },
+            }),
+            Event::CustomTrigger {
+                trigger_id: "boom".to_owned(),
+            },
+            Event::MouseMove {
+                ray_origin: [0.0, 1.0, 2.0],
+                ray_direction: [0.0, 0.0, -1.0],
+            },
+            Event::MouseUp,
+            Event::MouseDown,
+        ];
+        for event in events {
+            let wit_event = wit::Event::try_from(event.clone()).unwrap();
+            assert_eq!(Event::try_from(wit_event).unwrap(), event);
+        }
+    }
+
+    #[test]
+    fn midi_events_are_passed_as_raw_bytes() {
+        let note_on = Event::MidiEvent(MidiMsg::ChannelVoice {
+            channel: Channel::Ch2,
+            msg: ChannelVoiceMsg::NoteOn {
+                note: 60,
+                velocity: 100,
+            },
+        });
+        assert!(matches!(
+            wit::Event::try_from(note_on).unwrap(),
+            wit::Event::Midi(bytes) if bytes == [0x91, 60, 100]
+        ));
+        assert!(Event::try_from(wit::Event::Midi(Vec::new())).is_err());
+    }
+}
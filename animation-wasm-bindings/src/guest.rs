@@ -1,13 +1,76 @@
-use std::{cell::RefCell, marker::PhantomData};
+use std::{
+    cell::RefCell,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
 
 use animation_api::schema::GetSchema;
-use exports::guest::animation::plugin::{Color, Guest, Position};
+use exports::guest::animation::plugin::{
+    Color, ConfigurationSchema, Event, Guest, ParameterValue, Point, Position,
+};
+use guest::animation::host::{self, LogLevel};
 
 wit_bindgen::generate!({
     world: "animation",
     pub_export_macro: true,
 });
 
+mod conversions {
+    crate::conversions::wit_conversions!(super::exports::guest::animation::plugin);
+}
+
+/// Forwards records logged with the `log` crate to the server log.
+struct HostLogger;
+
+impl log::Log for HostLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        let level = match record.level() {
+            log::Level::Error => LogLevel::Error,
+            log::Level::Warn => LogLevel::Warn,
+            log::Level::Info => LogLevel::Info,
+            log::Level::Debug => LogLevel::Debug,
+            log::Level::Trace => LogLevel::Trace,
+        };
+        host::log(level, &record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: HostLogger = HostLogger;
+
+fn install_logger() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(log::LevelFilter::Trace);
+    }
+}
+
+/// Returns wall clock time provided by the host, which may be simulated.
+pub fn now() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_millis(host::now())
+}
+
+/// Returns a random number from the source provided by the host.
+pub fn random() -> u64 {
+    host::random()
+}
+
+/// Returns a random number between 0.0 (inclusive) and 1.0 (exclusive) from
+/// the source provided by the host.
+pub fn random_f64() -> f64 {
+    (random() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Returns the path of a file from the `assets` directory bundled with the plugin.
+pub fn asset_path<P: AsRef<Path>>(name: P) -> PathBuf {
+    Path::new(crate::GUEST_ASSETS_DIR).join(name)
+}
+
 pub struct GuestPluginBindings<T: animation_api::Animation> {
     _phantom: PhantomData<T>,
 }
@@ -24,6 +87,7 @@ impl<T: animation_api::Animation + 'static> exports::guest::animation::plugin::G
     for GuestAnimationBindings<T>
 {
     fn new(points: Vec<Position>) -> Self {
+        install_logger();
         Self {
             inner: RefCell::new(T::new(
                 points.into_iter().map(|p| (p.x, p.y, p.z)).collect(),
@@ -31,6 +95,13 @@ impl<T: animation_api::Animation + 'static> exports::guest::animation::plugin::G
         }
     }
 
+    fn with_points(points: Vec<Point>) -> exports::guest::animation::plugin::Animation {
+        install_logger();
+        exports::guest::animation::plugin::Animation::new(Self {
+            inner: RefCell::new(T::with_points(points.into_iter().map(Into::into).collect())),
+        })
+    }
+
     fn update(&self, time_delta: f64) {
         self.inner.borrow_mut().update(time_delta);
     }
@@ -40,24 +111,31 @@ impl<T: animation_api::Animation + 'static> exports::guest::animation::plugin::G
             .borrow()
             .render()
             .pixels_iter()
-            .map(|p| Color {
-                r: p.r,
-                g: p.g,
-                b: p.b,
-            })
+            .map(|p| Color::from(*p))
             .collect()
     }
 
-    fn get_schema(&self) -> String {
-        serde_json::to_string(&self.inner.borrow().get_schema()).unwrap()
+    fn get_schema(&self) -> ConfigurationSchema {
+        self.inner.borrow().get_schema().into()
     }
 
-    fn get_parameters(&self) -> String {
-        serde_json::to_string(&self.inner.borrow().get_parameters()).unwrap()
+    // Parameters of animations are structs, which are converted to and from
+    // values of individual parameters through their serde representation.
+    // Strings come back as enum options, so the schema tells which are texts.
+
+    fn get_parameters(&self) -> Vec<(String, ParameterValue)> {
+        let inner = self.inner.borrow();
+        serde_json::to_value(inner.get_parameters())
+            .and_then(serde_json::from_value)
+            .map(|values| inner.get_schema().typed_values(values))
+            .map(conversions::values_to_wit)
+            .unwrap_or_default()
     }
 
-    fn set_parameters(&self, values: String) {
-        if let Ok(values) = serde_json::from_str(&values) {
+    fn set_parameters(&self, values: Vec<(String, ParameterValue)>) {
+        if let Ok(values) = serde_json::to_value(conversions::values_from_wit(values))
+            .and_then(serde_json::from_value)
+        {
             self.inner.borrow_mut().set_parameters(values);
         }
     }
@@ -66,9 +144,17 @@ impl<T: animation_api::Animation + 'static> exports::guest::animation::plugin::G
         self.inner.borrow().get_fps()
     }
 
-    fn on_event(&self, event: String) {
-        if let Ok(event) = serde_json::from_str(&event) {
+    fn on_event(&self, event: Event) {
+        if let Ok(event) = event.try_into() {
             self.inner.borrow_mut().on_event(event);
         }
     }
+
+    fn save_state(&self) -> Option<Vec<u8>> {
+        self.inner.borrow().save_state()
+    }
+
+    fn load_state(&self, state: Vec<u8>) {
+        self.inner.borrow_mut().load_state(&state);
+    }
 }
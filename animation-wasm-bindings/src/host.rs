@@ -1,16 +1,35 @@
-use std::{collections::HashMap, io::Read, path::Path};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock, Once, OnceLock, Weak},
+    time::{Duration, SystemTime},
+};
 
-use animation_api::{event::Event, plugin_config::PluginManifest, schema};
-use animation_wrapper::unwrap::{self, PluginUnwrapError};
-use exports::guest::animation::plugin::Position;
+use animation_api::{
+    event::Event,
+    plugin_config::{PluginApiVersion, PluginManifest},
+    point::Point,
+    schema,
+};
+use animation_wrapper::{
+    ASSETS_DIR,
+    unwrap::{self, PluginUnwrapError},
+};
+use guest::animation::host::LogLevel;
 use itertools::Itertools;
-use tokio::sync::Mutex;
+use rand::{RngCore, SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tempfile::TempDir;
+use tokio::sync::{Mutex, MutexGuard};
 use wasmtime::{
-    AsContextMut, Config, Engine, Store,
-    component::{Component, Linker, ResourceAny, bindgen},
+    AsContextMut, Config, Engine, ResourceLimiter, Store, StoreLimits, StoreLimitsBuilder, Trap,
+    component::{Component, HasSelf, Linker, ResourceAny, bindgen},
 };
 use wasmtime_wasi::{
-    ResourceTable,
+    DirPerms, FilePerms, ResourceTable,
     p2::{IoView, WasiCtx, WasiCtxBuilder, WasiView},
 };
 
@@ -19,9 +38,216 @@ bindgen!({
     async: true,
 });
 
+mod conversions {
+    crate::conversions::wit_conversions!(super::exports::guest::animation::plugin);
+
+    pub(super) fn schema_from_wit(schema: wit::ConfigurationSchema) -> schema::ConfigurationSchema {
+        schema.into()
+    }
+
+    /// Returns `None` for kinds of events the plugin does not know.
+    pub(super) fn event_to_wit(event: Event) -> Option<wit::Event> {
+        event.try_into().ok()
+    }
+}
+mod v0_10;
+mod v0_9;
+
+/// Time between increments of the engine epoch, which determines the precision of call deadlines.
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+static ENGINE: OnceLock<Engine> = OnceLock::new();
+static EPOCH_TICKER: Once = Once::new();
+
+/// Plugin file with the length and SHA-256 digest of its contents, as
+/// modification times are too coarse to tell rewritten files apart.
+type AssetsKey = (PathBuf, u64, [u8; 32]);
+
+/// Assets unpacked from plugin files. Directories are removed once the last
+/// plugin using them is dropped.
+type AssetsCache = HashMap<AssetsKey, Weak<TempDir>>;
+static ASSETS: LazyLock<std::sync::Mutex<AssetsCache>> = LazyLock::new(Default::default);
+
+fn assets_key(path: &Path) -> Result<AssetsKey> {
+    let mut hasher = Sha256::new();
+    let length = std::io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok((path.to_owned(), length, hasher.finalize().into()))
+}
+
+/// Unpacks assets bundled with the plugin into a temporary directory, shared
+/// by all plugins created from the same, unchanged file.
+fn unpack_assets(path: &Path) -> Result<Option<Arc<TempDir>>> {
+    let key = assets_key(path)?;
+    if let Some(dir) = ASSETS.lock().unwrap().get(&key).and_then(Weak::upgrade) {
+        return Ok(Some(dir));
+    }
+
+    // Unpacked without holding the lock, so that large assets do not hold up
+    // loading other plugins
+    let dir = tempfile::Builder::new()
+        .prefix("rustmas-assets-")
+        .tempdir()?;
+    if unwrap::unpack_assets(path, dir.path())?.is_none() {
+        return Ok(None);
+    }
+
+    let mut cache = ASSETS.lock().unwrap();
+    cache.retain(|_, dir| dir.strong_count() > 0);
+    // Another plugin might have unpacked the same file in the meantime
+    if let Some(existing) = cache.get(&key).and_then(Weak::upgrade) {
+        return Ok(Some(existing));
+    }
+    let dir = Arc::new(dir);
+    cache.insert(key, Arc::downgrade(&dir));
+    Ok(Some(dir))
+}
+
+fn shared_engine() -> Result<Engine> {
+    if let Some(engine) = ENGINE.get() {
+        return Ok(engine.clone());
+    }
+
+    let mut config = Config::new();
+    config.async_support(true);
+    config.epoch_interruption(true);
+    let engine = Engine::new(&config)?;
+    // Another thread might have set the engine in the meantime, so use the stored one
+    let engine = ENGINE.get_or_init(|| engine).clone();
+
+    EPOCH_TICKER.call_once(|| {
+        let engine = engine.clone();
+        std::thread::spawn(move || {
+            loop {
+                std::thread::sleep(EPOCH_TICK);
+                engine.increment_epoch();
+            }
+        });
+    });
+
+    Ok(engine)
+}
+
+/// Resource limits applied to every animation plugin.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PluginLimits {
+    /// Maximum time a single call into the plugin can take.
+    #[serde(default = "PluginLimits::default_call_timeout_ms")]
+    pub call_timeout_ms: u64,
+    /// Maximum size of linear memory of the plugin.
+    #[serde(default = "PluginLimits::default_max_memory_mb")]
+    pub max_memory_mb: usize,
+}
+
+impl PluginLimits {
+    fn default_call_timeout_ms() -> u64 {
+        200
+    }
+
+    fn default_max_memory_mb() -> usize {
+        64
+    }
+
+    fn deadline_ticks(&self) -> u64 {
+        (self.call_timeout_ms / EPOCH_TICK.as_millis() as u64).max(1)
+    }
+}
+
+impl Default for PluginLimits {
+    fn default() -> Self {
+        Self {
+            call_timeout_ms: Self::default_call_timeout_ms(),
+            max_memory_mb: Self::default_max_memory_mb(),
+        }
+    }
+}
+
+/// Applies store limits, remembering whether they were exceeded during the
+/// current call, so that the resulting trap can be told apart from other failures.
+struct Limiter {
+    limits: StoreLimits,
+    exceeded: bool,
+}
+
+impl ResourceLimiter for Limiter {
+    fn memory_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        let allowed = self.limits.memory_growing(current, desired, maximum);
+        if !matches!(allowed, Ok(true)) {
+            self.exceeded = true;
+        }
+        allowed
+    }
+
+    fn table_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        let allowed = self.limits.table_growing(current, desired, maximum);
+        if !matches!(allowed, Ok(true)) {
+            self.exceeded = true;
+        }
+        allowed
+    }
+}
+
+/// Source of wall clock time reported to plugins.
+pub type WallClock = Arc<dyn Fn() -> SystemTime + Send + Sync>;
+
+/// Environment provided to plugins through the functions they import from the host.
+#[derive(Clone)]
+pub struct PluginHost {
+    pub clock: WallClock,
+    /// Seed of the random source of the plugin, or `None` for a different
+    /// sequence every time.
+    pub seed: Option<u64>,
+}
+
+impl Default for PluginHost {
+    fn default() -> Self {
+        Self {
+            clock: Arc::new(SystemTime::now),
+            seed: None,
+        }
+    }
+}
+
 struct State {
     ctx: WasiCtx,
     table: ResourceTable,
+    limiter: Limiter,
+    plugin_id: String,
+    clock: WallClock,
+    rng: StdRng,
+}
+
+impl guest::animation::host::Host for State {
+    async fn log(&mut self, level: LogLevel, message: String) {
+        let level = match level {
+            LogLevel::Error => log::Level::Error,
+            LogLevel::Warn => log::Level::Warn,
+            LogLevel::Info => log::Level::Info,
+            LogLevel::Debug => log::Level::Debug,
+            LogLevel::Trace => log::Level::Trace,
+        };
+        log::log!(level, "[{}] {message}", self.plugin_id);
+    }
+
+    async fn now(&mut self) -> u64 {
+        (self.clock)()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64
+    }
+
+    async fn random(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
 }
 
 impl WasiView for State {
@@ -45,62 +271,212 @@ pub enum AnimationPluginError {
 
     #[error("bundle error: {0}")]
     BundleError(#[from] PluginUnwrapError),
+
+    #[error("plugin exceeded its budget: {0}")]
+    BudgetExceeded(String),
+
+    #[error("unsupported plugin API version: {0}")]
+    UnsupportedApiVersion(PluginApiVersion),
 }
 type Result<T> = std::result::Result<T, AnimationPluginError>;
 
+/// Converts an error returned by a call into the plugin, recognizing traps
+/// caused by exceeding the plugin budget.
+fn call_error(
+    error: wasmtime::Error,
+    store: &Store<State>,
+    limits: &PluginLimits,
+) -> AnimationPluginError {
+    if error.downcast_ref::<Trap>() == Some(&Trap::Interrupt) {
+        AnimationPluginError::BudgetExceeded(format!(
+            "call took longer than {} ms",
+            limits.call_timeout_ms
+        ))
+    } else if store.data().limiter.exceeded {
+        AnimationPluginError::BudgetExceeded(format!(
+            "memory usage exceeded {} MB",
+            limits.max_memory_mb
+        ))
+    } else {
+        error.into()
+    }
+}
+
+/// Bindings of the WIT world matching the API version of the plugin.
+enum Bindings {
+    V0_9(v0_9::Animation),
+    V0_10(v0_10::Animation),
+    V0_11(Animation),
+}
+
+/// Makes the same call into the plugin with the bindings of any API version.
+/// Each version generates its own types, so `animation` is the animation
+/// interface of the bindings, and the optional `conversions` the module
+/// converting values to and from the WIT types of the version. Errors are converted with
+/// `call_error`.
+macro_rules! call_plugin {
+    ($self:ident, |$store:ident, $animation:ident $(, $conversions:ident)?| $call:expr) => {{
+        let mut $store = $self.lock_store().await;
+        let result = match &$self.bindings {
+            Bindings::V0_9(bindings) => {
+                $(use v0_9::conversions as $conversions;)?
+                let $animation = bindings.guest_animation_plugin().animation();
+                $call
+            }
+            Bindings::V0_10(bindings) => {
+                $(use v0_10::conversions as $conversions;)?
+                let $animation = bindings.guest_animation_plugin().animation();
+                $call
+            }
+            Bindings::V0_11(bindings) => {
+                $(use conversions as $conversions;)?
+                let $animation = bindings.guest_animation_plugin().animation();
+                $call
+            }
+        };
+        result.map_err(|e| call_error(e, &$store, &$self.limits))
+    }};
+}
+
 pub struct AnimationPlugin {
     store: Mutex<Store<State>>,
-    bindings: Animation,
+    bindings: Bindings,
     handle: ResourceAny,
     manifest: PluginManifest,
+    limits: PluginLimits,
+    /// Assets unpacked for the plugin, removed when the plugin is dropped.
+    _assets: Option<Arc<TempDir>>,
 }
 
 impl AnimationPlugin {
-    pub async fn new(executable_path: &Path, points: Vec<(f64, f64, f64)>) -> Result<Self> {
+    /// Versions of the plugin API the host can run plugins for.
+    pub const SUPPORTED_API_VERSIONS: &[PluginApiVersion] = &[
+        PluginApiVersion::V0_9,
+        PluginApiVersion::V0_10,
+        PluginApiVersion::V0_11,
+    ];
+
+    pub fn supports(api_version: PluginApiVersion) -> bool {
+        Self::SUPPORTED_API_VERSIONS.contains(&api_version)
+    }
+
+    pub async fn new(
+        executable_path: &Path,
+        points: Vec<Point>,
+        limits: PluginLimits,
+        host: PluginHost,
+    ) -> Result<Self> {
         let manifest = unwrap::unwrap_plugin(executable_path)?;
 
         let mut reader = unwrap::reader_from_crab(executable_path)?;
         let mut data = Vec::new();
         reader.read_to_end(&mut data)?;
 
-        let mut config = Config::new();
-        config.async_support(true);
-        let engine = Engine::new(&config)?;
+        let assets = unpack_assets(executable_path)?;
+        let mut ctx = WasiCtxBuilder::new();
+        if let Some(assets) = &assets {
+            ctx.preopened_dir(
+                assets.path().join(ASSETS_DIR),
+                crate::GUEST_ASSETS_DIR,
+                DirPerms::READ,
+                FilePerms::READ,
+            )?;
+        }
+
+        let engine = shared_engine()?;
         let component = Component::from_binary(&engine, &data)?;
 
         let mut linker = Linker::new(&engine);
         wasmtime_wasi::p2::add_to_linker_async(&mut linker)?;
+        Animation::add_to_linker::<_, HasSelf<_>>(&mut linker, |state| state)?;
 
         let mut store = Store::new(
             &engine,
             State {
-                ctx: WasiCtxBuilder::new().build(),
+                ctx: ctx.build(),
                 table: ResourceTable::new(),
+                limiter: Limiter {
+                    limits: StoreLimitsBuilder::new()
+                        .memory_size(limits.max_memory_mb * 1024 * 1024)
+                        .build(),
+                    exceeded: false,
+                },
+                plugin_id: manifest.id.clone(),
+                clock: host.clock,
+                rng: match host.seed {
+                    Some(seed) => StdRng::seed_from_u64(seed),
+                    None => StdRng::from_os_rng(),
+                },
             },
         );
+        store.limiter(|state| &mut state.limiter);
+        store.set_epoch_deadline(limits.deadline_ticks());
 
-        let bindings = Animation::instantiate_async(&mut store, &component, &linker)
-            .await
-            .unwrap();
-        let guest = bindings.guest_animation_plugin();
-        let animation = guest.animation();
-        let points = points
-            .into_iter()
-            .map(|(x, y, z)| Position { x, y, z })
-            .collect_vec();
-        let handle = animation
-            .call_constructor(&mut store, &points)
-            .await
-            .unwrap();
+        let (bindings, handle) = match manifest.api_version {
+            PluginApiVersion::V0_9 => {
+                let bindings = v0_9::Animation::instantiate_async(&mut store, &component, &linker)
+                    .await
+                    .map_err(|e| call_error(e, &store, &limits))?;
+                let points = v0_9::conversions::points_to_wit(points);
+                store.set_epoch_deadline(limits.deadline_ticks());
+                let handle = bindings
+                    .guest_animation_plugin()
+                    .animation()
+                    .call_constructor(&mut store, &points)
+                    .await
+                    .map_err(|e| call_error(e, &store, &limits))?;
+                (Bindings::V0_9(bindings), handle)
+            }
+            PluginApiVersion::V0_10 => {
+                let bindings = v0_10::Animation::instantiate_async(&mut store, &component, &linker)
+                    .await
+                    .map_err(|e| call_error(e, &store, &limits))?;
+                let points = v0_10::conversions::points_to_wit(points);
+                store.set_epoch_deadline(limits.deadline_ticks());
+                let handle = bindings
+                    .guest_animation_plugin()
+                    .animation()
+                    .call_constructor(&mut store, &points)
+                    .await
+                    .map_err(|e| call_error(e, &store, &limits))?;
+                (Bindings::V0_10(bindings), handle)
+            }
+            PluginApiVersion::V0_11 => {
+                let bindings = Animation::instantiate_async(&mut store, &component, &linker)
+                    .await
+                    .map_err(|e| call_error(e, &store, &limits))?;
+                let points = points.into_iter().map(Into::into).collect_vec();
+                store.set_epoch_deadline(limits.deadline_ticks());
+                let handle = bindings
+                    .guest_animation_plugin()
+                    .animation()
+                    .call_with_points(&mut store, &points)
+                    .await
+                    .map_err(|e| call_error(e, &store, &limits))?;
+                (Bindings::V0_11(bindings), handle)
+            }
+            version => return Err(AnimationPluginError::UnsupportedApiVersion(version)),
+        };
 
         Ok(Self {
             store: Mutex::new(store),
             bindings,
             handle,
             manifest,
+            limits,
+            _assets: assets,
         })
     }
 
+    /// Locks the store for a single call into the plugin, setting the deadline for the call.
+    /// Locks the store for a new call into the plugin.
+    async fn lock_store(&self) -> MutexGuard<'_, Store<State>> {
+        let mut store = self.store.lock().await;
+        store.set_epoch_deadline(self.limits.deadline_ticks());
+        store.data_mut().limiter.exceeded = false;
+        store
+    }
+
     pub fn manifest(&self) -> &PluginManifest {
         &self.manifest
     }
@@ -115,89 +491,208 @@ impl AnimationPlugin {
     }
 
     pub async fn update(&self, time_delta: f64) -> Result<()> {
-        let mut store = self.store.lock().await;
-        self.bindings
-            .guest_animation_plugin()
-            .animation()
-            .call_update(store.as_context_mut(), self.handle, time_delta)
-            .await
-            .map_err(Into::into)
+        call_plugin!(self, |store, animation| {
+            animation
+                .call_update(store.as_context_mut(), self.handle, time_delta)
+                .await
+        })
     }
 
     pub async fn render(&self) -> Result<lightfx::Frame> {
-        let mut store = self.store.lock().await;
-        self.bindings
+        call_plugin!(self, |store, animation| {
+            animation
+                .call_render(store.as_context_mut(), self.handle)
+                .await
+                .map(|pixels| pixels.into_iter().map(Into::into).collect())
+        })
+    }
+
+    /// Returns the state the plugin wants preserved across restarts, if any.
+    /// Plugins built against API versions before 0.11 have no state.
+    pub async fn save_state(&self) -> Result<Option<Vec<u8>>> {
+        let Bindings::V0_11(bindings) = &self.bindings else {
+            return Ok(None);
+        };
+        let mut store = self.lock_store().await;
+        bindings
             .guest_animation_plugin()
             .animation()
-            .call_render(store.as_context_mut(), self.handle)
+            .call_save_state(store.as_context_mut(), self.handle)
             .await
-            .map_err(Into::into)
-            .map(|pixels| {
-                pixels
-                    .into_iter()
-                    .map(|c| lightfx::Color::rgb(c.r, c.g, c.b))
-                    .collect()
-            })
+            .map_err(|e| call_error(e, &store, &self.limits))
     }
 
-    pub async fn get_schema(&self) -> Result<schema::ConfigurationSchema> {
-        let mut store = self.store.lock().await;
-        let schema = self
-            .bindings
+    /// Restores state previously returned by `save_state`.
+    pub async fn load_state(&self, state: &[u8]) -> Result<()> {
+        let Bindings::V0_11(bindings) = &self.bindings else {
+            return Ok(());
+        };
+        let mut store = self.lock_store().await;
+        bindings
             .guest_animation_plugin()
             .animation()
-            .call_get_schema(store.as_context_mut(), self.handle)
-            .await?;
+            .call_load_state(store.as_context_mut(), self.handle, state)
+            .await
+            .map_err(|e| call_error(e, &store, &self.limits))
+    }
 
-        Ok(serde_json::from_str(&schema).unwrap())
+    pub async fn get_schema(&self) -> Result<schema::ConfigurationSchema> {
+        call_plugin!(self, |store, animation, conversions| {
+            animation
+                .call_get_schema(store.as_context_mut(), self.handle)
+                .await
+                .map(conversions::schema_from_wit)
+        })
     }
 
     pub async fn set_parameters(
         &mut self,
         values: &HashMap<String, schema::ParameterValue>,
     ) -> Result<()> {
-        let mut store = self.store.lock().await;
-        let values = serde_json::to_string(values).unwrap();
-
-        self.bindings
-            .guest_animation_plugin()
-            .animation()
-            .call_set_parameters(store.as_context_mut(), self.handle, &values)
-            .await
-            .map_err(Into::into)
+        call_plugin!(self, |store, animation, conversions| {
+            let values = conversions::values_to_wit(values.clone());
+            animation
+                .call_set_parameters(store.as_context_mut(), self.handle, &values)
+                .await
+        })
     }
 
+    /// Values of texts are told apart from enum options by the schema, as
+    /// plugins before API version 0.11 return both as enum options.
     pub async fn get_parameters(&self) -> Result<HashMap<String, schema::ParameterValue>> {
-        let mut store = self.store.lock().await;
-        let values = self
-            .bindings
-            .guest_animation_plugin()
-            .animation()
-            .call_get_parameters(store.as_context_mut(), self.handle)
-            .await?;
-
-        Ok(serde_json::from_str(&values).unwrap_or_default())
+        let values = call_plugin!(self, |store, animation, conversions| {
+            animation
+                .call_get_parameters(store.as_context_mut(), self.handle)
+                .await
+                .map(conversions::values_from_wit)
+        })?;
+        Ok(self.get_schema().await?.typed_values(values))
     }
 
     pub async fn get_fps(&self) -> Result<f64> {
-        let mut store = self.store.lock().await;
-        self.bindings
-            .guest_animation_plugin()
-            .animation()
-            .call_get_fps(store.as_context_mut(), self.handle)
-            .await
-            .map_err(Into::into)
+        call_plugin!(self, |store, animation| {
+            animation
+                .call_get_fps(store.as_context_mut(), self.handle)
+                .await
+        })
     }
 
     pub async fn send_event(&self, event: Event) -> Result<()> {
-        let mut store = self.store.lock().await;
-        let event = serde_json::to_string(&event).unwrap();
+        call_plugin!(self, |store, animation, conversions| {
+            let Some(event) = conversions::event_to_wit(event) else {
+                return Ok(());
+            };
+            animation
+                .call_on_event(store.as_context_mut(), self.handle, &event)
+                .await
+        })
+    }
+}
 
-        self.bindings
-            .guest_animation_plugin()
-            .animation()
-            .call_on_event(store.as_context_mut(), self.handle, &event)
-            .await
-            .map_err(Into::into)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestPlugin;
+
+    async fn start(plugin: TestPlugin) -> Result<AnimationPlugin> {
+        let dir = tempfile::tempdir().unwrap();
+        let points = vec![Point::from((0.0, 0.0, 0.0)), Point::from((1.0, 0.0, 0.0))];
+        AnimationPlugin::new(
+            &plugin.write_crab(dir.path()),
+            points,
+            PluginLimits::default(),
+            PluginHost::default(),
+        )
+        .await
+    }
+
+    /// Renders a frame, returning the number of points the plugin was created with.
+    async fn point_count(plugin: &AnimationPlugin) -> u8 {
+        let frame = plugin.render().await.unwrap();
+        frame.pixels_iter().next().unwrap().g
+    }
+
+    #[test]
+    fn assets_are_unpacked_once_per_plugin_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let assets = dir.path().join("data");
+        std::fs::create_dir(&assets).unwrap();
+        std::fs::write(assets.join("image.png"), b"png").unwrap();
+        std::fs::write(dir.path().join("manifest.json"), b"{}").unwrap();
+        std::fs::write(dir.path().join("plugin.wasm"), b"wasm").unwrap();
+        let wrap = |assets| {
+            let path = dir.path().join("plugin.crab");
+            animation_wrapper::wrap::wrap_plugin(
+                &path,
+                dir.path().join("plugin.wasm"),
+                dir.path().join("manifest.json"),
+                assets,
+            )
+            .unwrap();
+            path
+        };
+
+        let path = wrap(Some(&assets));
+        let first = unpack_assets(&path).unwrap().unwrap();
+        let second = unpack_assets(&path).unwrap().unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(first.path().join(ASSETS_DIR).join("image.png").exists());
+
+        // A rewritten file is unpacked again, however soon it is rewritten
+        std::fs::write(assets.join("image.png"), b"new png").unwrap();
+        let path = wrap(Some(&assets));
+        let third = unpack_assets(&path).unwrap().unwrap();
+        assert!(!Arc::ptr_eq(&first, &third));
+        assert_eq!(
+            std::fs::read(third.path().join(ASSETS_DIR).join("image.png")).unwrap(),
+            b"new png"
+        );
+
+        let unpacked = first.path().to_owned();
+        drop((first, second));
+        assert!(!unpacked.exists());
+
+        let path = wrap(None);
+        assert!(unpack_assets(&path).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn bindings_match_api_version_of_plugin() {
+        let plugin = start(TestPlugin {
+            api_version: PluginApiVersion::V0_9,
+            ..TestPlugin::new("old")
+        })
+        .await
+        .unwrap();
+        assert!(matches!(plugin.bindings, Bindings::V0_9(_)));
+        assert_eq!(point_count(&plugin).await, 2);
+        assert_eq!(plugin.save_state().await.unwrap(), None);
+
+        let plugin = start(TestPlugin {
+            api_version: PluginApiVersion::V0_10,
+            ..TestPlugin::new("previous")
+        })
+        .await
+        .unwrap();
+        assert!(matches!(plugin.bindings, Bindings::V0_10(_)));
+        assert_eq!(point_count(&plugin).await, 2);
+        assert_eq!(plugin.save_state().await.unwrap(), None);
+
+        let plugin = start(TestPlugin::new("current")).await.unwrap();
+        assert!(matches!(plugin.bindings, Bindings::V0_11(_)));
+        assert_eq!(point_count(&plugin).await, 2);
+        assert!(plugin.save_state().await.unwrap().is_some());
+
+        let result = start(TestPlugin {
+            api_version: PluginApiVersion::Unknown,
+            ..TestPlugin::new("future")
+        })
+        .await;
+        assert!(matches!(
+            result,
+            Err(AnimationPluginError::UnsupportedApiVersion(
+                PluginApiVersion::Unknown
+            ))
+        ));
     }
 }
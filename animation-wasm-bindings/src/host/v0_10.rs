@@ -0,0 +1,147 @@
+//! Bindings for plugins built against version 0.10 of the plugin API, which
+//! knows fewer kinds of parameters and creates animations from positions only.
+
+wasmtime::component::bindgen!({
+    path: "wit/v0_10",
+    world: "animation",
+    async: true,
+});
+
+pub(super) mod conversions {
+    use std::collections::HashMap;
+
+    use animation_api::{event::Event, point::Point, schema};
+
+    use super::exports::guest::animation::plugin as wit;
+
+    impl From<wit::Color> for lightfx::Color {
+        fn from(color: wit::Color) -> Self {
+            lightfx::Color::rgb(color.r, color.g, color.b)
+        }
+    }
+
+    impl From<lightfx::Color> for wit::Color {
+        fn from(color: lightfx::Color) -> Self {
+            Self {
+                r: color.r,
+                g: color.g,
+                b: color.b,
+            }
+        }
+    }
+
+    impl From<wit::EnumOption> for schema::EnumOption {
+        fn from(option: wit::EnumOption) -> Self {
+            Self {
+                name: option.name,
+                description: option.description,
+                value: option.value,
+            }
+        }
+    }
+
+    impl From<wit::ValueSchema> for schema::ValueSchema {
+        fn from(value: wit::ValueSchema) -> Self {
+            match value {
+                wit::ValueSchema::Number(wit::NumberSchema { min, max, step }) => {
+                    Self::Number { min, max, step }
+                }
+                wit::ValueSchema::Color => Self::Color,
+                wit::ValueSchema::Enum(values) => Self::Enum {
+                    values: values.into_iter().map(Into::into).collect(),
+                },
+                wit::ValueSchema::Speed => Self::Speed,
+                wit::ValueSchema::Percentage => Self::Percentage,
+            }
+        }
+    }
+
+    impl From<wit::ParameterSchema> for schema::ParameterSchema {
+        fn from(parameter: wit::ParameterSchema) -> Self {
+            Self {
+                id: parameter.id,
+                name: parameter.name,
+                description: parameter.description,
+                group: None,
+                visible_when: None,
+                value: parameter.value.into(),
+            }
+        }
+    }
+
+    pub(in crate::host) fn points_to_wit(points: Vec<Point>) -> Vec<wit::Position> {
+        points
+            .into_iter()
+            .map(|p| wit::Position {
+                x: p.x,
+                y: p.y,
+                z: p.z,
+            })
+            .collect()
+    }
+
+    pub(in crate::host) fn schema_from_wit(
+        schema: wit::ConfigurationSchema,
+    ) -> schema::ConfigurationSchema {
+        schema::ConfigurationSchema {
+            parameters: schema.parameters.into_iter().map(Into::into).collect(),
+            custom_triggers: schema.custom_triggers.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Skips values of kinds of parameters the plugin does not know.
+    pub(in crate::host) fn values_to_wit(
+        values: HashMap<String, schema::ParameterValue>,
+    ) -> Vec<(String, wit::ParameterValue)> {
+        values
+            .into_iter()
+            .filter_map(|(id, value)| {
+                let value = match value {
+                    schema::ParameterValue::Number(n) => wit::ParameterValue::Number(n),
+                    schema::ParameterValue::Color(c) => wit::ParameterValue::Color(c.into()),
+                    schema::ParameterValue::EnumOption(o) | schema::ParameterValue::Text(o) => {
+                        wit::ParameterValue::EnumOption(o)
+                    }
+                    _ => return None,
+                };
+                Some((id, value))
+            })
+            .collect()
+    }
+
+    pub(in crate::host) fn values_from_wit(
+        values: Vec<(String, wit::ParameterValue)>,
+    ) -> HashMap<String, schema::ParameterValue> {
+        values
+            .into_iter()
+            .map(|(id, value)| {
+                let value = match value {
+                    wit::ParameterValue::Number(n) => schema::ParameterValue::Number(n),
+                    wit::ParameterValue::Color(c) => schema::ParameterValue::Color(c.into()),
+                    wit::ParameterValue::EnumOption(o) => schema::ParameterValue::EnumOption(o),
+                };
+                (id, value)
+            })
+            .collect()
+    }
+
+    /// Returns `None` for kinds of events the plugin does not know.
+    pub(in crate::host) fn event_to_wit(event: Event) -> Option<wit::Event> {
+        Some(match event {
+            Event::BeatEvent { bpm } => wit::Event::Beat(bpm),
+            Event::FftEvent { bands, wave } => wit::Event::Fft(wit::FftEvent { bands, wave }),
+            Event::MidiEvent(message) => wit::Event::Midi(message.to_midi()),
+            Event::CustomTrigger { trigger_id } => wit::Event::CustomTrigger(trigger_id),
+            Event::MouseMove {
+                ray_origin: [ox, oy, oz],
+                ray_direction: [dx, dy, dz],
+            } => wit::Event::MouseMove(wit::MouseMoveEvent {
+                ray_origin: (ox, oy, oz),
+                ray_direction: (dx, dy, dz),
+            }),
+            Event::MouseUp => wit::Event::MouseUp,
+            Event::MouseDown => wit::Event::MouseDown,
+            _ => return None,
+        })
+    }
+}
@@ -0,0 +1,53 @@
+//! Bindings for plugins built against version 0.9 of the plugin API, which
+//! passes schemas, parameters and events as JSON strings.
+
+wasmtime::component::bindgen!({
+    path: "wit/v0_9",
+    world: "animation",
+    async: true,
+});
+
+pub(super) mod conversions {
+    use std::collections::HashMap;
+
+    use animation_api::{event::Event, point::Point, schema};
+
+    use super::exports::guest::animation::plugin as wit;
+
+    impl From<wit::Color> for lightfx::Color {
+        fn from(color: wit::Color) -> Self {
+            lightfx::Color::rgb(color.r, color.g, color.b)
+        }
+    }
+
+    pub(in crate::host) fn points_to_wit(points: Vec<Point>) -> Vec<wit::Position> {
+        points
+            .into_iter()
+            .map(|p| wit::Position {
+                x: p.x,
+                y: p.y,
+                z: p.z,
+            })
+            .collect()
+    }
+
+    pub(in crate::host) fn schema_from_wit(schema: String) -> schema::ConfigurationSchema {
+        serde_json::from_str(&schema).unwrap_or_default()
+    }
+
+    pub(in crate::host) fn values_to_wit(
+        values: HashMap<String, schema::ParameterValue>,
+    ) -> String {
+        serde_json::to_string(&values).unwrap()
+    }
+
+    pub(in crate::host) fn values_from_wit(
+        values: String,
+    ) -> HashMap<String, schema::ParameterValue> {
+        serde_json::from_str(&values).unwrap_or_default()
+    }
+
+    pub(in crate::host) fn event_to_wit(event: Event) -> Option<String> {
+        serde_json::to_string(&event).ok()
+    }
+}
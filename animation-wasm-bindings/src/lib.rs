@@ -1,5 +1,13 @@
+mod conversions;
+
+/// Directory under which plugins find the assets bundled with them.
+pub const GUEST_ASSETS_DIR: &str = "/assets";
+
 #[cfg(feature = "guest")]
 pub mod guest;
 
 #[cfg(feature = "host")]
 pub mod host;
+
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
@@ -0,0 +1,230 @@
+//! Minimal animation plugins for tests, built without a WebAssembly toolchain.
+//!
+//! Rendered colors encode the state of the plugin: red is the animation time
+//! in tenths of a second (wrapping at 256), green is the number of points and
+//! blue is chosen by the test to tell plugins apart.
+
+use std::path::{Path, PathBuf};
+
+use animation_api::plugin_config::{PluginApiVersion, PluginManifest};
+use wit_component::{ComponentEncoder, StringEncoding, embed_component_metadata};
+use wit_parser::Resolve;
+
+pub struct TestPlugin {
+    pub id: String,
+    pub api_version: PluginApiVersion,
+    pub fps: f64,
+    /// Blue component of all rendered colors
+    pub blue: u8,
+    /// Makes every render call trap
+    pub failing: bool,
+}
+
+impl TestPlugin {
+    pub fn new(id: &str) -> Self {
+        Self {
+            id: id.to_owned(),
+            api_version: PluginApiVersion::CURRENT,
+            fps: 30.0,
+            blue: 0,
+            failing: false,
+        }
+    }
+
+    /// Builds the plugin component for the WIT world of its API version.
+    pub fn component(&self) -> Vec<u8> {
+        let (wit, exports) = match self.api_version {
+            PluginApiVersion::V0_9 => (
+                include_str!("../wit/v0_9/animation.wit"),
+                V0_9_EXPORTS.to_owned(),
+            ),
+            PluginApiVersion::V0_10 => (
+                include_str!("../wit/v0_10/animation.wit"),
+                V0_10_EXPORTS.to_owned(),
+            ),
+            _ => (
+                include_str!("../wit/animation.wit"),
+                format!("{V0_10_EXPORTS}{V0_11_EXPORTS}"),
+            ),
+        };
+        let mut resolve = Resolve::default();
+        let package = resolve
+            .push_str("animation.wit", wit)
+            .expect("WIT should parse");
+        let world = resolve
+            .select_world(package, Some("animation"))
+            .expect("WIT should have the animation world");
+
+        let mut module = wat::parse_str(self.wat(&exports)).expect("test plugin should be valid");
+        embed_component_metadata(&mut module, &resolve, world, StringEncoding::UTF8)
+            .expect("metadata should match the world");
+        ComponentEncoder::default()
+            .module(&module)
+            .and_then(|encoder| encoder.validate(true).encode())
+            .expect("test plugin should implement the world")
+    }
+
+    /// Writes the plugin as a CRAB file to the directory, returning its path.
+    pub fn write_crab(&self, dir: &Path) -> PathBuf {
+        let manifest = PluginManifest {
+            id: self.id.clone(),
+            display_name: self.id.clone(),
+            author: "Test".to_owned(),
+            api_version: self.api_version,
+            version: "1.0".to_owned(),
+            tags: Vec::new(),
+        };
+        let manifest_path = dir.join(format!("{}.json", self.id));
+        let wasm_path = dir.join(format!("{}.wasm", self.id));
+        let crab_path = dir.join(format!("{}.crab", self.id));
+        std::fs::write(&manifest_path, serde_json::to_vec(&manifest).unwrap()).unwrap();
+        std::fs::write(&wasm_path, self.component()).unwrap();
+        animation_wrapper::wrap::wrap_plugin(&crab_path, &wasm_path, &manifest_path, None).unwrap();
+        std::fs::remove_file(manifest_path).unwrap();
+        std::fs::remove_file(wasm_path).unwrap();
+        crab_path
+    }
+
+    fn wat(&self, exports: &str) -> String {
+        let fps = self.fps;
+        let blue = self.blue;
+        let trap = if self.failing { "unreachable" } else { "" };
+        format!(
+            r#"(module
+  (import "[export]guest:animation/plugin" "[resource-new]animation"
+    (func $resource_new (param i32) (result i32)))
+  (memory (export "memory") 1)
+  (global $time (mut f64) (f64.const 0))
+  (global $count (mut i32) (i32.const 0))
+  (global $heap (mut i32) (i32.const 1024))
+
+  (func $realloc (export "cabi_realloc")
+    (param $old i32) (param $old_size i32) (param $align i32) (param $size i32) (result i32)
+    (local $ptr i32) (local $end i32)
+    (local.set $ptr
+      (i32.and
+        (i32.add (global.get $heap) (i32.sub (local.get $align) (i32.const 1)))
+        (i32.sub (i32.const 0) (local.get $align))))
+    (local.set $end (i32.add (local.get $ptr) (local.get $size)))
+    (if (i32.gt_u (local.get $end) (i32.mul (memory.size) (i32.const 65536)))
+      (then
+        (if (i32.eq
+              (memory.grow
+                (i32.sub
+                  (i32.div_u (i32.add (local.get $end) (i32.const 65535)) (i32.const 65536))
+                  (memory.size)))
+              (i32.const -1))
+          (then unreachable))))
+    (global.set $heap (local.get $end))
+    (local.get $ptr))
+
+  ;; Lists passed to the plugin and returned by it are only read during or
+  ;; right after a call, so the heap can be reused by the next one.
+  (func $reset
+    (global.set $heap (i32.const 1024)))
+
+  (func $construct (param $len i32) (result i32)
+    (global.set $count (local.get $len))
+    (call $reset)
+    (call $resource_new (i32.const 1)))
+
+  (func (export "guest:animation/plugin#[constructor]animation")
+    (param $ptr i32) (param $len i32) (result i32)
+    (call $construct (local.get $len)))
+
+  (func (export "guest:animation/plugin#[method]animation.update")
+    (param $self i32) (param $delta f64)
+    (global.set $time (f64.add (global.get $time) (local.get $delta))))
+
+  (func (export "guest:animation/plugin#[method]animation.render")
+    (param $self i32) (result i32)
+    (local $ret i32) (local $pixels i32) (local $pixel i32) (local $i i32)
+    {trap}
+    (call $reset)
+    (local.set $ret (call $realloc (i32.const 0) (i32.const 0) (i32.const 4) (i32.const 8)))
+    (local.set $pixels
+      (call $realloc
+        (i32.const 0) (i32.const 0) (i32.const 1)
+        (i32.mul (global.get $count) (i32.const 3))))
+    (block $done
+      (loop $next
+        (br_if $done (i32.ge_u (local.get $i) (global.get $count)))
+        (local.set $pixel (i32.add (local.get $pixels) (i32.mul (local.get $i) (i32.const 3))))
+        (i32.store8 (local.get $pixel)
+          (i32.trunc_sat_f64_u (f64.mul (global.get $time) (f64.const 10))))
+        (i32.store8 offset=1 (local.get $pixel) (global.get $count))
+        (i32.store8 offset=2 (local.get $pixel) (i32.const {blue}))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $next)))
+    (i32.store (local.get $ret) (local.get $pixels))
+    (i32.store offset=4 (local.get $ret) (global.get $count))
+    (local.get $ret))
+
+  ;; Empty schema, parameters or strings, depending on the API version
+  (func $empty (result i32)
+    (local $ret i32)
+    (call $reset)
+    (local.set $ret (call $realloc (i32.const 0) (i32.const 0) (i32.const 4) (i32.const 16)))
+    (i64.store (local.get $ret) (i64.const 0))
+    (i64.store offset=8 (local.get $ret) (i64.const 0))
+    (local.get $ret))
+
+  (func (export "guest:animation/plugin#[method]animation.get-schema")
+    (param $self i32) (result i32)
+    (call $empty))
+
+  (func (export "guest:animation/plugin#[method]animation.get-parameters")
+    (param $self i32) (result i32)
+    (call $empty))
+
+  (func (export "guest:animation/plugin#[method]animation.set-parameters")
+    (param $self i32) (param $ptr i32) (param $len i32)
+    (call $reset))
+
+  (func (export "guest:animation/plugin#[method]animation.get-fps")
+    (param $self i32) (result f64)
+    (f64.const {fps}))
+
+{exports})
+"#
+        )
+    }
+}
+
+const V0_9_EXPORTS: &str = r#"
+  (func (export "guest:animation/plugin#[method]animation.on-event")
+    (param $self i32) (param $ptr i32) (param $len i32)
+    (call $reset))
+"#;
+
+const V0_10_EXPORTS: &str = r#"
+  (func (export "guest:animation/plugin#[method]animation.on-event")
+    (param i32 i32 i64 i32 i32 i32 f32 f32)
+    (call $reset))
+"#;
+
+/// Exports added in version 0.11, on top of those of version 0.10
+const V0_11_EXPORTS: &str = r#"
+  (func (export "guest:animation/plugin#[static]animation.with-points")
+    (param $ptr i32) (param $len i32) (result i32)
+    (call $construct (local.get $len)))
+
+  ;; The state is the animation time
+  (func (export "guest:animation/plugin#[method]animation.save-state")
+    (param $self i32) (result i32)
+    (local $ret i32) (local $data i32)
+    (call $reset)
+    (local.set $ret (call $realloc (i32.const 0) (i32.const 0) (i32.const 4) (i32.const 12)))
+    (local.set $data (call $realloc (i32.const 0) (i32.const 0) (i32.const 1) (i32.const 8)))
+    (f64.store (local.get $data) (global.get $time))
+    (i32.store8 (local.get $ret) (i32.const 1))
+    (i32.store offset=4 (local.get $ret) (local.get $data))
+    (i32.store offset=8 (local.get $ret) (i32.const 8))
+    (local.get $ret))
+
+  (func (export "guest:animation/plugin#[method]animation.load-state")
+    (param $self i32) (param $ptr i32) (param $len i32)
+    (if (i32.eq (local.get $len) (i32.const 8))
+      (then (global.set $time (f64.load (local.get $ptr)))))
+    (call $reset))
+"#;
@@ -1,10 +1,12 @@
 use std::{
-    fs::{File, read_dir},
-    io::{BufReader, Read},
-    path::PathBuf,
+    fs::{File, OpenOptions, read_dir, read_to_string},
+    io::{BufReader, Read, Write},
+    path::{Path, PathBuf},
     process::{Command, ExitStatus},
 };
 
+use animation_wrapper::sign;
+
 fn find_project_name() -> std::io::Result<String> {
     #[derive(serde::Deserialize)]
     struct Package {
@@ -64,6 +66,11 @@ fn find_manifest() -> std::io::Result<PathBuf> {
     }
 }
 
+fn find_assets() -> std::io::Result<Option<PathBuf>> {
+    let assets_path = std::env::current_dir()?.join(animation_wrapper::ASSETS_DIR);
+    Ok(assets_path.is_dir().then_some(assets_path))
+}
+
 fn find_animation_executable() -> std::io::Result<PathBuf> {
     let project_root = find_project_root()?;
     let project_name = find_project_name()?.replace('-', "_");
@@ -111,17 +118,79 @@ fn find_project_root() -> std::io::Result<PathBuf> {
         })
 }
 
-fn main() -> std::io::Result<()> {
+fn wrap() -> std::io::Result<()> {
     let manifest_path = find_manifest()?;
 
     build_plugin()?;
 
     let executable_path = find_animation_executable()?;
     let output_path = get_output_path()?;
+    let assets_path = find_assets()?;
 
-    animation_wrapper::wrap::wrap_plugin(&output_path, &executable_path, &manifest_path)?;
+    animation_wrapper::wrap::wrap_plugin(
+        &output_path,
+        &executable_path,
+        &manifest_path,
+        assets_path.as_deref(),
+    )?;
 
     println!("Plugin ready at {}", output_path.to_string_lossy());
 
     Ok(())
 }
+
+/// Generates a key pair for signing plugins, storing the private key in the
+/// given file and printing the public key, to be trusted by servers.
+fn keygen(key_path: &Path) -> std::io::Result<()> {
+    let key = sign::generate_key();
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    // Readable only by the owner, from the moment it is created
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(key_path)?
+        .write_all(sign::signing_key_to_hex(&key).as_bytes())?;
+
+    println!("Private key written to {}", key_path.to_string_lossy());
+    println!(
+        "Public key: {}",
+        sign::verifying_key_to_hex(&key.verifying_key())
+    );
+
+    Ok(())
+}
+
+fn sign(key_path: &Path, plugin_path: Option<PathBuf>) -> std::io::Result<()> {
+    let key =
+        sign::signing_key_from_hex(&read_to_string(key_path)?).map_err(std::io::Error::other)?;
+    let plugin_path = match plugin_path {
+        Some(path) => path,
+        None => get_output_path()?,
+    };
+
+    sign::sign_plugin(&plugin_path, &key).map_err(std::io::Error::other)?;
+
+    println!("Plugin at {} signed", plugin_path.to_string_lossy());
+
+    Ok(())
+}
+
+fn main() -> std::io::Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [] => wrap(),
+        ["keygen", key_path] => keygen(Path::new(key_path)),
+        ["sign", key_path] => sign(Path::new(key_path), None),
+        ["sign", key_path, plugin_path] => sign(Path::new(key_path), Some(plugin_path.into())),
+        _ => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Usage: crabwrap [keygen <key file> | sign <key file> [<plugin.crab>]]",
+        )),
+    }
+}
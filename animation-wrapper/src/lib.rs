@@ -4,6 +4,12 @@ pub mod wrap;
 #[cfg(feature = "unwrap")]
 pub mod unwrap;
 
+#[cfg(feature = "sign")]
+pub mod sign;
+
+/// Directory of the CRAB file holding data files of the plugin.
+pub const ASSETS_DIR: &str = "assets";
+
 #[derive(Debug, thiserror::Error)]
 pub enum PluginConfigError {
     #[error("Failed to parse manifest: {reason}")]
@@ -0,0 +1,248 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use ed25519_dalek::{SIGNATURE_LENGTH, Signature, Signer};
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+use sha2::{Digest, Sha512};
+use tar::{Archive, Header};
+use tempfile::NamedTempFile;
+
+/// Entry of the CRAB file holding the signature of all other entries.
+const SIGNATURE_ENTRY: &str = "signature";
+
+#[derive(Debug, thiserror::Error)]
+pub enum PluginSignatureError {
+    #[error("Cannot access plugin file: {0}")]
+    CannotOpenFile(#[from] std::io::Error),
+
+    #[error("CRAB is not signed")]
+    MissingSignature,
+
+    #[error("CRAB is not signed by a trusted key, or was modified after signing")]
+    UntrustedSignature,
+
+    #[error("Invalid key: {reason}")]
+    InvalidKey { reason: String },
+}
+
+/// Entries of a CRAB file, apart from the signature.
+struct Contents {
+    entries: Vec<(Header, PathBuf, Vec<u8>)>,
+    signature: Option<Vec<u8>>,
+}
+
+impl Contents {
+    fn read(reader: impl Read) -> Result<Self, PluginSignatureError> {
+        let mut archive = Archive::new(reader);
+        let mut entries = Vec::new();
+        let mut signature = None;
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.into_owned();
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data)?;
+
+            if path == Path::new(SIGNATURE_ENTRY) {
+                signature = Some(data);
+            } else {
+                entries.push((entry.header().clone(), path, data));
+            }
+        }
+
+        Ok(Self { entries, signature })
+    }
+
+    /// Digest covering paths, types, modes, link names and contents of all
+    /// entries, in archive order.
+    fn digest(&self) -> Vec<u8> {
+        let mut hasher = Sha512::new();
+        for (header, path, data) in &self.entries {
+            hasher.update(path.to_string_lossy().as_bytes());
+            hasher.update([0]);
+            hasher.update([header.entry_type().as_byte()]);
+            hasher.update(header.as_old().mode);
+            hasher.update(header.link_name_bytes().as_deref().unwrap_or_default());
+            hasher.update([0]);
+            hasher.update((data.len() as u64).to_le_bytes());
+            hasher.update(data);
+        }
+        hasher.finalize().to_vec()
+    }
+}
+
+fn sign_archive(
+    reader: impl Read,
+    writer: impl Write,
+    key: &SigningKey,
+) -> Result<(), PluginSignatureError> {
+    let contents = Contents::read(reader)?;
+    let signature = key.sign(&contents.digest());
+
+    let mut archive = tar::Builder::new(writer);
+    for (mut header, path, data) in contents.entries {
+        archive.append_data(&mut header, path, data.as_slice())?;
+    }
+    let mut header = Header::new_gnu();
+    header.set_size(SIGNATURE_LENGTH as u64);
+    header.set_mode(0o644);
+    archive.append_data(
+        &mut header,
+        SIGNATURE_ENTRY,
+        signature.to_bytes().as_slice(),
+    )?;
+    archive.into_inner()?.flush()?;
+    Ok(())
+}
+
+fn verify_archive(
+    reader: impl Read,
+    trusted_keys: &[VerifyingKey],
+) -> Result<(), PluginSignatureError> {
+    let contents = Contents::read(reader)?;
+    let signature = contents
+        .signature
+        .as_deref()
+        .ok_or(PluginSignatureError::MissingSignature)?;
+    let signature =
+        Signature::from_slice(signature).map_err(|_| PluginSignatureError::UntrustedSignature)?;
+
+    let digest = contents.digest();
+    trusted_keys
+        .iter()
+        .any(|key| key.verify_strict(&digest, &signature).is_ok())
+        .then_some(())
+        .ok_or(PluginSignatureError::UntrustedSignature)
+}
+
+/// Adds a signature of the plugin contents to the CRAB file, replacing any
+/// previous signature.
+pub fn sign_plugin<P: AsRef<Path>>(path: P, key: &SigningKey) -> Result<(), PluginSignatureError> {
+    let path = path.as_ref();
+    let directory = path.parent().unwrap_or(Path::new("."));
+    let mut signed = NamedTempFile::new_in(directory)?;
+    sign_archive(
+        BufReader::new(File::open(path)?),
+        BufWriter::new(signed.as_file_mut()),
+        key,
+    )?;
+    signed.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// Checks that the CRAB file is signed by one of the trusted keys and was not
+/// modified since.
+pub fn verify_plugin<P: AsRef<Path>>(
+    path: P,
+    trusted_keys: &[VerifyingKey],
+) -> Result<(), PluginSignatureError> {
+    verify_archive(BufReader::new(File::open(path.as_ref())?), trusted_keys)
+}
+
+pub fn generate_key() -> SigningKey {
+    SigningKey::from_bytes(&rand::random())
+}
+
+fn key_bytes(hex_key: &str) -> Result<[u8; 32], PluginSignatureError> {
+    let bytes = hex::decode(hex_key.trim()).map_err(|e| PluginSignatureError::InvalidKey {
+        reason: e.to_string(),
+    })?;
+    bytes
+        .try_into()
+        .map_err(|_| PluginSignatureError::InvalidKey {
+            reason: "expected 32 bytes".to_owned(),
+        })
+}
+
+/// Parses a private key, stored as hex.
+pub fn signing_key_from_hex(hex_key: &str) -> Result<SigningKey, PluginSignatureError> {
+    Ok(SigningKey::from_bytes(&key_bytes(hex_key)?))
+}
+
+/// Parses a public key, stored as hex.
+pub fn verifying_key_from_hex(hex_key: &str) -> Result<VerifyingKey, PluginSignatureError> {
+    VerifyingKey::from_bytes(&key_bytes(hex_key)?).map_err(|e| PluginSignatureError::InvalidKey {
+        reason: e.to_string(),
+    })
+}
+
+pub fn signing_key_to_hex(key: &SigningKey) -> String {
+    hex::encode(key.to_bytes())
+}
+
+pub fn verifying_key_to_hex(key: &VerifyingKey) -> String {
+    hex::encode(key.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use tar::EntryType;
+
+    use super::*;
+
+    fn archive(wasm: &[u8]) -> Vec<u8> {
+        let mut archive = tar::Builder::new(Vec::new());
+        for (path, data) in [("manifest.json", b"{}".as_slice()), ("plugin.wasm", wasm)] {
+            let mut header = Header::new_gnu();
+            header.set_size(data.len() as u64);
+            archive.append_data(&mut header, path, data).unwrap();
+        }
+        archive.into_inner().unwrap()
+    }
+
+    fn tamper(signed: &[u8], change: impl FnOnce(&mut Header, &mut Vec<u8>)) -> Vec<u8> {
+        let mut contents = Contents::read(signed).unwrap();
+        let (header, _, data) = &mut contents.entries[1];
+        change(header, data);
+        let mut archive = tar::Builder::new(Vec::new());
+        for (mut header, path, data) in contents.entries {
+            archive
+                .append_data(&mut header, path, data.as_slice())
+                .unwrap();
+        }
+        let mut header = Header::new_gnu();
+        let signature = contents.signature.unwrap();
+        header.set_size(signature.len() as u64);
+        archive
+            .append_data(&mut header, SIGNATURE_ENTRY, signature.as_slice())
+            .unwrap();
+        archive.into_inner().unwrap()
+    }
+
+    #[test]
+    fn only_untampered_plugins_signed_by_trusted_keys_verify() {
+        let key = generate_key();
+        let other_key = generate_key();
+        let unsigned = archive(b"wasm");
+        let mut signed = Vec::new();
+        sign_archive(unsigned.as_slice(), &mut signed, &key).unwrap();
+
+        let trusted = [other_key.verifying_key(), key.verifying_key()];
+        assert!(verify_archive(signed.as_slice(), &trusted).is_ok());
+        assert!(matches!(
+            verify_archive(signed.as_slice(), &trusted[..1]),
+            Err(PluginSignatureError::UntrustedSignature)
+        ));
+        assert!(matches!(
+            verify_archive(unsigned.as_slice(), &trusted),
+            Err(PluginSignatureError::MissingSignature)
+        ));
+        let tampered = [
+            tamper(&signed, |_, data| *data = b"evil".to_vec()),
+            tamper(&signed, |header, _| header.set_mode(0o777)),
+            tamper(&signed, |header, _| {
+                header.set_entry_type(EntryType::Symlink);
+                header.set_link_name("/etc/passwd").unwrap();
+            }),
+        ];
+        for archive in tampered {
+            assert!(matches!(
+                verify_archive(archive.as_slice(), &trusted),
+                Err(PluginSignatureError::UntrustedSignature)
+            ));
+        }
+    }
+}
@@ -1,12 +1,14 @@
 use std::{
     fs::File,
     io::{BufReader, Read, Seek},
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 use animation_api::plugin_config::PluginManifest;
 use tar::Archive;
 
+use crate::ASSETS_DIR;
+
 #[derive(Debug, thiserror::Error)]
 pub enum PluginUnwrapError {
     #[error("Cannot open plugin file: {0}")]
@@ -46,6 +48,29 @@ pub fn unwrap_plugin<P: AsRef<Path>>(path: P) -> Result<PluginManifest, PluginUn
     manifest_from_crab(path.as_ref())
 }
 
+/// Unpacks assets of the plugin into the destination directory, returning
+/// the directory holding them, or `None` if the plugin has no assets.
+pub fn unpack_assets<P: AsRef<Path>, Q: AsRef<Path>>(
+    path: P,
+    destination: Q,
+) -> Result<Option<PathBuf>, PluginUnwrapError> {
+    let reader = BufReader::new(File::open(path.as_ref())?);
+    let mut archive = Archive::new(reader);
+    let mut found = false;
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if entry.path()?.starts_with(ASSETS_DIR) {
+            // Entries leading outside of the destination are skipped
+            if entry.unpack_in(destination.as_ref())? {
+                found = true;
+            }
+        }
+    }
+
+    Ok(found.then(|| destination.as_ref().join(ASSETS_DIR)))
+}
+
 pub fn reader_from_crab<P: AsRef<Path>>(path: P) -> Result<impl Read, PluginUnwrapError> {
     let (start, size) = {
         let reader = BufReader::new(File::open(path.as_ref())?);
@@ -105,3 +130,79 @@ where
         Ok(read)
     }
 }
+
+#[cfg(all(test, feature = "wrap"))]
+mod tests {
+    use super::*;
+    use crate::wrap::wrap_plugin;
+
+    #[test]
+    fn assets_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let assets = dir.path().join("data");
+        std::fs::create_dir_all(assets.join("fonts")).unwrap();
+        std::fs::write(assets.join("image.png"), b"png").unwrap();
+        std::fs::write(assets.join("fonts/font.ttf"), b"ttf").unwrap();
+        std::fs::write(dir.path().join("manifest.json"), b"{}").unwrap();
+        std::fs::write(dir.path().join("plugin.wasm"), b"wasm").unwrap();
+
+        let with_assets = dir.path().join("with.crab");
+        let without_assets = dir.path().join("without.crab");
+        for (path, assets) in [
+            (&with_assets, Some(assets.as_path())),
+            (&without_assets, None),
+        ] {
+            wrap_plugin(
+                path,
+                dir.path().join("plugin.wasm"),
+                dir.path().join("manifest.json"),
+                assets,
+            )
+            .unwrap();
+        }
+
+        let destination = dir.path().join("unpacked");
+        std::fs::create_dir(&destination).unwrap();
+        let unpacked = unpack_assets(&with_assets, &destination).unwrap().unwrap();
+        assert_eq!(unpacked, destination.join(ASSETS_DIR));
+        assert_eq!(std::fs::read(unpacked.join("image.png")).unwrap(), b"png");
+        assert_eq!(
+            std::fs::read(unpacked.join("fonts/font.ttf")).unwrap(),
+            b"ttf"
+        );
+        assert!(
+            unpack_assets(&without_assets, &destination)
+                .unwrap()
+                .is_none()
+        );
+
+        let mut wasm = Vec::new();
+        reader_from_crab(&with_assets)
+            .unwrap()
+            .read_to_end(&mut wasm)
+            .unwrap();
+        assert_eq!(wasm, b"wasm");
+    }
+
+    #[test]
+    fn assets_leading_outside_are_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plugin.crab");
+        let mut builder = tar::Builder::new(File::create(&path).unwrap());
+        let mut header = tar::Header::new_gnu();
+        // Written directly, as setting the path rejects parent directories
+        let name = format!("{ASSETS_DIR}/../escaped.txt");
+        header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_size(3);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, &b"out"[..]).unwrap();
+        builder.finish().unwrap();
+        drop(builder);
+
+        let destination = dir.path().join("unpacked");
+        std::fs::create_dir(&destination).unwrap();
+        assert!(unpack_assets(&path, &destination).unwrap().is_none());
+        assert!(!dir.path().join("escaped.txt").exists());
+    }
+}
@@ -4,10 +4,15 @@ use std::{
     path::Path,
 };
 
+use crate::ASSETS_DIR;
+
+/// Packs the plugin into a CRAB file. Contents of the assets directory, if
+/// given, are made available to the plugin at run time.
 pub fn wrap_plugin<P, Q, R>(
     output_path: P,
     executable_path: Q,
     manifest_path: R,
+    assets_path: Option<&Path>,
 ) -> std::io::Result<()>
 where
     P: AsRef<Path>,
@@ -18,10 +23,14 @@ where
         output_path: &Path,
         executable_path: &Path,
         manifest_path: &Path,
+        assets_path: Option<&Path>,
     ) -> std::io::Result<()> {
         let mut archive = tar::Builder::new(Vec::new());
         archive.append_path_with_name(manifest_path, "manifest.json")?;
         archive.append_path_with_name(executable_path, "plugin.wasm")?;
+        if let Some(assets_path) = assets_path {
+            archive.append_dir_all(ASSETS_DIR, assets_path)?;
+        }
         let archive_data = archive.into_inner()?;
 
         BufWriter::new(File::create(output_path)?).write_all(&archive_data)
@@ -30,5 +39,6 @@ where
         output_path.as_ref(),
         executable_path.as_ref(),
         manifest_path.as_ref(),
+        assets_path,
     )
 }
@@ -83,6 +83,22 @@ impl Animation for Draw {
         self.parameters.clone()
     }
 
+    /// The canvas is saved as RGB bytes of each light.
+    fn save_state(&self) -> Option<Vec<u8>> {
+        Some(self.canvas.iter().flat_map(|c| [c.r, c.g, c.b]).collect())
+    }
+
+    fn load_state(&mut self, state: &[u8]) {
+        // Canvas drawn for different lights does not apply
+        if state.len() != self.canvas.len() * 3 {
+            return;
+        }
+        self.canvas = state
+            .chunks_exact(3)
+            .map(|c| Color::rgb(c[0], c[1], c[2]))
+            .collect();
+    }
+
     fn render(&self) -> lightfx::Frame {
         let mut canvas = self.canvas.clone();
         if self.parameters.mode != Mode::Watching
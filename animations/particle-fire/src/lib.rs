@@ -103,25 +103,50 @@ pub struct Parameters {
     #[schema_field(name = "Dimension", enum_options)]
     dimension: Dimension,
 
-    #[schema_field(name = "Generation rate", number(min = 0.0, max = 500.0, step = 10.0))]
+    #[schema_field(
+        name = "Generation rate",
+        group = "Particles",
+        number(min = 0.0, max = 500.0, step = 10.0)
+    )]
     gen_rate: usize,
 
-    #[schema_field(name = "Decay rate", number(min = 0.0, max = 1.0, step = 0.01))]
+    #[schema_field(
+        name = "Decay rate",
+        group = "Particles",
+        number(min = 0.0, max = 1.0, step = 0.01)
+    )]
     decay_rate: f64,
 
-    #[schema_field(name = "Decay rate spread", number(min = 0.0, max = 0.2, step = 0.01))]
+    #[schema_field(
+        name = "Decay rate spread",
+        group = "Particles",
+        number(min = 0.0, max = 0.2, step = 0.01)
+    )]
     decay_rate_spread: f64,
 
     #[schema_field(name = "Bottom line", number(min = "-1.0", max = 1.0, step = 0.01))]
     bottom_line: f64,
 
-    #[schema_field(name = "Particle range", number(min = 0.0, max = 1.0, step = 0.01))]
+    #[schema_field(
+        name = "Particle range",
+        group = "Particles",
+        number(min = 0.0, max = 1.0, step = 0.01)
+    )]
     particle_range: f64,
 
-    #[schema_field(name = "Wind", number(min = "-0.1", max = 0.1, step = 0.001))]
+    #[schema_field(
+        name = "Wind",
+        group = "Wind",
+        number(min = "-0.1", max = 0.1, step = 0.001)
+    )]
     wind: f64,
 
-    #[schema_field(name = "Wind direction", number(min = 0.0, max = 360.0, step = 10.0))]
+    #[schema_field(
+        name = "Wind direction",
+        group = "Wind",
+        visible_when(parameter = "dimension", equals = "Dim3D"),
+        number(min = 0.0, max = 360.0, step = 10.0)
+    )]
     wind_direction: f64,
 }
 
@@ -14,6 +14,8 @@ impl GetSchema for Parameters {
             id: "bit".to_owned(),
             name: "Bit".to_owned(),
             description: None,
+            group: None,
+            visible_when: None,
             value: ValueSchema::Enum {
                 values: (0..10)
                     .map(|i| EnumOption {
@@ -0,0 +1,140 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, SystemTime},
+};
+
+use animation_api::{event::Event, schema::ParameterValue};
+use animation_wasm_bindings::host::{AnimationPlugin, PluginHost, PluginLimits};
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use lightfx::Frame;
+use rustmas_animator::points_from_path;
+use serde::{Deserialize, Serialize};
+
+/// Renders an animation plugin offline, with a fixed time step, and writes
+/// the frames to a file as JSON lines
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+struct Args {
+    /// Path to the .crab file of the animation
+    plugin: PathBuf,
+    /// Path to the points CSV file
+    #[arg(short, long, default_value = "lights.csv")]
+    points: PathBuf,
+    /// Path of the output file
+    #[arg(short, long, default_value = "frames.jsonl")]
+    output: PathBuf,
+    /// Number of seconds to render
+    #[arg(short, long, default_value_t = 10.0)]
+    duration: f64,
+    /// Number of frames rendered per second of animation time
+    #[arg(short, long, default_value_t = 30.0)]
+    fps: f64,
+    /// Path to a JSON file with parameter values to set before rendering
+    #[arg(long)]
+    params: Option<PathBuf>,
+    /// Path to a JSON file with a list of events to send, each with the time
+    /// (in seconds) at which it should be sent
+    #[arg(long)]
+    events: Option<PathBuf>,
+    /// Seed for the random source of the animation, for reproducible output
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Wall clock time seen by the animation at the start of rendering. It is
+    /// fixed by default, so that the output does not depend on when it is rendered
+    #[arg(long, default_value = "2024-12-24T18:00:00Z")]
+    start_time: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct ScriptedEvent {
+    time: f64,
+    event: Event,
+}
+
+#[derive(Serialize)]
+struct RenderedFrame<'a> {
+    time: f64,
+    #[serde(flatten)]
+    frame: &'a Frame,
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, Box<dyn Error>> {
+    Ok(serde_json::from_reader(File::open(path)?)?)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    if args.fps <= 0.0 {
+        return Err("fps must be positive".into());
+    }
+
+    // Wall clock seen by the animation follows animation time
+    let start = SystemTime::from(args.start_time);
+    let elapsed_millis = Arc::new(AtomicU64::new(0));
+    let host = PluginHost {
+        clock: {
+            let elapsed_millis = elapsed_millis.clone();
+            Arc::new(move || start + Duration::from_millis(elapsed_millis.load(Ordering::Relaxed)))
+        },
+        seed: args.seed,
+    };
+
+    let points = points_from_path(&args.points)?;
+    let mut animation =
+        AnimationPlugin::new(&args.plugin, points, PluginLimits::default(), host).await?;
+
+    if let Some(path) = &args.params {
+        let params: HashMap<String, ParameterValue> = read_json(path)?;
+        animation.set_parameters(&params).await?;
+    }
+
+    let mut events: Vec<ScriptedEvent> = match &args.events {
+        Some(path) => read_json(path)?,
+        None => Vec::new(),
+    };
+    events.sort_by(|a, b| a.time.total_cmp(&b.time));
+    let mut events = events.into_iter().peekable();
+
+    let mut output = BufWriter::new(File::create(&args.output)?);
+    let time_step = 1.0 / args.fps;
+    let frame_count = (args.duration * args.fps).round() as usize;
+
+    for step in 0..frame_count {
+        let time = step as f64 * time_step;
+        elapsed_millis.store((time * 1000.0) as u64, Ordering::Relaxed);
+        while let Some(scripted) = events.next_if(|e| e.time <= time) {
+            animation.send_event(scripted.event).await?;
+        }
+        if step > 0 {
+            animation.update(time_step).await?;
+        }
+
+        let frame = animation.render().await?;
+        serde_json::to_writer(
+            &mut output,
+            &RenderedFrame {
+                time,
+                frame: &frame,
+            },
+        )?;
+        writeln!(output)?;
+    }
+    output.flush()?;
+
+    println!(
+        "Rendered {frame_count} frames of {} to {}",
+        animation.manifest().id,
+        args.output.display()
+    );
+    Ok(())
+}
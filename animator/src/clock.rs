@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use tokio::sync::watch;
+
+/// Source of time for the controller, which decides when frames are rendered
+/// and how much time passes between them.
+#[async_trait]
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Waits until the given amount of time passes on this clock.
+    async fn sleep(&self, duration: Duration);
+}
+
+/// Wall clock time, used when running on real lights.
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration.to_std().unwrap_or_default()).await;
+    }
+}
+
+/// Clock that only moves forward when told to, for deterministic tests.
+pub struct ManualClock {
+    now: watch::Sender<DateTime<Utc>>,
+}
+
+impl ManualClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            now: watch::Sender::new(start),
+        }
+    }
+
+    /// Moves the clock forward, waking up everyone sleeping until then.
+    pub fn advance(&self, duration: Duration) {
+        self.now.send_modify(|now| *now += duration);
+    }
+}
+
+#[async_trait]
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.borrow()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        let until = self.now() + duration;
+        let _ = self.now.subscribe().wait_for(|now| *now >= until).await;
+    }
+}
@@ -1,11 +1,160 @@
 use std::path::PathBuf;
 
+use animation_api::point::Point;
+use animation_wasm_bindings::host::PluginLimits;
+use lightfx::Transition;
 use rustmas_light_client::LightsConfig;
+use rustmas_light_client::power_limiter::PowerLimit;
 use serde::{Deserialize, Serialize};
 
+use crate::SyncConfig;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ControllerConfig {
     pub points_path: PathBuf,
     pub lights: Vec<LightsConfig>,
+    /// Power limit for all lights together, applied before limits of individual lights
+    #[serde(default)]
+    pub power_limit: Option<PowerLimit>,
     pub plugin_path: PathBuf,
+    #[serde(default)]
+    pub default_transition: Option<Transition>,
+    #[serde(default)]
+    pub zones: Vec<ZoneConfig>,
+    /// Rate at which frames are sent to the lights, interpolating between
+    /// frames rendered by the animations. Frames are sent as they are
+    /// rendered if not set.
+    #[serde(default)]
+    pub output_fps: Option<f64>,
+    #[serde(default)]
+    pub plugin_limits: PluginLimits,
+    #[serde(default)]
+    pub watchdog: WatchdogConfig,
+    /// Synchronization of animations with other servers
+    #[serde(default)]
+    pub sync: Option<SyncConfig>,
+    #[serde(default)]
+    pub plugin_signing: PluginSigningConfig,
+}
+
+/// Verification of signatures of installed animation plugins.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginSigningConfig {
+    /// Refuse to install plugins not signed by one of the trusted keys.
+    #[serde(default)]
+    pub require_signature: bool,
+    /// Public keys of trusted plugin authors, as hex.
+    #[serde(default)]
+    pub trusted_keys: Vec<String>,
+}
+
+/// Recovery from animations that keep failing to render.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    /// Number of consecutive failed frames after which the animation is replaced.
+    #[serde(default = "WatchdogConfig::default_max_failures")]
+    pub max_failures: u32,
+    /// ID of the animation to switch to, turns the lights off if not set.
+    #[serde(default)]
+    pub fallback_animation: Option<String>,
+}
+
+impl WatchdogConfig {
+    fn default_max_failures() -> u32 {
+        10
+    }
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            max_failures: Self::default_max_failures(),
+            fallback_animation: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZoneConfig {
+    pub name: String,
+    pub points: ZoneSelector,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ZoneSelector {
+    /// Points with indices from `start` (inclusive) to `end` (exclusive).
+    Indices { start: usize, end: usize },
+    /// Points lying within the given bounds, each given as `[min, max]`.
+    /// Missing bounds do not restrict the zone along that axis.
+    Bounds {
+        #[serde(default)]
+        x: Option<[f64; 2]>,
+        #[serde(default)]
+        y: Option<[f64; 2]>,
+        #[serde(default)]
+        z: Option<[f64; 2]>,
+    },
+}
+
+impl ZoneSelector {
+    /// Returns indices of the points belonging to the zone.
+    pub fn select(&self, points: &[Point]) -> Vec<usize> {
+        match self {
+            ZoneSelector::Indices { start, end } => (*start..*end.min(&points.len())).collect(),
+            ZoneSelector::Bounds { x, y, z } => {
+                let within = |bounds: &Option<[f64; 2]>, c: f64| {
+                    bounds.is_none_or(|[min, max]| (min..=max).contains(&c))
+                };
+                points
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, p)| within(x, p.x) && within(y, p.y) && within(z, p.z))
+                    .map(|(i, _)| i)
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points() -> Vec<Point> {
+        [
+            (0.0, 0.0, 0.0),
+            (0.5, -0.5, 0.0),
+            (-0.5, 0.5, 1.0),
+            (1.0, 1.0, -1.0),
+        ]
+        .into_iter()
+        .map(Point::from)
+        .collect()
+    }
+
+    #[test]
+    fn indices_are_clamped_to_points() {
+        let select = |start, end| ZoneSelector::Indices { start, end }.select(&points());
+        assert_eq!(select(1, 3), vec![1, 2]);
+        assert_eq!(select(2, 10), vec![2, 3]);
+        assert!(select(5, 10).is_empty());
+    }
+
+    #[test]
+    fn bounds_are_inclusive_and_optional() {
+        let selector = ZoneSelector::Bounds {
+            x: Some([0.0, 1.0]),
+            y: None,
+            z: Some([-1.0, 0.0]),
+        };
+        assert_eq!(selector.select(&points()), vec![0, 1, 3]);
+
+        let everything = ZoneSelector::Bounds {
+            x: None,
+            y: None,
+            z: None,
+        };
+        assert_eq!(everything.select(&points()), vec![0, 1, 2, 3]);
+    }
 }
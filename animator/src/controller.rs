@@ -1,10 +1,12 @@
 use std::collections::HashMap;
 use std::error::Error;
+use std::path::Path;
 use std::sync::Arc;
 
 use animation_api::event::Event;
+use animation_api::point::Point;
 use animation_api::schema::{Configuration, ConfigurationSchema, ParameterValue};
-use animation_wasm_bindings::host::{AnimationPlugin, AnimationPluginError};
+use animation_wasm_bindings::host::{AnimationPlugin, AnimationPluginError, PluginHost};
 use chrono::{DateTime, Duration, Utc};
 use client::combined::CombinedLightClient;
 #[cfg(feature = "audio")]
@@ -14,15 +16,22 @@ use events::event_generator::EventGenerator;
 use events::fft_generator::FftEventGenerator;
 #[cfg(feature = "midi")]
 use events::midi_generator::MidiEventGenerator;
-use lightfx::Frame;
+use lightfx::{BlendMode, Frame, PostProcessing, Transition};
 use log::{info, warn};
 use rustmas_light_client as client;
 use rustmas_light_client::LightClientError;
+use rustmas_light_client::power_limiter::{PowerLimiter, PowerMeter};
+use rustmas_light_client::recorder::{Recorder, RecordingLightClient, read_recording};
 use tokio::sync::{Mutex, mpsc};
 use tokio::task::JoinHandle;
 
-use crate::ControllerConfig;
-use crate::factory::AnimationFactoryError;
+use crate::clock::{Clock, SystemClock};
+use crate::factory::{AnimationFactory, AnimationFactoryError};
+use crate::interpolation::Interpolator;
+use crate::replay::Replay;
+use crate::sync::{self, SyncClock, SyncConfig, SyncLeader, SyncMessage};
+use crate::zone::{LayerInfo, Zone, ZoneInfo};
+use crate::{ControllerConfig, WatchdogConfig, points_from_path};
 
 #[derive(Debug, thiserror::Error)]
 pub enum ControllerError {
@@ -37,41 +46,348 @@ pub enum ControllerError {
 
     #[error("no animation selected")]
     NoAnimationSelected,
+
+    #[error("no such layer: {0}")]
+    NoSuchLayer(String),
+
+    #[error("no such zone: {0}")]
+    NoSuchZone(String),
+}
+
+/// Describes an animation that was replaced by the watchdog after failing to
+/// render too many frames in a row.
+#[derive(Clone, Debug)]
+pub struct AnimationFailure {
+    pub zone: Option<String>,
+    /// Key of the failed layer, which is removed without a fallback, or `None`
+    /// for the base animation of the zone
+    pub layer: Option<String>,
+    pub animation_id: String,
+    pub error: String,
+    pub fallback_animation_id: Option<String>,
+    pub time: DateTime<Utc>,
+}
+
+/// Replacement of an animation that keeps failing with the fallback animation.
+struct FallbackRequest {
+    zone: Option<String>,
+    animation_id: String,
+    /// Time of the failure, to recognize zones that were switched since
+    failure_time: DateTime<Utc>,
 }
 
 struct ControllerState {
-    animation: Option<AnimationPlugin>,
+    /// Zone covering all points, rendered underneath all other zones
+    main: Zone,
+    zones: Vec<Zone>,
     last_frame: DateTime<Utc>,
     next_frame: DateTime<Utc>,
-    fps: f64,
     event_generators: HashMap<String, Box<dyn EventGenerator>>,
+    watchdog: WatchdogConfig,
+    /// Most recent watchdog intervention for each zone
+    failures: Vec<AnimationFailure>,
+    fallback_sender: mpsc::UnboundedSender<FallbackRequest>,
+    /// Streams changes to followers, if this server leads synchronization
+    sync_leader: Option<SyncLeader>,
+    clock: Arc<dyn Clock>,
+    post_processing: PostProcessing,
+    /// Recorded frames displayed instead of the animations
+    replay: Option<Replay>,
+    /// Animations are not updated while paused, and the last frame is sent again instead
+    paused: bool,
+    /// Time by which animations should advance in the next frame while paused
+    pending_step: Option<f64>,
+    /// Last rendered frame, before post-processing
+    last_rendered: Option<Frame>,
 }
 
 impl ControllerState {
-    async fn set_animation(
+    /// Returns the given zone, or the main zone if no zone is given.
+    fn zone(&self, zone: Option<&str>) -> Result<&Zone, ControllerError> {
+        match zone {
+            None => Ok(&self.main),
+            Some(name) => self
+                .zones
+                .iter()
+                .find(|z| z.name == name)
+                .ok_or_else(|| ControllerError::NoSuchZone(name.to_owned())),
+        }
+    }
+
+    fn zone_mut(&mut self, zone: Option<&str>) -> Result<&mut Zone, ControllerError> {
+        match zone {
+            None => Ok(&mut self.main),
+            Some(name) => self
+                .zones
+                .iter_mut()
+                .find(|z| z.name == name)
+                .ok_or_else(|| ControllerError::NoSuchZone(name.to_owned())),
+        }
+    }
+
+    fn all_zones(&self) -> impl Iterator<Item = &Zone> {
+        std::iter::once(&self.main).chain(self.zones.iter())
+    }
+
+    /// Sends the change to followers, if this server leads synchronization.
+    fn publish(&self, message: SyncMessage) {
+        if let Some(leader) = &self.sync_leader {
+            leader.publish(message);
+        }
+    }
+
+    fn frame_rate(&self) -> f64 {
+        self.all_zones().map(Zone::frame_rate).fold(0.0, f64::max)
+    }
+
+    /// Renders a frame as soon as possible. While paused, the frame is
+    /// rendered without advancing the animations, to show changes made to them.
+    fn request_frame(&mut self) {
+        if self.paused {
+            self.pending_step.get_or_insert(0.0);
+        }
+        self.next_frame = self.clock.now();
+    }
+
+    /// Logs a frame that failed to render, and turns off the animation of the
+    /// zone once it fails too many times in a row. The fallback animation is
+    /// started in its place as soon as it is ready. Layers failing too many
+    /// times in a row are removed.
+    async fn handle_failure(
+        &mut self,
+        zone: Option<&str>,
+        layer: Option<&str>,
+        error: AnimationPluginError,
+    ) {
+        let Ok(failed_zone) = self.zone(zone) else {
+            return;
+        };
+        let animation_id = failed_zone.animation_id(layer).unwrap_or_default();
+        let failures = failed_zone.failures(layer);
+        warn!(
+            "Animation {animation_id} failed to render in {}zone {} ({failures} consecutive failures): {error}",
+            layer
+                .map(|key| format!("layer {key} of "))
+                .unwrap_or_default(),
+            zone.unwrap_or("main"),
+        );
+        if failures < self.watchdog.max_failures {
+            return;
+        }
+
+        if let Some(key) = layer {
+            warn!(
+                "Removing layer {key} of zone {} running {animation_id}",
+                zone.unwrap_or("main"),
+            );
+            if let Ok(failed_zone) = self.zone_mut(zone) {
+                let _ = failed_zone.remove_layer(key);
+            }
+            self.publish(SyncMessage::RemoveLayer {
+                zone: zone.map(str::to_owned),
+                key: key.to_owned(),
+            });
+            let time = self.clock.now();
+            self.record_failure(zone, layer, animation_id, error, None, time);
+            return;
+        }
+
+        let fallback_animation_id = self
+            .watchdog
+            .fallback_animation
+            .clone()
+            .filter(|fallback_id| *fallback_id != animation_id);
+        warn!(
+            "Replacing animation {animation_id} in zone {} with {}",
+            zone.unwrap_or("main"),
+            fallback_animation_id.as_deref().unwrap_or("black"),
+        );
+
+        let Ok(failed_zone) = self.zone_mut(zone) else {
+            return;
+        };
+        let _ = failed_zone.set_animation(None, None).await;
+
+        let time = self.clock.now();
+        self.publish(SyncMessage::SwitchAnimation {
+            zone: zone.map(str::to_owned),
+            animation_id: None,
+            transition: None,
+            time,
+        });
+        if let Some(animation_id) = &fallback_animation_id {
+            let _ = self.fallback_sender.send(FallbackRequest {
+                zone: zone.map(str::to_owned),
+                animation_id: animation_id.clone(),
+                failure_time: time,
+            });
+        }
+        self.record_failure(zone, None, animation_id, error, fallback_animation_id, time);
+    }
+
+    /// Keeps the most recent watchdog intervention for the zone or layer.
+    fn record_failure(
+        &mut self,
+        zone: Option<&str>,
+        layer: Option<&str>,
+        animation_id: String,
+        error: AnimationPluginError,
+        fallback_animation_id: Option<String>,
+        time: DateTime<Utc>,
+    ) {
+        self.clear_failure(zone, layer);
+        self.failures.push(AnimationFailure {
+            zone: zone.map(str::to_owned),
+            layer: layer.map(str::to_owned),
+            animation_id,
+            error: error.to_string(),
+            fallback_animation_id,
+            time,
+        });
+        self.request_frame();
+    }
+
+    fn clear_failure(&mut self, zone: Option<&str>, layer: Option<&str>) {
+        self.failures
+            .retain(|f| f.zone.as_deref() != zone || f.layer.as_deref() != layer);
+    }
+
+    /// Switches to the fallback animation, unless the zone was switched to
+    /// another animation since it failed. Followers switch along with the leader.
+    async fn apply_fallback(
+        &mut self,
+        request: FallbackRequest,
+        animation: Result<AnimationPlugin, AnimationFactoryError>,
+    ) {
+        let zone = request.zone.as_deref();
+        let Some(index) = self.failures.iter().position(|f| {
+            f.zone.as_deref() == zone && f.layer.is_none() && f.time == request.failure_time
+        }) else {
+            return;
+        };
+
+        let result = match (animation, self.zone_mut(zone)) {
+            (Ok(animation), Ok(failed_zone)) => failed_zone
+                .set_animation(Some(animation), None)
+                .await
+                .map_err(|e| e.to_string()),
+            (Err(e), _) => Err(e.to_string()),
+            (_, Err(e)) => Err(e.to_string()),
+        };
+        match result {
+            Ok(()) => {
+                self.last_frame = self.clock.now();
+                self.publish(SyncMessage::SwitchAnimation {
+                    zone: request.zone.clone(),
+                    animation_id: Some(request.animation_id),
+                    transition: None,
+                    time: self.last_frame,
+                });
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to start fallback animation {}: {e}",
+                    request.animation_id
+                );
+                self.failures[index].fallback_animation_id = None;
+            }
+        }
+        self.request_frame();
+    }
+
+    /// Applies a change streamed by the sync leader, along with the animation
+    /// it switches to or adds as a layer, which is loaded beforehand.
+    async fn apply_sync_message(
         &mut self,
+        message: SyncMessage,
         animation: Option<AnimationPlugin>,
     ) -> Result<(), ControllerError> {
-        let now = Utc::now();
-        self.fps = if let Some(animation) = &animation {
-            animation.get_fps().await?
-        } else {
-            0.0
-        };
-        self.last_frame = now;
-        self.next_frame = now;
-        self.animation = animation;
+        match message {
+            SyncMessage::Clock { .. } => {}
+            SyncMessage::SwitchAnimation {
+                zone,
+                transition,
+                time,
+                ..
+            } => {
+                let zone = zone.as_deref();
+                self.zone_mut(zone)?
+                    .set_animation(animation, transition)
+                    .await?;
+                self.clear_failure(zone, None);
+                // Catch up with the time that passed on the leader since the switch
+                self.last_frame = time.min(self.clock.now());
+                self.request_frame();
+            }
+            SyncMessage::SetParameters {
+                zone,
+                layer,
+                values,
+            } => {
+                self.zone_mut(zone.as_deref())?
+                    .animation_mut(layer.as_deref())?
+                    .set_parameters(&values)
+                    .await?;
+                self.request_frame();
+            }
+            SyncMessage::AddLayer {
+                zone,
+                key,
+                opacity,
+                blend_mode,
+                ..
+            } => {
+                if let Some(animation) = animation {
+                    self.zone_mut(zone.as_deref())?
+                        .add_layer(&key, animation, opacity, blend_mode)
+                        .await?;
+                    self.request_frame();
+                }
+            }
+            SyncMessage::RemoveLayer { zone, key } => {
+                self.zone_mut(zone.as_deref())?.remove_layer(&key)?;
+                self.request_frame();
+            }
+            SyncMessage::SetLayerBlending {
+                zone,
+                key,
+                opacity,
+                blend_mode,
+            } => {
+                self.zone_mut(zone.as_deref())?
+                    .set_layer_blending(&key, opacity, blend_mode)?;
+                self.request_frame();
+            }
+        }
         Ok(())
     }
 }
 
+async fn make_animation(
+    factory: &AnimationFactory,
+    animation_id: &str,
+    zone: Option<&str>,
+) -> Result<AnimationPlugin, AnimationFactoryError> {
+    let plugins = factory.discover()?;
+    let plugin = plugins
+        .get(animation_id)
+        .ok_or(AnimationFactoryError::AnimationNotFound)?;
+    factory.make_from_path(&plugin.path, zone).await
+}
+
 pub struct Controller {
     animation_join_handle: JoinHandle<()>,
     event_generator_join_handle: JoinHandle<()>,
     state: Arc<Mutex<ControllerState>>,
     event_sender: mpsc::Sender<Event>,
+    default_transition: Option<Transition>,
+    power_meters: Vec<PowerMeter>,
+    recorder: Recorder,
 }
 
+/// Interval at which the last frame is sent again while paused.
+const PAUSED_FRAME_INTERVAL: Duration = Duration::seconds(1);
+
 enum PollFrameResult {
     Ready(Frame),
     TryLater(DateTime<Utc>),
@@ -80,38 +396,63 @@ enum PollFrameResult {
 impl Controller {
     fn new(
         client: Box<dyn rustmas_light_client::LightClient + Sync + Send>,
-        points_count: usize,
+        main: Zone,
+        zones: Vec<Zone>,
+        factory: AnimationFactory,
+        config: &ControllerConfig,
+        clock: Arc<dyn Clock>,
+        sync_leader: Option<SyncLeader>,
     ) -> Self {
-        let now = Utc::now();
+        let now = clock.now();
         let (event_sender, event_receiver) = mpsc::channel(16);
+        let (fallback_sender, fallback_receiver) = mpsc::unbounded_channel();
 
         let state = Arc::new(Mutex::new(ControllerState {
+            main,
+            zones,
             last_frame: now,
             next_frame: now,
-            fps: 0.0,
-            animation: None,
             event_generators: Self::start_generators(event_sender.clone()),
+            watchdog: config.watchdog.clone(),
+            failures: Vec::new(),
+            fallback_sender,
+            sync_leader,
+            clock: clock.clone(),
+            post_processing: PostProcessing::default(),
+            replay: None,
+            paused: false,
+            pending_step: None,
+            last_rendered: None,
         }));
 
-        let animation_join_handle = tokio::spawn(Self::run(state.clone(), client, points_count));
+        let output_interval = config
+            .output_fps
+            .filter(|fps| *fps > 0.0)
+            .map(|fps| Duration::microseconds((1_000_000.0 / fps) as i64));
+        let recorder = Self::make_recorder(clock.clone());
+        let animation_join_handle =
+            tokio::spawn(Self::run(state.clone(), client, clock, output_interval));
         let event_generator_join_handle =
             tokio::spawn(Self::event_loop(state.clone(), event_receiver));
+        tokio::spawn(Self::start_fallbacks(
+            state.clone(),
+            factory,
+            fallback_receiver,
+        ));
 
         Self {
             state,
             animation_join_handle,
             event_generator_join_handle,
             event_sender,
+            default_transition: config.default_transition,
+            power_meters: Vec::new(),
+            recorder,
         }
     }
 
     pub async fn current_animation_id(&self) -> Option<String> {
-        self.state
-            .lock()
-            .await
-            .animation
-            .as_ref()
-            .map(|animation| animation.manifest().id.clone())
+        self.state.lock().await.main.animation_id(None)
     }
 
     #[allow(unused_variables)]
@@ -141,27 +482,44 @@ impl Controller {
     async fn run(
         state: Arc<Mutex<ControllerState>>,
         client: Box<dyn rustmas_light_client::LightClient + Sync + Send>,
-        point_count: usize,
+        clock: Arc<dyn Clock>,
+        output_interval: Option<Duration>,
     ) {
-        let mut next_check = Utc::now();
+        let mut next_check = clock.now();
+        let mut interpolator = Interpolator::default();
 
         loop {
-            let now = Utc::now();
-            tokio::time::sleep(
-                (next_check - now)
-                    .clamp(Duration::milliseconds(0), Duration::milliseconds(33))
-                    .to_std()
-                    .unwrap(),
-            )
-            .await;
-
-            let frame = match Self::poll_next_frame(&state, now, point_count).await {
-                PollFrameResult::Ready(frame) => frame,
+            let wake_up = match interpolator.next_output() {
+                Some(next_output) => next_check.min(next_output),
+                None => next_check,
+            };
+            clock
+                .sleep(
+                    (wake_up - clock.now())
+                        .clamp(Duration::milliseconds(0), Duration::milliseconds(33)),
+                )
+                .await;
+            let now = clock.now();
+
+            let rendered = match Self::poll_next_frame(&state, now).await {
+                PollFrameResult::Ready(frame) => Some(frame),
                 PollFrameResult::TryLater(when) => {
                     next_check = when;
-                    continue;
+                    None
                 }
             };
+            let frame = match output_interval {
+                Some(interval) => {
+                    if let Some(frame) = rendered {
+                        interpolator.push(now, frame);
+                    }
+                    interpolator.poll(now, interval)
+                }
+                None => rendered,
+            };
+            let Some(frame) = frame else {
+                continue;
+            };
 
             if client.display_frame(&frame).await == Err(LightClientError::ProcessExited) {
                 warn!("Light client exited, exiting");
@@ -173,43 +531,146 @@ impl Controller {
     async fn poll_next_frame(
         state: &Mutex<ControllerState>,
         now: DateTime<Utc>,
-        frame_size: usize,
     ) -> PollFrameResult {
         let mut state = state.lock().await;
         let in_one_second = state.next_frame.min(now + Duration::seconds(1));
         if now < state.next_frame {
             return PollFrameResult::TryLater(in_one_second);
         }
-        state.next_frame = if state.fps != 0.0 {
-            now + Duration::milliseconds((1000.0 / state.fps) as i64)
+
+        if let Some(replay) = &state.replay {
+            match replay.frame_at(now) {
+                Some((frame, next_frame)) => {
+                    // Frames are recorded as displayed, so post-processing is already applied
+                    let frame = frame.clone();
+                    state.next_frame = next_frame;
+                    state.last_frame = now;
+                    return PollFrameResult::Ready(frame);
+                }
+                None => {
+                    info!("Replay finished, resuming animations");
+                    state.replay = None;
+                    state.last_frame = now;
+                }
+            }
+        }
+
+        let delta = if state.paused {
+            state.last_frame = now;
+            match state.pending_step.take() {
+                Some(delta) => {
+                    state.next_frame = now + PAUSED_FRAME_INTERVAL;
+                    delta
+                }
+                None => {
+                    // Keep sending the frame, so that lights do not time out
+                    state.next_frame = now + PAUSED_FRAME_INTERVAL;
+                    return match &state.last_rendered {
+                        Some(frame) => PollFrameResult::Ready(state.post_processing.apply(frame)),
+                        None => PollFrameResult::TryLater(state.next_frame),
+                    };
+                }
+            }
         } else {
-            now + Duration::days(1)
+            let fps = state.frame_rate();
+            state.next_frame = if fps != 0.0 {
+                now + Duration::milliseconds((1000.0 / fps) as i64)
+            } else {
+                now + Duration::days(1)
+            };
+
+            let delta = (now - state.last_frame).num_milliseconds() as f64 / 1000.0;
+            state.last_frame = now;
+            delta
+        };
+        let mut failed_layers = Vec::new();
+        let mut frame = match state.main.render(delta, &mut failed_layers).await {
+            Ok(frame) => frame.unwrap_or_else(|| Frame::new_black(state.main.indices.len())),
+            Err(e) => {
+                state.handle_failure(None, None, e).await;
+                return PollFrameResult::TryLater(state.next_frame.min(in_one_second));
+            }
         };
+        let mut failures = failed_layers
+            .into_iter()
+            .map(|(layer, e)| (None, Some(layer), e))
+            .collect::<Vec<_>>();
 
-        let delta = now - state.last_frame;
-        state.last_frame = now;
-        if let Some(ref mut animation) = state.animation {
-            if animation
-                .update(delta.num_milliseconds() as f64 / 1000.0)
-                .await
-                .is_err()
-            {
-                PollFrameResult::TryLater(in_one_second)
-            } else if let Ok(frame) = animation.render().await {
-                PollFrameResult::Ready(frame)
-            } else {
-                PollFrameResult::TryLater(in_one_second)
+        for zone in state.zones.iter_mut() {
+            let mut failed_layers = Vec::new();
+            match zone.render(delta, &mut failed_layers).await {
+                Ok(Some(zone_frame)) => zone.composite(&mut frame, &zone_frame),
+                Ok(None) => {}
+                Err(e) => failures.push((Some(zone.name.clone()), None, e)),
+            }
+            failures.extend(
+                failed_layers
+                    .into_iter()
+                    .map(|(layer, e)| (Some(zone.name.clone()), Some(layer), e)),
+            );
+        }
+        for (zone, layer, e) in failures {
+            state
+                .handle_failure(zone.as_deref(), layer.as_deref(), e)
+                .await;
+        }
+
+        let output = state.post_processing.apply(&frame);
+        state.last_rendered = Some(frame);
+        PollFrameResult::Ready(output)
+    }
+
+    /// Prepares fallback animations requested by the watchdog. Plugins are
+    /// loaded without holding the state lock, which would stall rendering.
+    async fn start_fallbacks(
+        state: Arc<Mutex<ControllerState>>,
+        factory: AnimationFactory,
+        mut receiver: mpsc::UnboundedReceiver<FallbackRequest>,
+    ) {
+        while let Some(request) = receiver.recv().await {
+            let animation =
+                make_animation(&factory, &request.animation_id, request.zone.as_deref()).await;
+            state.lock().await.apply_fallback(request, animation).await;
+        }
+    }
+
+    /// Applies changes streamed by the sync leader. Like fallbacks, plugins
+    /// are loaded without holding the state lock.
+    async fn follow_leader(
+        state: Arc<Mutex<ControllerState>>,
+        factory: AnimationFactory,
+        mut receiver: mpsc::Receiver<SyncMessage>,
+    ) {
+        while let Some(message) = receiver.recv().await {
+            let animation = match &message {
+                SyncMessage::SwitchAnimation {
+                    zone,
+                    animation_id: Some(animation_id),
+                    ..
+                }
+                | SyncMessage::AddLayer {
+                    zone, animation_id, ..
+                } => match make_animation(&factory, animation_id, zone.as_deref()).await {
+                    Ok(animation) => Some(animation),
+                    Err(e) => {
+                        warn!("Failed to start animation {animation_id} of the sync leader: {e}");
+                        continue;
+                    }
+                },
+                _ => None,
+            };
+            let mut state = state.lock().await;
+            if let Err(e) = state.apply_sync_message(message, animation).await {
+                warn!("Failed to apply change from the sync leader: {e}");
             }
-        } else {
-            PollFrameResult::Ready(lightfx::Frame::new_black(frame_size))
         }
     }
 
     async fn event_loop(state: Arc<Mutex<ControllerState>>, mut receiver: mpsc::Receiver<Event>) {
         while let Some(event) = receiver.recv().await {
             let state = state.lock().await;
-            if let Some(animation) = &state.animation {
-                let _ = animation.send_event(event).await;
+            for animation in state.all_zones().flat_map(Zone::animations) {
+                let _ = animation.send_event(event.clone()).await;
             }
         }
     }
@@ -217,7 +678,17 @@ impl Controller {
     pub fn from_config(
         config: &ControllerConfig,
         feedback: Option<mpsc::Sender<lightfx::Frame>>,
-        point_count: usize,
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::from_config_with_clock(config, feedback, Arc::new(SystemClock))
+    }
+
+    /// Creates a controller timing frames and animations with the given clock,
+    /// e.g. a `ManualClock` for tests. Followers apply their offset from the
+    /// leader to this clock.
+    pub fn from_config_with_clock(
+        config: &ControllerConfig,
+        feedback: Option<mpsc::Sender<lightfx::Frame>>,
+        clock: Arc<dyn Clock>,
     ) -> Result<Self, Box<dyn Error>> {
         let mut light_client_builder =
             CombinedLightClient::builder().with_config(&config.lights)?;
@@ -226,7 +697,159 @@ impl Controller {
                 light_client_builder.with(client::feedback::FeedbackLightClient::new(sender));
         }
 
-        Ok(Self::new(light_client_builder.build(), point_count))
+        let sync_clock = Arc::new(SyncClock::new(clock.clone()));
+        let clock: Arc<dyn Clock> = match &config.sync {
+            Some(SyncConfig::Follower { .. }) => sync_clock.clone(),
+            _ => clock,
+        };
+
+        let recorder = Self::make_recorder(clock.clone());
+        light_client_builder =
+            light_client_builder.with(RecordingLightClient::new(recorder.clone()));
+
+        let mut power_meters = light_client_builder.power_meters();
+        let mut light_client = light_client_builder.build();
+        if let Some(limit) = config.power_limit {
+            info!("Limiting power of all lights to {:.2} A", limit.max_amps);
+            let limiter = PowerLimiter::new(light_client, limit, "all");
+            power_meters.insert(0, limiter.meter());
+            light_client = Box::new(limiter);
+        }
+
+        let points = points_from_path(&config.points_path)?;
+        let zones = config
+            .zones
+            .iter()
+            .map(|zone| {
+                let indices = zone.points.select(&points);
+                let zone_points = indices.iter().map(|i| points[*i].position()).collect();
+                info!("Zone {} has {} points", zone.name, indices.len());
+                Zone::new(zone.name.clone(), indices, zone_points)
+            })
+            .collect();
+        let main = Zone::new(
+            String::new(),
+            (0..points.len()).collect(),
+            points.iter().map(Point::position).collect(),
+        );
+
+        let sync_leader = match &config.sync {
+            Some(SyncConfig::Leader { listen }) => Some(SyncLeader::bind(*listen, clock.clone())?),
+            _ => None,
+        };
+
+        let mut controller = Self::new(
+            light_client,
+            main,
+            zones,
+            Self::make_factory(config, clock.clone())?,
+            config,
+            clock.clone(),
+            sync_leader,
+        );
+        controller.power_meters = power_meters;
+        controller.recorder = recorder;
+
+        if let Some(SyncConfig::Follower { leader }) = &config.sync {
+            let (sender, receiver) = mpsc::channel(16);
+            tokio::spawn(sync::follow(leader.clone(), sync_clock, sender));
+            tokio::spawn(Self::follow_leader(
+                controller.state.clone(),
+                Self::make_factory(config, clock)?,
+                receiver,
+            ));
+        }
+        Ok(controller)
+    }
+
+    /// Creates a recorder timing frames with the controller clock.
+    fn make_recorder(clock: Arc<dyn Clock>) -> Recorder {
+        Recorder::new(Arc::new(move || clock.now()))
+    }
+
+    /// Creates a factory for plugins reading the wall clock from the controller clock.
+    fn make_factory(
+        config: &ControllerConfig,
+        clock: Arc<dyn Clock>,
+    ) -> Result<AnimationFactory, AnimationFactoryError> {
+        let mut factory = AnimationFactory::from_config(config)?;
+        factory.set_host(PluginHost {
+            clock: Arc::new(move || clock.now().into()),
+            seed: None,
+        });
+        Ok(factory)
+    }
+
+    /// Returns meters of power limited lights, starting with the limit for all
+    /// lights, if configured.
+    pub fn power_meters(&self) -> &[PowerMeter] {
+        &self.power_meters
+    }
+
+    pub async fn post_processing(&self) -> PostProcessing {
+        self.state.lock().await.post_processing
+    }
+
+    /// Sets adjustments applied to all frames, regardless of the animations
+    /// running. Values outside of supported ranges are clamped.
+    pub async fn set_post_processing(&self, post_processing: PostProcessing) -> PostProcessing {
+        let mut state = self.state.lock().await;
+        state.post_processing = post_processing.clamped();
+        state.request_frame();
+        state.post_processing
+    }
+
+    /// Starts recording displayed frames to a file, replacing the recording
+    /// in progress, if any.
+    pub fn start_recording(&self, path: &Path) -> Result<(), ControllerError> {
+        info!("Recording frames to {}", path.display());
+        self.recorder
+            .start(path)
+            .map_err(|e| ControllerError::InternalError {
+                reason: format!("failed to start recording: {e}"),
+            })
+    }
+
+    /// Stops recording, returning whether a recording was in progress.
+    pub fn stop_recording(&self) -> Result<bool, ControllerError> {
+        self.recorder
+            .stop()
+            .map_err(|e| ControllerError::InternalError {
+                reason: format!("failed to finish recording: {e}"),
+            })
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_recording()
+    }
+
+    /// Displays frames from a recording instead of the animations, until the
+    /// recording ends or forever if looped. Frames are sent as recorded,
+    /// without the current post-processing.
+    pub async fn start_replay(&self, path: &Path, looped: bool) -> Result<(), ControllerError> {
+        let frames = read_recording(path).map_err(|e| ControllerError::InternalError {
+            reason: format!("failed to read recording: {e}"),
+        })?;
+        info!("Replaying {} frames from {}", frames.len(), path.display());
+
+        let mut state = self.state.lock().await;
+        let now = state.clock.now();
+        state.replay = Some(Replay::new(frames, now, looped));
+        state.request_frame();
+        Ok(())
+    }
+
+    /// Stops the replay in progress and resumes the animations.
+    pub async fn stop_replay(&self) {
+        let mut state = self.state.lock().await;
+        if state.replay.take().is_some() {
+            state.last_frame = state.clock.now();
+            state.request_frame();
+        }
+    }
+
+    pub async fn is_replaying(&self) -> bool {
+        self.state.lock().await.replay.is_some()
     }
 
     pub async fn restart_event_generators(&self) {
@@ -287,52 +910,233 @@ impl Controller {
             })
     }
 
+    /// Switches the given zone (or the main zone, if none is given) to a new
+    /// animation. If no transition is provided, the default transition from the
+    /// configuration is used. Transitions with zero duration result in an
+    /// immediate switch.
     pub async fn switch_animation(
         &self,
+        zone: Option<&str>,
         animation: AnimationPlugin,
+        transition: Option<Transition>,
     ) -> Result<Configuration, ControllerError> {
         let configuration = animation.configuration().await?;
+        let animation_id = animation.manifest().id.clone();
+        let transition = transition.or(self.default_transition);
         let mut state = self.state.lock().await;
-        state.set_animation(Some(animation)).await?;
+        state
+            .zone_mut(zone)?
+            .set_animation(Some(animation), transition)
+            .await?;
+        state.clear_failure(zone, None);
+        state.last_frame = state.clock.now();
+        state.request_frame();
+        let time = state.last_frame;
+        state.publish(SyncMessage::SwitchAnimation {
+            zone: zone.map(str::to_owned),
+            animation_id: Some(animation_id),
+            transition,
+            time,
+        });
         Ok(configuration)
     }
 
-    pub async fn turn_off(&self) {
+    pub async fn turn_off(&self, zone: Option<&str>) -> Result<(), ControllerError> {
         info!("Turning off the animation");
-        let _ = self.state.lock().await.set_animation(None).await;
+        let mut state = self.state.lock().await;
+        state.zone_mut(zone)?.set_animation(None, None).await?;
+        state.clear_failure(zone, None);
+        state.request_frame();
+        let time = state.clock.now();
+        state.publish(SyncMessage::SwitchAnimation {
+            zone: zone.map(str::to_owned),
+            animation_id: None,
+            transition: None,
+            time,
+        });
+        Ok(())
+    }
+
+    /// Returns animations replaced by the watchdog, which were not switched
+    /// away from since.
+    pub async fn failures(&self) -> Vec<AnimationFailure> {
+        self.state.lock().await.failures.clone()
+    }
+
+    /// Stops updating animations, keeping the lights on the last frame.
+    pub async fn pause(&self) {
+        info!("Pausing animations");
+        let mut state = self.state.lock().await;
+        state.paused = true;
+    }
+
+    /// Resumes updating animations, without accounting for the time spent paused.
+    pub async fn resume(&self) {
+        info!("Resuming animations");
+        let mut state = self.state.lock().await;
+        state.paused = false;
+        state.pending_step = None;
+        state.last_frame = state.clock.now();
+        state.request_frame();
+    }
+
+    /// Advances paused animations by the given number of seconds and renders
+    /// a single frame. Animations are paused first, if they are running.
+    pub async fn step(&self, delta: f64) {
+        let mut state = self.state.lock().await;
+        state.paused = true;
+        *state.pending_step.get_or_insert(0.0) += delta.max(0.0);
+        state.request_frame();
     }
 
-    pub async fn get_parameters(&self) -> Result<Configuration, ControllerError> {
+    pub async fn is_paused(&self) -> bool {
+        self.state.lock().await.paused
+    }
+
+    pub async fn zones(&self) -> Vec<ZoneInfo> {
+        self.state
+            .lock()
+            .await
+            .zones
+            .iter()
+            .map(Zone::info)
+            .collect()
+    }
+
+    /// Adds an animation as a layer on top of the base animation of a zone. If
+    /// a layer with the same key already exists, its animation is replaced, but
+    /// it keeps its position in the stack.
+    pub async fn add_layer(
+        &self,
+        zone: Option<&str>,
+        key: &str,
+        animation: AnimationPlugin,
+        opacity: f64,
+        blend_mode: BlendMode,
+    ) -> Result<Configuration, ControllerError> {
+        let configuration = animation.configuration().await?;
+        let animation_id = animation.manifest().id.clone();
+        let mut state = self.state.lock().await;
+        state
+            .zone_mut(zone)?
+            .add_layer(key, animation, opacity, blend_mode)
+            .await?;
+        state.clear_failure(zone, Some(key));
+        state.request_frame();
+        state.publish(SyncMessage::AddLayer {
+            zone: zone.map(str::to_owned),
+            key: key.to_owned(),
+            animation_id,
+            opacity,
+            blend_mode,
+        });
+        Ok(configuration)
+    }
+
+    pub async fn remove_layer(&self, zone: Option<&str>, key: &str) -> Result<(), ControllerError> {
+        let mut state = self.state.lock().await;
+        state.zone_mut(zone)?.remove_layer(key)?;
+        state.request_frame();
+        state.publish(SyncMessage::RemoveLayer {
+            zone: zone.map(str::to_owned),
+            key: key.to_owned(),
+        });
+        Ok(())
+    }
+
+    pub async fn set_layer_blending(
+        &self,
+        zone: Option<&str>,
+        key: &str,
+        opacity: f64,
+        blend_mode: BlendMode,
+    ) -> Result<(), ControllerError> {
+        let mut state = self.state.lock().await;
+        state
+            .zone_mut(zone)?
+            .set_layer_blending(key, opacity, blend_mode)?;
+        state.request_frame();
+        state.publish(SyncMessage::SetLayerBlending {
+            zone: zone.map(str::to_owned),
+            key: key.to_owned(),
+            opacity,
+            blend_mode,
+        });
+        Ok(())
+    }
+
+    pub async fn layers(&self, zone: Option<&str>) -> Result<Vec<LayerInfo>, ControllerError> {
+        Ok(self.state.lock().await.zone(zone)?.layers())
+    }
+
+    /// Returns the ID of the animation running in the given zone and layer.
+    /// Missing zone or layer refer to the main zone and its base animation.
+    pub async fn animation_id(&self, zone: Option<&str>, layer: Option<&str>) -> Option<String> {
+        self.state
+            .lock()
+            .await
+            .zone(zone)
+            .ok()
+            .and_then(|zone| zone.animation_id(layer))
+    }
+
+    /// Returns the state the animation running in the given zone and layer
+    /// wants preserved, if any.
+    pub async fn animation_state(
+        &self,
+        zone: Option<&str>,
+        layer: Option<&str>,
+    ) -> Result<Option<Vec<u8>>, ControllerError> {
         let state = self.state.lock().await;
-        let Some(animation) = &state.animation else {
-            return Err(ControllerError::NoAnimationSelected);
-        };
-        Ok(animation.configuration().await?)
+        match state.zone(zone)?.animation(layer) {
+            Ok(animation) => Ok(animation.save_state().await?),
+            Err(ControllerError::NoAnimationSelected) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn get_parameters(
+        &self,
+        zone: Option<&str>,
+        layer: Option<&str>,
+    ) -> Result<Configuration, ControllerError> {
+        let state = self.state.lock().await;
+        Ok(state.zone(zone)?.animation(layer)?.configuration().await?)
     }
 
     pub async fn get_parameter_values(
         &self,
+        zone: Option<&str>,
+        layer: Option<&str>,
     ) -> Result<HashMap<String, ParameterValue>, ControllerError> {
-        if let Some(animation) = &self.state.lock().await.animation {
-            Ok(animation.get_parameters().await?)
-        } else {
-            Ok(HashMap::new())
+        let state = self.state.lock().await;
+        match state.zone(zone)?.animation(layer) {
+            Ok(animation) => Ok(animation.get_parameters().await?),
+            Err(ControllerError::NoAnimationSelected) => Ok(HashMap::new()),
+            Err(e) => Err(e),
         }
     }
 
     pub async fn set_parameters(
         &mut self,
+        zone: Option<&str>,
+        layer: Option<&str>,
         parameters: &HashMap<String, ParameterValue>,
     ) -> Result<Configuration, ControllerError> {
         let mut state = self.state.lock().await;
-        if let Some(ref mut animation) = state.animation {
-            animation.set_parameters(parameters).await?;
-            let configuration = animation.configuration().await?;
-            state.next_frame = Utc::now();
-            Ok(configuration)
-        } else {
-            Err(ControllerError::NoAnimationSelected)
+        let animation = state.zone_mut(zone)?.animation_mut(layer)?;
+        animation.set_parameters(parameters).await?;
+        let configuration = animation.configuration().await?;
+        if state.sync_leader.is_some() {
+            let values = state.zone(zone)?.animation(layer)?.get_parameters().await?;
+            state.publish(SyncMessage::SetParameters {
+                zone: zone.map(str::to_owned),
+                layer: layer.map(str::to_owned),
+                values,
+            });
         }
+        state.request_frame();
+        Ok(configuration)
     }
 
     pub async fn join(self) -> Result<(), ControllerError> {
@@ -351,3 +1155,705 @@ impl Controller {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use animation_wasm_bindings::testing::TestPlugin;
+    use lightfx::{Color, TransitionKind};
+    use tokio::sync::broadcast;
+
+    use super::*;
+    use crate::ManualClock;
+
+    fn fade(duration_seconds: f64) -> Transition {
+        Transition {
+            kind: TransitionKind::Fade,
+            duration_seconds,
+        }
+    }
+
+    fn start_time() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+    }
+
+    fn frame_interval(fps: f64) -> Duration {
+        Duration::milliseconds((1000.0 / fps) as i64)
+    }
+
+    /// Configuration with points written to the given directory, which is
+    /// also where plugins are loaded from.
+    fn test_config(dir: &Path) -> ControllerConfig {
+        let points_path = dir.join("points.csv");
+        std::fs::write(&points_path, "0,0,0\n1,0,0\n0,1,0\n0,0,1\n").unwrap();
+        ControllerConfig {
+            points_path,
+            lights: Vec::new(),
+            power_limit: None,
+            plugin_path: dir.to_owned(),
+            default_transition: None,
+            zones: Vec::new(),
+            output_fps: None,
+            plugin_limits: Default::default(),
+            watchdog: Default::default(),
+            sync: None,
+            plugin_signing: Default::default(),
+        }
+    }
+
+    /// Creates controller state without any animations, so that frames can
+    /// be produced without compiled plugins. Zones in transition between no
+    /// animations still render at transition frame rate. Fallback requests
+    /// are returned instead of being handled, so that tests can decide when
+    /// they complete.
+    fn test_state(
+        config: &ControllerConfig,
+        clock: Arc<ManualClock>,
+    ) -> (ControllerState, mpsc::UnboundedReceiver<FallbackRequest>) {
+        let points = points_from_path(&config.points_path).unwrap();
+        let now = clock.now();
+        let (fallback_sender, fallback_receiver) = mpsc::unbounded_channel();
+        let state = ControllerState {
+            main: Zone::new(
+                String::new(),
+                (0..points.len()).collect(),
+                points.iter().map(Point::position).collect(),
+            ),
+            zones: Vec::new(),
+            last_frame: now,
+            next_frame: now,
+            event_generators: HashMap::new(),
+            watchdog: config.watchdog.clone(),
+            failures: Vec::new(),
+            fallback_sender,
+            sync_leader: None,
+            clock,
+            post_processing: PostProcessing::default(),
+            replay: None,
+            paused: false,
+            pending_step: None,
+            last_rendered: None,
+        };
+        (state, fallback_receiver)
+    }
+
+    /// Advances the clock frame by frame, until the controller sends a frame
+    /// or a few frames pass without one.
+    async fn next_frame(
+        clock: &ManualClock,
+        receiver: &mut mpsc::Receiver<Frame>,
+    ) -> Option<Frame> {
+        for _ in 0..10 {
+            clock.advance(frame_interval(30.0));
+            let real_timeout = std::time::Duration::from_millis(20);
+            if let Ok(frame) = tokio::time::timeout(real_timeout, receiver.recv()).await {
+                return frame;
+            }
+        }
+        None
+    }
+
+    fn try_later(result: PollFrameResult) -> Option<DateTime<Utc>> {
+        match result {
+            PollFrameResult::Ready(_) => None,
+            PollFrameResult::TryLater(when) => Some(when),
+        }
+    }
+
+    #[tokio::test]
+    async fn frames_are_paced_by_frame_rate() {
+        let dir = tempfile::tempdir().unwrap();
+        let clock = Arc::new(ManualClock::new(start_time()));
+        let state = Mutex::new(test_state(&test_config(dir.path()), clock.clone()).0);
+        state
+            .lock()
+            .await
+            .main
+            .set_animation(None, Some(fade(10.0)))
+            .await
+            .unwrap();
+        let start = clock.now();
+        let interval = frame_interval(30.0);
+
+        assert_eq!(
+            try_later(Controller::poll_next_frame(&state, start).await),
+            None
+        );
+        assert_eq!(
+            try_later(
+                Controller::poll_next_frame(&state, start + Duration::milliseconds(10)).await
+            ),
+            Some(start + interval)
+        );
+        assert_eq!(
+            try_later(Controller::poll_next_frame(&state, start + interval).await),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn delta_is_time_since_last_frame() {
+        let dir = tempfile::tempdir().unwrap();
+        let clock = Arc::new(ManualClock::new(start_time()));
+        let state = Mutex::new(test_state(&test_config(dir.path()), clock.clone()).0);
+        state
+            .lock()
+            .await
+            .main
+            .set_animation(None, Some(fade(1.0)))
+            .await
+            .unwrap();
+        let start = clock.now();
+
+        for millis in [0, 250, 500] {
+            let now = start + Duration::milliseconds(millis);
+            assert_eq!(
+                try_later(Controller::poll_next_frame(&state, now).await),
+                None
+            );
+            assert_eq!(state.lock().await.last_frame, now);
+        }
+        assert_eq!(state.lock().await.frame_rate(), 30.0);
+
+        // Transition is complete once deltas add up to its duration
+        let now = start + Duration::milliseconds(1000);
+        assert_eq!(
+            try_later(Controller::poll_next_frame(&state, now).await),
+            None
+        );
+        assert_eq!(state.lock().await.frame_rate(), 0.0);
+
+        // The frame scheduled before completion still gets rendered, but no more after it
+        let now = now + Duration::milliseconds(100);
+        assert_eq!(
+            try_later(Controller::poll_next_frame(&state, now).await),
+            None
+        );
+        let now = now + Duration::milliseconds(100);
+        assert_eq!(
+            try_later(Controller::poll_next_frame(&state, now).await),
+            Some(now + Duration::seconds(1))
+        );
+    }
+
+    #[tokio::test]
+    async fn requested_frame_is_rendered_immediately() {
+        let plugin_dir = tempfile::tempdir().unwrap();
+        let still = TestPlugin {
+            fps: 0.0,
+            ..TestPlugin::new("still")
+        }
+        .write_crab(plugin_dir.path());
+        let config = test_config(plugin_dir.path());
+        let clock = Arc::new(ManualClock::new(start_time()));
+        let (sender, mut receiver) = mpsc::channel(16);
+        let mut controller =
+            Controller::from_config_with_clock(&config, Some(sender), clock.clone()).unwrap();
+        let animation = AnimationFactory::from_config(&config)
+            .unwrap()
+            .make_from_path(&still, None)
+            .await
+            .unwrap();
+        // Frame rendered at startup, before any animation is running
+        assert!(next_frame(&clock, &mut receiver).await.is_some());
+
+        controller
+            .switch_animation(None, animation, None)
+            .await
+            .unwrap();
+        assert!(next_frame(&clock, &mut receiver).await.is_some());
+        assert!(next_frame(&clock, &mut receiver).await.is_none());
+
+        // Animations with zero frame rate are only rendered again on changes
+        controller
+            .set_parameters(None, None, &HashMap::new())
+            .await
+            .unwrap();
+        assert!(next_frame(&clock, &mut receiver).await.is_some());
+        assert!(next_frame(&clock, &mut receiver).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn paused_animations_only_advance_by_steps() {
+        let dir = tempfile::tempdir().unwrap();
+        let clock = Arc::new(ManualClock::new(start_time()));
+        let state = Mutex::new(test_state(&test_config(dir.path()), clock.clone()).0);
+        state
+            .lock()
+            .await
+            .main
+            .set_animation(None, Some(fade(1.0)))
+            .await
+            .unwrap();
+        let start = clock.now();
+
+        assert_eq!(
+            try_later(Controller::poll_next_frame(&state, start).await),
+            None
+        );
+        state.lock().await.paused = true;
+
+        // The last frame is sent again, but the transition does not progress
+        let now = start + Duration::seconds(5);
+        assert_eq!(
+            try_later(Controller::poll_next_frame(&state, now).await),
+            None
+        );
+        assert_eq!(state.lock().await.frame_rate(), 30.0);
+        assert_eq!(
+            try_later(Controller::poll_next_frame(&state, now).await),
+            Some(now + PAUSED_FRAME_INTERVAL)
+        );
+
+        {
+            let mut state = state.lock().await;
+            state.pending_step = Some(1.0);
+            state.request_frame();
+        }
+        assert_eq!(
+            try_later(Controller::poll_next_frame(&state, now).await),
+            None
+        );
+        assert_eq!(state.lock().await.frame_rate(), 0.0);
+    }
+
+    #[tokio::test]
+    async fn run_waits_for_the_clock() {
+        let dir = tempfile::tempdir().unwrap();
+        let clock = Arc::new(ManualClock::new(start_time()));
+        let state = test_state(&test_config(dir.path()), clock.clone()).0;
+        let state = Arc::new(Mutex::new(state));
+        state
+            .lock()
+            .await
+            .main
+            .set_animation(None, Some(fade(10.0)))
+            .await
+            .unwrap();
+        let (sender, mut receiver) = mpsc::channel(16);
+        let client = Box::new(client::feedback::FeedbackLightClient::new(sender));
+        let handle = tokio::spawn(Controller::run(state, client, clock.clone(), None));
+        let real_timeout = std::time::Duration::from_millis(100);
+
+        let frame = tokio::time::timeout(real_timeout, receiver.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(frame.pixels_iter().count(), 4);
+        assert!(
+            tokio::time::timeout(real_timeout, receiver.recv())
+                .await
+                .is_err()
+        );
+
+        clock.advance(frame_interval(30.0));
+        assert!(
+            tokio::time::timeout(real_timeout, receiver.recv())
+                .await
+                .unwrap()
+                .is_some()
+        );
+        handle.abort();
+    }
+
+    fn ready(result: PollFrameResult) -> Frame {
+        match result {
+            PollFrameResult::Ready(frame) => frame,
+            PollFrameResult::TryLater(_) => panic!("frame should be ready"),
+        }
+    }
+
+    #[tokio::test]
+    async fn switching_during_transition_starts_from_blended_frame() {
+        let plugin_dir = tempfile::tempdir().unwrap();
+        let dark = TestPlugin::new("dark").write_crab(plugin_dir.path());
+        let bright = TestPlugin {
+            blue: 200,
+            ..TestPlugin::new("bright")
+        }
+        .write_crab(plugin_dir.path());
+        let config = test_config(plugin_dir.path());
+        let factory = AnimationFactory::from_config(&config).unwrap();
+        let clock = Arc::new(ManualClock::new(start_time()));
+        let (mut state, _) = test_state(&config, clock);
+        let blue = |frame: Option<Frame>| -> Vec<u8> {
+            frame.unwrap().pixels_iter().map(|color| color.b).collect()
+        };
+
+        let animation = factory.make_from_path(&dark, None).await.unwrap();
+        state
+            .main
+            .set_animation(Some(animation), None)
+            .await
+            .unwrap();
+        let animation = factory.make_from_path(&bright, None).await.unwrap();
+        state
+            .main
+            .set_animation(Some(animation), Some(fade(1.0)))
+            .await
+            .unwrap();
+        let halfway = blue(state.main.render(0.5, &mut Vec::new()).await.unwrap());
+        assert!(halfway.iter().all(|b| (1..200).contains(b)));
+
+        // Switching back picks up where the interrupted transition was
+        let animation = factory.make_from_path(&dark, None).await.unwrap();
+        state
+            .main
+            .set_animation(Some(animation), Some(fade(1.0)))
+            .await
+            .unwrap();
+        assert_eq!(
+            blue(state.main.render(0.0, &mut Vec::new()).await.unwrap()),
+            halfway
+        );
+        assert!(
+            blue(state.main.render(1.0, &mut Vec::new()).await.unwrap())
+                .iter()
+                .all(|b| *b == 0)
+        );
+    }
+
+    #[tokio::test]
+    async fn failing_animation_is_replaced_by_fallback() {
+        let plugin_dir = tempfile::tempdir().unwrap();
+        let broken = TestPlugin {
+            failing: true,
+            ..TestPlugin::new("broken")
+        }
+        .write_crab(plugin_dir.path());
+        TestPlugin {
+            blue: 200,
+            ..TestPlugin::new("fallback")
+        }
+        .write_crab(plugin_dir.path());
+
+        let clock = Arc::new(ManualClock::new(start_time()));
+        let config = ControllerConfig {
+            watchdog: WatchdogConfig {
+                max_failures: 3,
+                fallback_animation: Some("fallback".to_owned()),
+            },
+            ..test_config(plugin_dir.path())
+        };
+        let factory = AnimationFactory::from_config(&config).unwrap();
+        let (state, mut fallback_receiver) = test_state(&config, clock.clone());
+        let state = Arc::new(Mutex::new(state));
+        {
+            let mut state = state.lock().await;
+            let animation = factory.make_from_path(&broken, None).await.unwrap();
+            state
+                .main
+                .set_animation(Some(animation), None)
+                .await
+                .unwrap();
+        }
+
+        for failures in 1..=2 {
+            assert!(try_later(Controller::poll_next_frame(&state, clock.now()).await).is_some());
+            clock.advance(frame_interval(30.0));
+            let state = state.lock().await;
+            assert_eq!(state.main.failures(None), failures);
+            assert_eq!(state.main.animation_id(None).as_deref(), Some("broken"));
+            assert!(state.failures.is_empty());
+        }
+
+        // The failing animation is turned off right away, and the fallback
+        // is started separately
+        assert!(try_later(Controller::poll_next_frame(&state, clock.now()).await).is_some());
+        {
+            let state = state.lock().await;
+            assert_eq!(state.main.animation_id(None), None);
+            assert_eq!(state.failures.len(), 1);
+            assert_eq!(state.failures[0].animation_id, "broken");
+            assert_eq!(
+                state.failures[0].fallback_animation_id.as_deref(),
+                Some("fallback")
+            );
+        }
+
+        let request = fallback_receiver.try_recv().unwrap();
+        let animation = make_animation(&factory, &request.animation_id, None).await;
+        state.lock().await.apply_fallback(request, animation).await;
+        assert_eq!(
+            state.lock().await.main.animation_id(None).as_deref(),
+            Some("fallback")
+        );
+        let frame = ready(Controller::poll_next_frame(&state, clock.now()).await);
+        assert!(frame.pixels_iter().all(|color| color.b == 200));
+    }
+
+    #[tokio::test]
+    async fn failing_layer_is_removed() {
+        let plugin_dir = tempfile::tempdir().unwrap();
+        let broken = TestPlugin {
+            failing: true,
+            ..TestPlugin::new("broken")
+        }
+        .write_crab(plugin_dir.path());
+        let config = ControllerConfig {
+            watchdog: WatchdogConfig {
+                max_failures: 2,
+                fallback_animation: Some("fallback".to_owned()),
+            },
+            ..test_config(plugin_dir.path())
+        };
+        let factory = AnimationFactory::from_config(&config).unwrap();
+        let clock = Arc::new(ManualClock::new(start_time()));
+        let (mut state, mut fallback_receiver) = test_state(&config, clock.clone());
+        let animation = factory.make_from_path(&broken, None).await.unwrap();
+        state
+            .main
+            .add_layer("top", animation, 1.0, BlendMode::Add)
+            .await
+            .unwrap();
+        let state = Mutex::new(state);
+
+        // Frames keep rendering without the failing layer
+        ready(Controller::poll_next_frame(&state, clock.now()).await);
+        assert_eq!(state.lock().await.main.failures(Some("top")), 1);
+        clock.advance(frame_interval(30.0));
+        ready(Controller::poll_next_frame(&state, clock.now()).await);
+
+        let state = state.lock().await;
+        assert!(state.main.layers().is_empty());
+        assert_eq!(state.failures.len(), 1);
+        assert_eq!(state.failures[0].layer.as_deref(), Some("top"));
+        assert_eq!(state.failures[0].animation_id, "broken");
+        assert_eq!(state.failures[0].fallback_animation_id, None);
+        assert!(fallback_receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn fallback_does_not_replace_animation_switched_to_meanwhile() {
+        let plugin_dir = tempfile::tempdir().unwrap();
+        let broken = TestPlugin {
+            failing: true,
+            ..TestPlugin::new("broken")
+        }
+        .write_crab(plugin_dir.path());
+        let other = TestPlugin::new("other").write_crab(plugin_dir.path());
+        TestPlugin::new("fallback").write_crab(plugin_dir.path());
+
+        let clock = Arc::new(ManualClock::new(start_time()));
+        let config = ControllerConfig {
+            watchdog: WatchdogConfig {
+                max_failures: 1,
+                fallback_animation: Some("fallback".to_owned()),
+            },
+            ..test_config(plugin_dir.path())
+        };
+        let factory = AnimationFactory::from_config(&config).unwrap();
+        let (mut state, mut fallback_receiver) = test_state(&config, clock.clone());
+        let animation = factory.make_from_path(&broken, None).await.unwrap();
+        state
+            .main
+            .set_animation(Some(animation), None)
+            .await
+            .unwrap();
+        let state = Mutex::new(state);
+
+        assert!(try_later(Controller::poll_next_frame(&state, clock.now()).await).is_some());
+        let request = fallback_receiver.try_recv().unwrap();
+
+        // Switching animations clears the failure
+        let mut state = state.into_inner();
+        let animation = factory.make_from_path(&other, None).await.unwrap();
+        state
+            .main
+            .set_animation(Some(animation), None)
+            .await
+            .unwrap();
+        state.failures.clear();
+
+        let animation = make_animation(&factory, &request.animation_id, None).await;
+        state.apply_fallback(request, animation).await;
+        assert_eq!(state.main.animation_id(None).as_deref(), Some("other"));
+    }
+
+    #[tokio::test]
+    async fn replayed_frames_are_not_post_processed_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let clock = Arc::new(ManualClock::new(start_time()));
+        let mut state = test_state(&test_config(dir.path()), clock.clone()).0;
+        state.post_processing.brightness = 0.5;
+        let recorded = Frame::new(4, Color::rgb(200, 100, 50));
+        state.replay = Some(Replay::new(
+            vec![(Duration::zero(), recorded.clone())],
+            clock.now(),
+            true,
+        ));
+        let state = Mutex::new(state);
+
+        let frame = ready(Controller::poll_next_frame(&state, clock.now()).await);
+        assert!(frame.pixels_iter().eq(recorded.pixels_iter()));
+    }
+
+    /// Passes the changes published by the leader so far on to the follower,
+    /// returning once the follower applied all of them. Clock messages are left
+    /// out, the test keeps the clocks in sync instead.
+    async fn forward_to_follower(
+        published: &mut broadcast::Receiver<SyncMessage>,
+        follower: &Arc<Mutex<ControllerState>>,
+        config: &ControllerConfig,
+    ) {
+        let (sender, receiver) = mpsc::channel(16);
+        let messages = std::iter::from_fn(|| published.try_recv().ok())
+            .filter(|message| !matches!(message, SyncMessage::Clock { .. }))
+            .collect::<Vec<_>>();
+        for message in messages {
+            sender.send(message).await.unwrap();
+        }
+        drop(sender);
+        let factory = AnimationFactory::from_config(config).unwrap();
+        Controller::follow_leader(follower.clone(), factory, receiver).await;
+    }
+
+    #[tokio::test]
+    async fn followers_apply_layer_changes_of_leader() {
+        let plugin_dir = tempfile::tempdir().unwrap();
+        TestPlugin::new("sparkles").write_crab(plugin_dir.path());
+        let config = test_config(plugin_dir.path());
+        let clock = Arc::new(ManualClock::new(start_time()));
+        let sync_leader = SyncLeader::bind("127.0.0.1:0".parse().unwrap(), clock.clone()).unwrap();
+        let mut published = sync_leader.subscribe();
+        let follower = Arc::new(Mutex::new(test_state(&config, clock.clone()).0));
+
+        let layer = |state: &ControllerState| {
+            state
+                .main
+                .layers()
+                .into_iter()
+                .map(|l| (l.key, l.animation_id, l.opacity, l.blend_mode))
+                .collect::<Vec<_>>()
+        };
+        sync_leader.publish(SyncMessage::AddLayer {
+            zone: None,
+            key: "top".to_owned(),
+            animation_id: "sparkles".to_owned(),
+            opacity: 1.0,
+            blend_mode: BlendMode::Add,
+        });
+        sync_leader.publish(SyncMessage::SetLayerBlending {
+            zone: None,
+            key: "top".to_owned(),
+            opacity: 0.5,
+            blend_mode: BlendMode::Screen,
+        });
+        sync_leader.publish(SyncMessage::SetParameters {
+            zone: None,
+            layer: Some("top".to_owned()),
+            values: HashMap::new(),
+        });
+        forward_to_follower(&mut published, &follower, &config).await;
+        assert_eq!(
+            layer(&*follower.lock().await),
+            vec![(
+                "top".to_owned(),
+                "sparkles".to_owned(),
+                0.5,
+                BlendMode::Screen
+            )]
+        );
+
+        sync_leader.publish(SyncMessage::RemoveLayer {
+            zone: None,
+            key: "top".to_owned(),
+        });
+        forward_to_follower(&mut published, &follower, &config).await;
+        assert!(layer(&*follower.lock().await).is_empty());
+    }
+
+    #[tokio::test]
+    async fn followers_switch_to_fallback_in_phase_with_leader() {
+        let plugin_dir = tempfile::tempdir().unwrap();
+        let broken = TestPlugin {
+            failing: true,
+            ..TestPlugin::new("broken")
+        }
+        .write_crab(plugin_dir.path());
+        TestPlugin {
+            blue: 200,
+            ..TestPlugin::new("fallback")
+        }
+        .write_crab(plugin_dir.path());
+
+        let leader_config = ControllerConfig {
+            watchdog: WatchdogConfig {
+                max_failures: 1,
+                fallback_animation: Some("fallback".to_owned()),
+            },
+            ..test_config(plugin_dir.path())
+        };
+        let leader_clock = Arc::new(ManualClock::new(start_time()));
+        let (mut leader, mut fallback_receiver) = test_state(&leader_config, leader_clock.clone());
+        let sync_leader =
+            SyncLeader::bind("127.0.0.1:0".parse().unwrap(), leader_clock.clone()).unwrap();
+        let mut published = sync_leader.subscribe();
+        leader.sync_leader = Some(sync_leader);
+
+        // The follower never replaces failing animations on its own, and its
+        // clock is an hour off before applying the time of the leader
+        let follower_config = ControllerConfig {
+            watchdog: WatchdogConfig {
+                max_failures: u32::MAX,
+                fallback_animation: None,
+            },
+            ..test_config(plugin_dir.path())
+        };
+        let follower_clock = Arc::new(ManualClock::new(start_time() - Duration::hours(1)));
+        let (mut follower, _) = test_state(&follower_config, follower_clock.clone());
+        let sync_clock = Arc::new(SyncClock::new(follower_clock.clone()));
+        sync_clock.set_leader_time(leader_clock.now());
+        follower.clock = sync_clock.clone();
+        follower.last_frame = sync_clock.now();
+        follower.next_frame = sync_clock.now();
+
+        let factory = AnimationFactory::from_config(&leader_config).unwrap();
+        for state in [&mut leader, &mut follower] {
+            let animation = factory.make_from_path(&broken, None).await.unwrap();
+            state
+                .main
+                .set_animation(Some(animation), None)
+                .await
+                .unwrap();
+        }
+        let leader = Arc::new(Mutex::new(leader));
+        let follower = Arc::new(Mutex::new(follower));
+
+        // Steps of 1/8 s keep animation times exact, so the frames compare equal
+        let step = Duration::milliseconds(125);
+        let advance = || {
+            leader_clock.advance(step);
+            follower_clock.advance(step);
+        };
+        advance();
+        assert!(
+            try_later(Controller::poll_next_frame(&leader, leader_clock.now()).await).is_some()
+        );
+        let request = fallback_receiver.try_recv().unwrap();
+        let animation = make_animation(&factory, &request.animation_id, None).await;
+        leader.lock().await.apply_fallback(request, animation).await;
+
+        // The follower only learns about the fallback a few frames later
+        for _ in 0..3 {
+            ready(Controller::poll_next_frame(&leader, leader_clock.now()).await);
+            advance();
+        }
+        forward_to_follower(&mut published, &follower, &follower_config).await;
+        assert_eq!(
+            follower.lock().await.main.animation_id(None).as_deref(),
+            Some("fallback")
+        );
+
+        for _ in 0..4 {
+            let leader_frame =
+                ready(Controller::poll_next_frame(&leader, leader_clock.now()).await);
+            let follower_frame =
+                ready(Controller::poll_next_frame(&follower, sync_clock.now()).await);
+            assert!(leader_frame.pixels_iter().all(|color| color.b == 200));
+            assert!(leader_frame.pixels_iter().eq(follower_frame.pixels_iter()));
+            advance();
+        }
+    }
+}
@@ -3,11 +3,21 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use animation_api::plugin_config::PluginConfig;
-use animation_wasm_bindings::host::{AnimationPlugin, AnimationPluginError};
-use animation_wrapper::{PluginConfigError, unwrap};
+use animation_api::{
+    plugin_config::{PluginApiVersion, PluginConfig},
+    point::Point,
+};
+use animation_wasm_bindings::host::{
+    AnimationPlugin, AnimationPluginError, PluginHost, PluginLimits,
+};
+use animation_wrapper::{
+    PluginConfigError,
+    sign::{self, PluginSignatureError, VerifyingKey},
+    unwrap,
+};
 use itertools::Itertools;
-use log::info;
+use log::{info, warn};
+use serde::Deserialize;
 
 use crate::ControllerConfig;
 
@@ -30,19 +40,84 @@ pub enum AnimationFactoryError {
 
     #[error("invalid points file: {0}")]
     InvalidPointsFile(#[from] csv::Error),
+
+    #[error("no such zone: {0}")]
+    ZoneNotFound(String),
+
+    #[error("plugin signature check failed: {0}")]
+    InvalidSignature(#[from] PluginSignatureError),
+
+    #[error(
+        "plugin API version {version} is not supported, supported versions are: {}",
+        supported_api_versions()
+    )]
+    UnsupportedApiVersion { version: PluginApiVersion },
+}
+
+fn supported_api_versions() -> String {
+    AnimationPlugin::SUPPORTED_API_VERSIONS
+        .iter()
+        .map(ToString::to_string)
+        .join(", ")
 }
 
 pub struct AnimationFactory {
     plugin_dir: PathBuf,
-    points: Vec<(f64, f64, f64)>,
+    points: Vec<Point>,
+    zones: HashMap<String, Vec<Point>>,
+    limits: PluginLimits,
+    host: PluginHost,
+    /// Keys plugins must be signed with to be installed, or `None` if
+    /// signatures are not required.
+    trusted_keys: Option<Vec<VerifyingKey>>,
+}
+
+/// Row of the points file. Only the coordinates are required, the remaining
+/// columns can be left empty or omitted.
+#[derive(Deserialize)]
+struct PointRecord(
+    f64,
+    f64,
+    f64,
+    #[serde(default)] Option<u32>,
+    #[serde(default)] Option<u32>,
+    #[serde(default)] Option<String>,
+    #[serde(default)] Option<f64>,
+);
+
+impl From<PointRecord> for Point {
+    fn from(PointRecord(x, y, z, strand, index, tags, confidence): PointRecord) -> Self {
+        Self {
+            x,
+            y,
+            z,
+            strand,
+            index,
+            tags: tags
+                .iter()
+                .flat_map(|tags| tags.split_whitespace())
+                .map(ToOwned::to_owned)
+                .collect(),
+            confidence,
+        }
+    }
 }
 
-pub fn points_from_path(path: &Path) -> Result<Vec<(f64, f64, f64)>, AnimationFactoryError> {
-    let points: Vec<_> = csv::ReaderBuilder::new()
+fn points_from_reader(reader: impl std::io::Read) -> Result<Vec<Point>, csv::Error> {
+    csv::ReaderBuilder::new()
         .has_headers(false)
-        .from_path(path)?
-        .deserialize()
-        .try_collect()?;
+        .flexible(true)
+        .from_reader(reader)
+        .deserialize::<PointRecord>()
+        .map_ok(Point::from)
+        .try_collect()
+}
+
+/// Reads points from a CSV file without headers. Columns are: x, y, z, strand,
+/// index along the strand, tags separated by spaces and confidence.
+pub fn points_from_path(path: &Path) -> Result<Vec<Point>, AnimationFactoryError> {
+    let file = std::fs::File::open(path).map_err(csv::Error::from)?;
+    let points = points_from_reader(file)?;
 
     info!(
         "Loaded {} points from {}",
@@ -54,9 +129,40 @@ pub fn points_from_path(path: &Path) -> Result<Vec<(f64, f64, f64)>, AnimationFa
 
 impl AnimationFactory {
     pub fn from_config(config: &ControllerConfig) -> Result<Self, AnimationFactoryError> {
+        let points = points_from_path(&config.points_path)?;
+        let zones = config
+            .zones
+            .iter()
+            .map(|zone| {
+                let zone_points = zone
+                    .points
+                    .select(&points)
+                    .into_iter()
+                    .map(|i| points[i].clone())
+                    .collect();
+                (zone.name.clone(), zone_points)
+            })
+            .collect();
+        let trusted_keys = config
+            .plugin_signing
+            .require_signature
+            .then(|| {
+                config
+                    .plugin_signing
+                    .trusted_keys
+                    .iter()
+                    .map(|key| sign::verifying_key_from_hex(key))
+                    .try_collect()
+            })
+            .transpose()?;
+
         Ok(Self {
             plugin_dir: config.plugin_path.clone(),
-            points: points_from_path(&config.points_path)?,
+            points,
+            zones,
+            limits: config.plugin_limits,
+            host: PluginHost::default(),
+            trusted_keys,
         })
     }
 
@@ -69,23 +175,67 @@ impl AnimationFactory {
             })?
             .filter_map(|d| d.ok())
             .filter(|d| d.file_name().to_str().is_some_and(|d| d.ends_with(".crab")))
+            .filter(|d| match self.verify(&d.path()) {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!("Skipping plugin {}: {e}", d.path().display());
+                    false
+                }
+            })
             .filter_map(|d| Some(d.path().to_owned()).zip(unwrap::unwrap_plugin(d.path()).ok()))
+            .filter(|(path, manifest)| {
+                let supported = AnimationPlugin::supports(manifest.api_version);
+                if !supported {
+                    warn!(
+                        "Skipping plugin {} with unsupported API version {}",
+                        path.display(),
+                        manifest.api_version
+                    );
+                }
+                supported
+            })
             .map(|(path, manifest)| (manifest.id.clone(), PluginConfig { manifest, path }))
             .collect();
 
         Ok(plugins)
     }
 
+    /// Starts an animation plugin for the points of the given zone, or for all
+    /// points if no zone is given.
     pub async fn make_from_path(
         &self,
         path: &Path,
+        zone: Option<&str>,
     ) -> Result<AnimationPlugin, AnimationFactoryError> {
-        Ok(AnimationPlugin::new(path, self.points.clone()).await?)
+        let points = match zone {
+            None => self.points.clone(),
+            Some(zone) => self
+                .zones
+                .get(zone)
+                .ok_or_else(|| AnimationFactoryError::ZoneNotFound(zone.to_owned()))?
+                .clone(),
+        };
+        Ok(AnimationPlugin::new(path, points, self.limits, self.host.clone()).await?)
+    }
+
+    /// Checks that the plugin is signed by a trusted key, if signatures are
+    /// required.
+    pub fn verify(&self, path: &Path) -> Result<(), AnimationFactoryError> {
+        if let Some(trusted_keys) = &self.trusted_keys {
+            sign::verify_plugin(path, trusted_keys)?;
+        }
+        Ok(())
     }
 
     pub async fn install(&self, path: &Path) -> Result<PluginConfig, AnimationFactoryError> {
+        self.verify(path)?;
         let manifest = unwrap::unwrap_plugin(path)
             .map_err(|e| AnimationFactoryError::InvalidPlugin(PluginConfigError::InvalidCrab(e)))?;
+        if !AnimationPlugin::supports(manifest.api_version) {
+            return Err(AnimationFactoryError::UnsupportedApiVersion {
+                version: manifest.api_version,
+            });
+        }
 
         let new_path = self.plugin_dir.join(format!("{}.crab", manifest.id));
         tokio::fs::rename(path, &new_path).await?;
@@ -96,7 +246,103 @@ impl AnimationFactory {
         })
     }
 
-    pub fn points(&self) -> &[(f64, f64, f64)] {
+    /// Replaces the environment provided to plugins started from now on.
+    pub fn set_host(&mut self, host: PluginHost) {
+        self.host = host;
+    }
+
+    pub fn plugin_dir(&self) -> &Path {
+        &self.plugin_dir
+    }
+
+    pub fn points(&self) -> &[Point] {
         &self.points
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use animation_wasm_bindings::testing::TestPlugin;
+
+    use super::*;
+
+    fn factory(plugin_dir: &Path) -> AnimationFactory {
+        AnimationFactory {
+            plugin_dir: plugin_dir.to_owned(),
+            points: Vec::new(),
+            zones: HashMap::new(),
+            limits: PluginLimits::default(),
+            host: PluginHost::default(),
+            trusted_keys: None,
+        }
+    }
+
+    #[test]
+    fn points_have_optional_metadata() {
+        let csv = "0.1,0.2,0.3\n0.4,0.5,0.6,1,7,star top,0.9\n-1,-1,-1,2,,,\n";
+
+        let points = points_from_reader(csv.as_bytes()).unwrap();
+
+        assert_eq!(points[0], Point::from((0.1, 0.2, 0.3)));
+        assert_eq!(points[1].strand, Some(1));
+        assert_eq!(points[1].index, Some(7));
+        assert!(points[1].has_tag("star") && points[1].has_tag("top"));
+        assert_eq!(points[1].confidence, Some(0.9));
+        assert_eq!(points[2].strand, Some(2));
+        assert_eq!(points[2].index, None);
+        assert!(points[2].tags.is_empty());
+    }
+
+    #[tokio::test]
+    async fn only_plugins_with_supported_api_versions_are_installed() {
+        let plugin_dir = tempfile::tempdir().unwrap();
+        let upload_dir = tempfile::tempdir().unwrap();
+        let factory = factory(plugin_dir.path());
+
+        let future = TestPlugin {
+            api_version: PluginApiVersion::Unknown,
+            ..TestPlugin::new("future")
+        }
+        .write_crab(upload_dir.path());
+        assert!(matches!(
+            factory.install(&future).await,
+            Err(AnimationFactoryError::UnsupportedApiVersion {
+                version: PluginApiVersion::Unknown
+            })
+        ));
+        assert!(factory.discover().unwrap().is_empty());
+
+        let old = TestPlugin {
+            api_version: PluginApiVersion::V0_9,
+            ..TestPlugin::new("old")
+        }
+        .write_crab(upload_dir.path());
+        let installed = factory.install(&old).await.unwrap();
+        assert_eq!(installed.manifest.api_version, PluginApiVersion::V0_9);
+        assert_eq!(installed.path, plugin_dir.path().join("old.crab"));
+        assert!(factory.discover().unwrap().contains_key("old"));
+    }
+
+    #[test]
+    fn only_signed_plugins_are_discovered_when_signatures_are_required() {
+        let plugin_dir = tempfile::tempdir().unwrap();
+        let key = sign::generate_key();
+        let factory = AnimationFactory {
+            trusted_keys: Some(vec![key.verifying_key()]),
+            ..factory(plugin_dir.path())
+        };
+
+        let signed = TestPlugin::new("signed").write_crab(plugin_dir.path());
+        sign::sign_plugin(&signed, &key).unwrap();
+        let unsigned = TestPlugin::new("unsigned").write_crab(plugin_dir.path());
+
+        let plugins = factory.discover().unwrap();
+        assert!(plugins.contains_key("signed"));
+        assert!(!plugins.contains_key("unsigned"));
+        assert!(factory.verify(&signed).is_ok());
+        assert!(matches!(
+            factory.verify(&unsigned),
+            Err(AnimationFactoryError::InvalidSignature(_))
+        ));
+    }
+}
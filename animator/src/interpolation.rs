@@ -0,0 +1,97 @@
+use chrono::{DateTime, Duration, Utc};
+use lightfx::{BlendMode, Frame};
+
+/// Frames further apart than this are not interpolated, because they come
+/// from a change to the animations rather than from the animation running.
+const MAX_INTERPOLATED_SPAN: Duration = Duration::milliseconds(500);
+
+/// Produces output frames at a fixed rate by cross-fading between the last two
+/// rendered frames. Output lags behind rendering by one animation frame.
+#[derive(Default)]
+pub(crate) struct Interpolator {
+    previous: Option<(DateTime<Utc>, Frame)>,
+    current: Option<(DateTime<Utc>, Frame)>,
+    next_output: Option<DateTime<Utc>>,
+}
+
+impl Interpolator {
+    /// Adds a frame rendered by the animations at the given time.
+    pub(crate) fn push(&mut self, time: DateTime<Utc>, frame: Frame) {
+        self.previous = self.current.replace((time, frame));
+        self.next_output.get_or_insert(time);
+    }
+
+    /// Returns the time at which the next output frame is due, or `None` if
+    /// the output already caught up with the last rendered frame.
+    pub(crate) fn next_output(&self) -> Option<DateTime<Utc>> {
+        self.next_output
+    }
+
+    /// Returns the frame to display, if one is due at the given time.
+    pub(crate) fn poll(&mut self, now: DateTime<Utc>, interval: Duration) -> Option<Frame> {
+        if self.next_output.is_none_or(|next| now < next) {
+            return None;
+        }
+
+        let (frame, progress) = self.frame_at(now)?;
+        self.next_output = (progress < 1.0).then_some(now + interval);
+        Some(frame)
+    }
+
+    fn frame_at(&self, now: DateTime<Utc>) -> Option<(Frame, f64)> {
+        let (current_time, current) = self.current.as_ref()?;
+        let Some((previous_time, previous)) = &self.previous else {
+            return Some((current.clone(), 1.0));
+        };
+
+        let span = *current_time - *previous_time;
+        if span <= Duration::zero() || span > MAX_INTERPOLATED_SPAN {
+            return Some((current.clone(), 1.0));
+        }
+        let progress = ((now - *current_time).num_milliseconds() as f64
+            / span.num_milliseconds() as f64)
+            .clamp(0.0, 1.0);
+        let frame = BlendMode::AlphaOver.blend_frames(previous, current, progress);
+        Some((frame, progress))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lightfx::Color;
+
+    use super::*;
+
+    fn brightness(frame: &Frame) -> u8 {
+        frame.pixels_iter().next().unwrap().r
+    }
+
+    #[test]
+    fn output_fades_between_rendered_frames() {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let interval = Duration::milliseconds(10);
+        let mut interpolator = Interpolator::default();
+
+        interpolator.push(start, Frame::new(1, Color::black()));
+        assert_eq!(brightness(&interpolator.poll(start, interval).unwrap()), 0);
+        assert_eq!(interpolator.next_output(), None);
+
+        let second = start + Duration::milliseconds(40);
+        interpolator.push(second, Frame::new(1, Color::white()));
+        assert_eq!(brightness(&interpolator.poll(second, interval).unwrap()), 0);
+        assert!(
+            interpolator
+                .poll(second + Duration::milliseconds(5), interval)
+                .is_none()
+        );
+
+        let halfway = interpolator
+            .poll(second + Duration::milliseconds(20), interval)
+            .unwrap();
+        assert!((1..255).contains(&brightness(&halfway)));
+
+        let end = second + Duration::milliseconds(40);
+        assert_eq!(brightness(&interpolator.poll(end, interval).unwrap()), 255);
+        assert_eq!(interpolator.next_output(), None);
+    }
+}
@@ -1,7 +1,15 @@
+mod clock;
 mod config;
 mod controller;
 mod factory;
+mod interpolation;
+mod replay;
+mod sync;
+mod zone;
 
-pub use config::ControllerConfig;
-pub use controller::{Controller, ControllerError};
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use config::{ControllerConfig, PluginSigningConfig, WatchdogConfig, ZoneConfig, ZoneSelector};
+pub use controller::{AnimationFailure, Controller, ControllerError};
 pub use factory::{AnimationFactory, AnimationFactoryError, points_from_path};
+pub use sync::{SyncClock, SyncConfig, SyncLeader, SyncMessage};
+pub use zone::{LayerInfo, ZoneInfo};
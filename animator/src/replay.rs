@@ -0,0 +1,116 @@
+use chrono::{DateTime, Duration, Utc};
+use lightfx::Frame;
+
+/// Plays back recorded frames in place of the animations.
+pub(crate) struct Replay {
+    frames: Vec<(Duration, Frame)>,
+    started: DateTime<Utc>,
+    looped: bool,
+}
+
+impl Replay {
+    pub(crate) fn new(
+        frames: Vec<(Duration, Frame)>,
+        started: DateTime<Utc>,
+        looped: bool,
+    ) -> Self {
+        Self {
+            frames,
+            started,
+            looped,
+        }
+    }
+
+    /// Length of a single pass over the recording. Looped replays repeat the
+    /// last frame for one frame interval before starting over.
+    fn length(&self) -> Duration {
+        match self.frames.as_slice() {
+            [] => Duration::zero(),
+            [.., (previous, _), (last, _)] => *last + (*last - *previous),
+            [(only, _)] => *only + Duration::milliseconds(1),
+        }
+    }
+
+    /// Returns the frame to display at the given time, and the time at which
+    /// the next frame is due, or `None` if the replay is over.
+    pub(crate) fn frame_at(&self, now: DateTime<Utc>) -> Option<(&Frame, DateTime<Utc>)> {
+        let length = self.length();
+        let mut elapsed = now - self.started;
+        let mut pass_start = self.started;
+        if self.looped && length > Duration::zero() {
+            let passes = elapsed.num_milliseconds() / length.num_milliseconds().max(1);
+            pass_start += length * passes as i32;
+            elapsed = now - pass_start;
+        }
+
+        let index = self
+            .frames
+            .partition_point(|(time, _)| *time <= elapsed)
+            .checked_sub(1)?;
+        let next = match self.frames.get(index + 1) {
+            Some((time, _)) => pass_start + *time,
+            None if self.looped || elapsed < length => pass_start + length,
+            None => return None,
+        };
+
+        Some((&self.frames[index].1, next))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lightfx::Color;
+
+    use super::*;
+
+    fn recording() -> Vec<(Duration, Frame)> {
+        [0, 100, 200]
+            .into_iter()
+            .map(|ms| {
+                (
+                    Duration::milliseconds(ms),
+                    Frame::new(1, Color::gray(ms as u8)),
+                )
+            })
+            .collect()
+    }
+
+    fn brightness(frame: &Frame) -> u8 {
+        frame.pixels_iter().next().unwrap().r
+    }
+
+    #[test]
+    fn plays_frames_at_recorded_times() {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let replay = Replay::new(recording(), start, false);
+
+        let (frame, next) = replay
+            .frame_at(start + Duration::milliseconds(150))
+            .unwrap();
+        assert_eq!(brightness(frame), 100);
+        assert_eq!(next, start + Duration::milliseconds(200));
+
+        assert!(
+            replay
+                .frame_at(start + Duration::milliseconds(250))
+                .is_some()
+        );
+        assert!(
+            replay
+                .frame_at(start + Duration::milliseconds(300))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn looped_replay_starts_over() {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let replay = Replay::new(recording(), start, true);
+
+        let (frame, next) = replay
+            .frame_at(start + Duration::milliseconds(1250))
+            .unwrap();
+        assert_eq!(brightness(frame), 0);
+        assert_eq!(next, start + Duration::milliseconds(1300));
+    }
+}
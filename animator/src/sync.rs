@@ -0,0 +1,465 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use animation_api::schema::ParameterValue;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use lightfx::{BlendMode, Transition};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::{broadcast, mpsc},
+};
+
+use crate::clock::{Clock, SystemClock};
+
+/// Interval at which the leader sends its time to followers.
+const CLOCK_INTERVAL: Duration = Duration::seconds(1);
+/// Time a follower waits before reconnecting to the leader.
+const RECONNECT_INTERVAL: Duration = Duration::seconds(5);
+
+/// Role of this server in synchronizing animations between multiple servers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncConfig {
+    /// Streams animation changes to followers connecting on the given address.
+    Leader { listen: SocketAddr },
+    /// Renders the same animations as the leader at the given address.
+    Follower { leader: String },
+}
+
+/// Messages sent from the leader to followers, as lines of JSON.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SyncMessage {
+    /// Current time of the leader.
+    Clock { time: DateTime<Utc> },
+    /// Animation of a zone switched at the given time of the leader. Missing
+    /// animation ID means the zone was turned off.
+    SwitchAnimation {
+        zone: Option<String>,
+        animation_id: Option<String>,
+        transition: Option<Transition>,
+        time: DateTime<Utc>,
+    },
+    /// Parameters of the animation of a zone, or of one of its layers, changed.
+    SetParameters {
+        zone: Option<String>,
+        #[serde(default)]
+        layer: Option<String>,
+        values: HashMap<String, ParameterValue>,
+    },
+    /// Layer added on top of a zone, or replaced if it has the same key.
+    AddLayer {
+        zone: Option<String>,
+        key: String,
+        animation_id: String,
+        opacity: f64,
+        blend_mode: BlendMode,
+    },
+    /// Layer removed from a zone.
+    RemoveLayer { zone: Option<String>, key: String },
+    /// Blending of a layer with the layers underneath it changed.
+    SetLayerBlending {
+        zone: Option<String>,
+        key: String,
+        opacity: f64,
+        blend_mode: BlendMode,
+    },
+}
+
+/// Messages bringing a follower up to date with a zone.
+#[derive(Default)]
+struct ZoneSnapshot {
+    switch: Option<SyncMessage>,
+    parameters: Option<SyncMessage>,
+    /// Messages adding the layers, in order, along with their parameters
+    layers: Vec<(SyncMessage, Option<SyncMessage>)>,
+}
+
+impl ZoneSnapshot {
+    fn layer_mut(&mut self, layer: &str) -> Option<&mut (SyncMessage, Option<SyncMessage>)> {
+        self.layers
+            .iter_mut()
+            .find(|(add, _)| matches!(add, SyncMessage::AddLayer { key, .. } if key == layer))
+    }
+}
+
+/// Messages needed to bring a newly connected follower up to date, per zone.
+#[derive(Default)]
+struct Snapshot {
+    zones: HashMap<Option<String>, ZoneSnapshot>,
+}
+
+impl Snapshot {
+    fn update(&mut self, message: &SyncMessage) {
+        match message {
+            SyncMessage::Clock { .. } => {}
+            SyncMessage::SwitchAnimation { zone, .. } => {
+                let zone = self.zones.entry(zone.clone()).or_default();
+                zone.switch = Some(message.clone());
+                zone.parameters = None;
+            }
+            SyncMessage::SetParameters { zone, layer, .. } => {
+                let Some(zone) = self.zones.get_mut(zone) else {
+                    return;
+                };
+                match layer {
+                    None if zone.switch.is_some() => zone.parameters = Some(message.clone()),
+                    None => {}
+                    Some(key) => {
+                        if let Some((_, parameters)) = zone.layer_mut(key) {
+                            *parameters = Some(message.clone());
+                        }
+                    }
+                }
+            }
+            SyncMessage::AddLayer { zone, key, .. } => {
+                let zone = self.zones.entry(zone.clone()).or_default();
+                match zone.layer_mut(key) {
+                    Some(layer) => *layer = (message.clone(), None),
+                    None => zone.layers.push((message.clone(), None)),
+                }
+            }
+            SyncMessage::RemoveLayer { zone, key } => {
+                if let Some(zone) = self.zones.get_mut(zone) {
+                    zone.layers.retain(
+                        |(add, _)| !matches!(add, SyncMessage::AddLayer { key: k, .. } if k == key),
+                    );
+                }
+            }
+            SyncMessage::SetLayerBlending {
+                zone,
+                key,
+                opacity,
+                blend_mode,
+            } => {
+                let layer = self
+                    .zones
+                    .get_mut(zone)
+                    .and_then(|zone| zone.layer_mut(key));
+                if let Some((
+                    SyncMessage::AddLayer {
+                        opacity: o,
+                        blend_mode: b,
+                        ..
+                    },
+                    _,
+                )) = layer
+                {
+                    *o = *opacity;
+                    *b = *blend_mode;
+                }
+            }
+        }
+    }
+
+    fn messages(&self) -> Vec<SyncMessage> {
+        self.zones
+            .values()
+            .flat_map(|zone| {
+                zone.switch.iter().chain(&zone.parameters).chain(
+                    zone.layers
+                        .iter()
+                        .flat_map(|(add, parameters)| std::iter::once(add).chain(parameters)),
+                )
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Accepts followers and streams animation changes and the time to them.
+pub struct SyncLeader {
+    sender: broadcast::Sender<SyncMessage>,
+    snapshot: Arc<Mutex<Snapshot>>,
+    local_addr: SocketAddr,
+}
+
+impl SyncLeader {
+    pub fn bind(addr: SocketAddr, clock: Arc<dyn Clock>) -> std::io::Result<Self> {
+        let listener = std::net::TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let listener = TcpListener::from_std(listener)?;
+        let local_addr = listener.local_addr()?;
+        info!("Leading synchronization of followers on {local_addr}");
+
+        let (sender, _) = broadcast::channel(64);
+        let snapshot = Arc::new(Mutex::new(Snapshot::default()));
+        tokio::spawn(Self::accept_followers(
+            listener,
+            sender.clone(),
+            snapshot.clone(),
+        ));
+        tokio::spawn(Self::send_clock(sender.clone(), clock));
+
+        Ok(Self {
+            sender,
+            snapshot,
+            local_addr,
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Receives the messages sent to followers from now on.
+    #[cfg(test)]
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<SyncMessage> {
+        self.sender.subscribe()
+    }
+
+    /// Sends the message to all connected followers, and to followers that
+    /// connect later, unless it is made obsolete by another message.
+    pub fn publish(&self, message: SyncMessage) {
+        self.snapshot.lock().unwrap().update(&message);
+        let _ = self.sender.send(message);
+    }
+
+    async fn send_clock(sender: broadcast::Sender<SyncMessage>, clock: Arc<dyn Clock>) {
+        loop {
+            let _ = sender.send(SyncMessage::Clock { time: clock.now() });
+            clock.sleep(CLOCK_INTERVAL).await;
+        }
+    }
+
+    async fn accept_followers(
+        listener: TcpListener,
+        sender: broadcast::Sender<SyncMessage>,
+        snapshot: Arc<Mutex<Snapshot>>,
+    ) {
+        loop {
+            let (stream, addr) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    warn!("Failed to accept follower: {e}");
+                    continue;
+                }
+            };
+            info!("Follower connected from {addr}");
+            let receiver = sender.subscribe();
+            let snapshot = snapshot.clone();
+            tokio::spawn(async move {
+                if let Err(e) = Self::serve_follower(stream, receiver, snapshot).await {
+                    info!("Follower {addr} disconnected: {e}");
+                }
+            });
+        }
+    }
+
+    async fn serve_follower(
+        mut stream: TcpStream,
+        mut receiver: broadcast::Receiver<SyncMessage>,
+        snapshot: Arc<Mutex<Snapshot>>,
+    ) -> std::io::Result<()> {
+        let mut messages = snapshot.lock().unwrap().messages();
+        loop {
+            for message in messages.drain(..) {
+                let mut line = serde_json::to_vec(&message)?;
+                line.push(b'\n');
+                stream.write_all(&line).await?;
+            }
+
+            match receiver.recv().await {
+                Ok(message) => messages.push(message),
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    // Some changes were missed, so start over from the current state
+                    messages = snapshot.lock().unwrap().messages();
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+}
+
+/// Clock following the time of the leader, so that animations on followers
+/// progress at the same pace and changes happen at the same moments.
+pub struct SyncClock {
+    /// Local clock, which the offset from the leader is applied to
+    base: Arc<dyn Clock>,
+    offset: Mutex<Duration>,
+}
+
+impl SyncClock {
+    pub fn new(base: Arc<dyn Clock>) -> Self {
+        Self {
+            base,
+            offset: Mutex::new(Duration::zero()),
+        }
+    }
+
+    pub fn set_leader_time(&self, time: DateTime<Utc>) {
+        *self.offset.lock().unwrap() = time - self.base.now();
+    }
+}
+
+impl Default for SyncClock {
+    fn default() -> Self {
+        Self::new(Arc::new(SystemClock))
+    }
+}
+
+#[async_trait]
+impl Clock for SyncClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.base.now() + *self.offset.lock().unwrap()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        self.base.sleep(duration).await;
+    }
+}
+
+/// Connects to the leader, reconnecting whenever the connection is lost.
+/// Clock messages adjust the clock, all other messages are passed on.
+pub(crate) async fn follow(
+    leader: String,
+    clock: Arc<SyncClock>,
+    sender: mpsc::Sender<SyncMessage>,
+) {
+    loop {
+        match receive_from_leader(&leader, &clock, &sender).await {
+            Ok(()) => info!("Leader {leader} closed the connection"),
+            Err(e) => warn!("Lost connection to leader {leader}: {e}"),
+        }
+        if sender.is_closed() {
+            return;
+        }
+        tokio::time::sleep(RECONNECT_INTERVAL.to_std().unwrap_or_default()).await;
+    }
+}
+
+async fn receive_from_leader(
+    leader: &str,
+    clock: &SyncClock,
+    sender: &mpsc::Sender<SyncMessage>,
+) -> std::io::Result<()> {
+    let stream = TcpStream::connect(leader).await?;
+    info!("Following leader {leader}");
+
+    let mut lines = BufReader::new(stream).lines();
+    while let Some(line) = lines.next_line().await? {
+        match serde_json::from_str(&line)? {
+            SyncMessage::Clock { time } => clock.set_leader_time(time),
+            message => {
+                if sender.send(message).await.is_err() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SystemClock;
+
+    async fn receive(receiver: &mut mpsc::Receiver<SyncMessage>) -> SyncMessage {
+        tokio::time::timeout(std::time::Duration::from_secs(1), receiver.recv())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    fn add_layer(key: &str, animation_id: &str) -> SyncMessage {
+        SyncMessage::AddLayer {
+            zone: None,
+            key: key.into(),
+            animation_id: animation_id.into(),
+            opacity: 1.0,
+            blend_mode: BlendMode::Add,
+        }
+    }
+
+    fn set_parameters(layer: Option<&str>, speed: f64) -> SyncMessage {
+        SyncMessage::SetParameters {
+            zone: None,
+            layer: layer.map(Into::into),
+            values: HashMap::from([("speed".into(), ParameterValue::Number(speed))]),
+        }
+    }
+
+    #[test]
+    fn snapshot_keeps_latest_state_of_layers() {
+        let switch = SyncMessage::SwitchAnimation {
+            zone: None,
+            animation_id: Some("rainbow".into()),
+            transition: None,
+            time: Utc::now(),
+        };
+        let mut snapshot = Snapshot::default();
+        for message in [
+            add_layer("sparkles", "sparkles"),
+            add_layer("snow", "snow"),
+            set_parameters(Some("snow"), 1.0),
+            switch.clone(),
+            set_parameters(None, 2.0),
+            add_layer("sparkles", "stars"),
+            set_parameters(Some("snow"), 3.0),
+            SyncMessage::SetLayerBlending {
+                zone: None,
+                key: "snow".into(),
+                opacity: 0.5,
+                blend_mode: BlendMode::Screen,
+            },
+            add_layer("fog", "fog"),
+            SyncMessage::RemoveLayer {
+                zone: None,
+                key: "fog".into(),
+            },
+        ] {
+            snapshot.update(&message);
+        }
+
+        assert_eq!(
+            snapshot.messages(),
+            vec![
+                switch,
+                set_parameters(None, 2.0),
+                add_layer("sparkles", "stars"),
+                SyncMessage::AddLayer {
+                    zone: None,
+                    key: "snow".into(),
+                    animation_id: "snow".into(),
+                    opacity: 0.5,
+                    blend_mode: BlendMode::Screen,
+                },
+                set_parameters(Some("snow"), 3.0),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn followers_receive_current_state_and_changes() {
+        let leader =
+            SyncLeader::bind("127.0.0.1:0".parse().unwrap(), Arc::new(SystemClock)).unwrap();
+        let switch = SyncMessage::SwitchAnimation {
+            zone: None,
+            animation_id: Some("rainbow".into()),
+            transition: None,
+            time: Utc::now(),
+        };
+        leader.publish(switch.clone());
+
+        let clock = Arc::new(SyncClock::default());
+        let (sender, mut receiver) = mpsc::channel(16);
+        tokio::spawn(follow(
+            leader.local_addr().to_string(),
+            clock.clone(),
+            sender,
+        ));
+        assert_eq!(receive(&mut receiver).await, switch);
+
+        let parameters = set_parameters(None, 2.0);
+        leader.publish(parameters.clone());
+        assert_eq!(receive(&mut receiver).await, parameters);
+    }
+}
@@ -0,0 +1,405 @@
+use animation_wasm_bindings::host::{AnimationPlugin, AnimationPluginError};
+use lightfx::{BlendMode, Frame, Transition};
+use log::warn;
+
+use crate::ControllerError;
+
+const TRANSITION_FPS: f64 = 30.0;
+
+/// What a zone transitions away from.
+enum TransitionSource {
+    Animation(Box<AnimationPlugin>),
+    /// Last frame of a transition interrupted by another switch, so that the
+    /// new transition starts from what was shown instead of jumping
+    Frame(Frame),
+}
+
+struct ActiveTransition {
+    from: Option<TransitionSource>,
+    transition: Transition,
+    elapsed: f64,
+    /// Most recently rendered blend of both animations
+    last_frame: Option<Frame>,
+}
+
+struct Layer {
+    key: String,
+    animation: AnimationPlugin,
+    fps: f64,
+    opacity: f64,
+    blend_mode: BlendMode,
+    /// Number of consecutive frames the layer failed to render
+    failures: u32,
+}
+
+/// Describes a layer rendered on top of the base animation of a zone.
+#[derive(Clone, Debug)]
+pub struct LayerInfo {
+    pub key: String,
+    pub animation_id: String,
+    pub opacity: f64,
+    pub blend_mode: BlendMode,
+}
+
+/// Describes a region of lights running its own animation.
+#[derive(Clone, Debug)]
+pub struct ZoneInfo {
+    pub name: String,
+    pub point_count: usize,
+    pub animation_id: Option<String>,
+}
+
+/// A subset of lights with its own animation stack: a base animation, which
+/// can transition into another one, and layers composited on top of it.
+pub(crate) struct Zone {
+    pub(crate) name: String,
+    /// Indices of zone points in the full frame
+    pub(crate) indices: Vec<usize>,
+    points: Vec<(f64, f64, f64)>,
+    animation: Option<AnimationPlugin>,
+    fps: f64,
+    transition: Option<ActiveTransition>,
+    layers: Vec<Layer>,
+    /// Number of consecutive frames the base animation failed to render
+    failures: u32,
+}
+
+impl Zone {
+    pub(crate) fn new(name: String, indices: Vec<usize>, points: Vec<(f64, f64, f64)>) -> Self {
+        Self {
+            name,
+            indices,
+            points,
+            animation: None,
+            fps: 0.0,
+            transition: None,
+            layers: Vec::new(),
+            failures: 0,
+        }
+    }
+
+    pub(crate) fn info(&self) -> ZoneInfo {
+        ZoneInfo {
+            name: self.name.clone(),
+            point_count: self.points.len(),
+            animation_id: self.animation_id(None),
+        }
+    }
+
+    pub(crate) async fn set_animation(
+        &mut self,
+        animation: Option<AnimationPlugin>,
+        transition: Option<Transition>,
+    ) -> Result<(), ControllerError> {
+        let fps = if let Some(animation) = &animation {
+            animation.get_fps().await?
+        } else {
+            0.0
+        };
+
+        let previous = std::mem::replace(&mut self.animation, animation);
+        let from = match self.transition.take() {
+            Some(ActiveTransition {
+                last_frame: Some(frame),
+                ..
+            }) => Some(TransitionSource::Frame(frame)),
+            // Nothing of the interrupted transition was shown yet
+            Some(ActiveTransition { from, .. }) => from,
+            None => previous.map(|previous| TransitionSource::Animation(Box::new(previous))),
+        };
+        self.transition = transition
+            .filter(|t| t.duration_seconds > 0.0)
+            .map(|transition| ActiveTransition {
+                from,
+                transition,
+                elapsed: 0.0,
+                last_frame: None,
+            });
+        self.fps = fps;
+        self.failures = 0;
+        Ok(())
+    }
+
+    /// Returns the number of consecutive frames the given layer, or the base
+    /// animation if no layer is given, failed to render.
+    pub(crate) fn failures(&self, layer: Option<&str>) -> u32 {
+        match layer {
+            None => self.failures,
+            Some(key) => self
+                .layers
+                .iter()
+                .find(|l| l.key == key)
+                .map_or(0, |l| l.failures),
+        }
+    }
+
+    pub(crate) fn frame_rate(&self) -> f64 {
+        let fps = self.layers.iter().map(|l| l.fps).fold(self.fps, f64::max);
+        if self.transition.is_some() {
+            fps.max(TRANSITION_FPS)
+        } else {
+            fps
+        }
+    }
+
+    /// Returns the animation of the given layer, or the base animation if no layer is given.
+    pub(crate) fn animation(
+        &self,
+        layer: Option<&str>,
+    ) -> Result<&AnimationPlugin, ControllerError> {
+        match layer {
+            None => self
+                .animation
+                .as_ref()
+                .ok_or(ControllerError::NoAnimationSelected),
+            Some(key) => self
+                .layers
+                .iter()
+                .find(|l| l.key == key)
+                .map(|l| &l.animation)
+                .ok_or_else(|| ControllerError::NoSuchLayer(key.to_owned())),
+        }
+    }
+
+    pub(crate) fn animation_mut(
+        &mut self,
+        layer: Option<&str>,
+    ) -> Result<&mut AnimationPlugin, ControllerError> {
+        match layer {
+            None => self
+                .animation
+                .as_mut()
+                .ok_or(ControllerError::NoAnimationSelected),
+            Some(key) => self
+                .layers
+                .iter_mut()
+                .find(|l| l.key == key)
+                .map(|l| &mut l.animation)
+                .ok_or_else(|| ControllerError::NoSuchLayer(key.to_owned())),
+        }
+    }
+
+    pub(crate) fn animation_id(&self, layer: Option<&str>) -> Option<String> {
+        self.animation(layer)
+            .ok()
+            .map(|animation| animation.manifest().id.clone())
+    }
+
+    /// Iterates over all animations of the zone, including layers.
+    pub(crate) fn animations(&self) -> impl Iterator<Item = &AnimationPlugin> {
+        self.animation
+            .iter()
+            .chain(self.layers.iter().map(|l| &l.animation))
+    }
+
+    pub(crate) async fn add_layer(
+        &mut self,
+        key: &str,
+        animation: AnimationPlugin,
+        opacity: f64,
+        blend_mode: BlendMode,
+    ) -> Result<(), ControllerError> {
+        let layer = Layer {
+            key: key.to_owned(),
+            fps: animation.get_fps().await?,
+            animation,
+            opacity: opacity.clamp(0.0, 1.0),
+            blend_mode,
+            failures: 0,
+        };
+
+        match self.layers.iter_mut().find(|l| l.key == key) {
+            Some(existing) => *existing = layer,
+            None => self.layers.push(layer),
+        }
+        Ok(())
+    }
+
+    pub(crate) fn remove_layer(&mut self, key: &str) -> Result<(), ControllerError> {
+        let len = self.layers.len();
+        self.layers.retain(|l| l.key != key);
+        if self.layers.len() == len {
+            return Err(ControllerError::NoSuchLayer(key.to_owned()));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn set_layer_blending(
+        &mut self,
+        key: &str,
+        opacity: f64,
+        blend_mode: BlendMode,
+    ) -> Result<(), ControllerError> {
+        let layer = self
+            .layers
+            .iter_mut()
+            .find(|l| l.key == key)
+            .ok_or_else(|| ControllerError::NoSuchLayer(key.to_owned()))?;
+        layer.opacity = opacity.clamp(0.0, 1.0);
+        layer.blend_mode = blend_mode;
+        Ok(())
+    }
+
+    pub(crate) fn layers(&self) -> Vec<LayerInfo> {
+        self.layers
+            .iter()
+            .map(|l| LayerInfo {
+                key: l.key.clone(),
+                animation_id: l.animation.manifest().id.clone(),
+                opacity: l.opacity,
+                blend_mode: l.blend_mode,
+            })
+            .collect()
+    }
+
+    /// Renders the next frame of the zone, with one pixel per zone point, or
+    /// `None` if nothing runs in the zone.
+    ///
+    /// Animations exceeding their resource limits are unloaded, so that a single
+    /// misbehaving plugin cannot stall the whole display. Layers failing in other
+    /// ways are left out of the frame, and added to `failed_layers` along with
+    /// their errors.
+    pub(crate) async fn render(
+        &mut self,
+        delta: f64,
+        failed_layers: &mut Vec<(String, AnimationPluginError)>,
+    ) -> Result<Option<Frame>, AnimationPluginError> {
+        if self.is_idle() {
+            return Ok(None);
+        }
+
+        let mut frame = self.render_base(delta).await?;
+
+        let mut exceeded = Vec::new();
+        for layer in self.layers.iter_mut() {
+            match update_and_render(&layer.animation, delta).await {
+                Ok(top) => {
+                    layer.failures = 0;
+                    frame = layer.blend_mode.blend_frames(&frame, &top, layer.opacity);
+                }
+                Err(AnimationPluginError::BudgetExceeded(reason)) => {
+                    warn!(
+                        "Unloading layer {} of zone {}: {reason}",
+                        layer.key, self.name
+                    );
+                    exceeded.push(layer.key.clone());
+                }
+                Err(e) => {
+                    layer.failures += 1;
+                    failed_layers.push((layer.key.clone(), e));
+                }
+            }
+        }
+        self.layers.retain(|l| !exceeded.contains(&l.key));
+
+        Ok(Some(frame))
+    }
+
+    fn is_idle(&self) -> bool {
+        self.animation.is_none() && self.transition.is_none() && self.layers.is_empty()
+    }
+
+    /// Writes a frame rendered by the zone over its points in the full frame.
+    pub(crate) fn composite(&self, frame: &mut Frame, zone_frame: &Frame) {
+        for (index, color) in self.indices.iter().zip(zone_frame.pixels_iter()) {
+            frame.set_pixel(*index, *color);
+        }
+    }
+
+    async fn render_base(&mut self, delta: f64) -> Result<Frame, AnimationPluginError> {
+        let frame_size = self.points.len();
+        let frame = match self.animation {
+            Some(ref animation) => match update_and_render(animation, delta).await {
+                Err(AnimationPluginError::BudgetExceeded(reason)) => {
+                    warn!("Unloading animation of zone {}: {reason}", self.name);
+                    self.animation = None;
+                    self.fps = 0.0;
+                    Frame::new_black(frame_size)
+                }
+                Err(e) => {
+                    self.failures += 1;
+                    return Err(e);
+                }
+                Ok(frame) => {
+                    self.failures = 0;
+                    frame
+                }
+            },
+            None => Frame::new_black(frame_size),
+        };
+
+        let Some(ref mut transition) = self.transition else {
+            return Ok(frame);
+        };
+
+        transition.elapsed += delta;
+        let from_frame = match transition.from {
+            Some(TransitionSource::Animation(ref from)) => {
+                match update_and_render(from, delta).await {
+                    Ok(frame) => frame,
+                    Err(e) => {
+                        if let AnimationPluginError::BudgetExceeded(reason) = e {
+                            warn!(
+                                "Unloading previous animation of zone {}: {reason}",
+                                self.name
+                            );
+                            transition.from = None;
+                        }
+                        Frame::new_black(frame_size)
+                    }
+                }
+            }
+            Some(TransitionSource::Frame(ref frame)) => frame.clone(),
+            None => Frame::new_black(frame_size),
+        };
+        let progress = transition.elapsed / transition.transition.duration_seconds;
+        let frame = transition
+            .transition
+            .blend(&from_frame, &frame, progress, &self.points);
+        if progress >= 1.0 {
+            self.transition = None;
+        } else {
+            transition.last_frame = Some(frame.clone());
+        }
+
+        Ok(frame)
+    }
+}
+
+async fn update_and_render(
+    animation: &AnimationPlugin,
+    delta: f64,
+) -> Result<Frame, AnimationPluginError> {
+    animation.update(delta).await?;
+    animation.render().await
+}
+
+#[cfg(test)]
+mod tests {
+    use lightfx::Color;
+
+    use super::*;
+
+    fn colors(frame: &Frame) -> Vec<Color> {
+        frame.pixels_iter().copied().collect()
+    }
+
+    #[tokio::test]
+    async fn idle_zone_renders_nothing() {
+        let mut zone = Zone::new("star".to_owned(), vec![0, 1], vec![(0.0, 0.0, 0.0); 2]);
+        assert!(zone.render(0.1, &mut Vec::new()).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn zone_frame_is_written_over_zone_points_only() {
+        let zone = Zone::new("star".to_owned(), vec![1, 3], vec![(0.0, 0.0, 0.0); 2]);
+        let red = Color::rgb(255, 0, 0);
+        let blue = Color::rgb(0, 0, 255);
+        let green = Color::rgb(0, 255, 0);
+        let mut frame = Frame::new(5, red);
+
+        zone.composite(&mut frame, &Frame::from_vec(vec![blue, green]));
+
+        assert_eq!(colors(&frame), vec![red, blue, red, green, red]);
+    }
+}
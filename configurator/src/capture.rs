@@ -492,13 +492,22 @@ impl Capturer {
         coordinates
             .iter()
             .map(|p| {
-                if p.confident() {
+                let light = if p.confident() {
                     p.inner
                 } else {
                     Vector3::new(-1.0, -1.0, -1.0)
-                }
+                };
+                // Strand, index and tags are left for the user to fill in
+                writer.serialize((
+                    light[0],
+                    light[1],
+                    light[2],
+                    None::<u32>,
+                    None::<u32>,
+                    None::<String>,
+                    p.confidence,
+                ))
             })
-            .map(|light| writer.serialize((light[0], light[1], light[2])))
             .collect::<Result<Vec<_>, _>>()?;
         Ok(())
     }
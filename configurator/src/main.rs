@@ -134,6 +134,7 @@ fn capturer_from_options(
         .map(|endpoint| LightsConfig {
             endpoint,
             byte_order: ByteOrder::Rgb,
+            power_limit: None,
         })
         .into_iter()
         .collect_vec();
@@ -235,6 +235,8 @@ impl EventGenerator for FftEventGenerator {
             id: "device".to_owned(),
             name: "Input device".to_owned(),
             description: None,
+            group: None,
+            visible_when: None,
             value: ValueSchema::Enum {
                 values: host
                     .input_devices()
@@ -5,9 +5,9 @@ use animation_api::{
     schema::{EnumOption, ParameterSchema, ParameterValue, ValueSchema},
 };
 use anyhow::anyhow;
-use log::info;
+use log::{info, warn};
 use midi_msg::{ChannelVoiceMsg, MidiMsg, ReceiverContext};
-use midir::{MidiInput, MidiInputConnection, PortInfoError};
+use midir::{MidiInput, MidiInputConnection};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tokio::sync::mpsc;
@@ -71,19 +71,23 @@ pub struct Parameters {
 }
 
 impl Parameters {
-    fn from(midi_input: &MidiInput) -> Self {
+    fn from(midi_input: Option<&MidiInput>) -> Self {
         Self {
             device: midi_input
-                .ports()
-                .first()
-                .and_then(|p| midi_input.port_name(p).ok())
+                .and_then(|midi_input| {
+                    midi_input
+                        .ports()
+                        .first()
+                        .and_then(|p| midi_input.port_name(p).ok())
+                })
                 .unwrap_or("default".into()),
         }
     }
 }
 
 pub struct MidiEventGenerator {
-    midi_input: Mutex<MidiInput>,
+    /// Missing if the system has no MIDI support
+    midi_input: Mutex<Option<MidiInput>>,
     midi_stream: Mutex<Option<MidiStream>>,
     event_sender: mpsc::Sender<Event>,
     parameters: Parameters,
@@ -91,8 +95,10 @@ pub struct MidiEventGenerator {
 
 impl MidiEventGenerator {
     pub fn new(event_sender: mpsc::Sender<Event>) -> Self {
-        let midi_input = MidiInput::new("Rustmas Parameters").unwrap();
-        let parameters = Parameters::from(&midi_input);
+        let midi_input = MidiInput::new("Rustmas Parameters")
+            .inspect_err(|e| warn!("MIDI input is not available: {e}"))
+            .ok();
+        let parameters = Parameters::from(midi_input.as_ref());
 
         Self {
             midi_input: Mutex::new(midi_input),
@@ -119,19 +125,22 @@ impl EventGenerator for MidiEventGenerator {
             id: "device".into(),
             name: "MIDI Input Device".into(),
             description: None,
+            group: None,
+            visible_when: None,
             value: ValueSchema::Enum {
                 values: midi_input
-                    .ports()
-                    .into_iter()
-                    .map(|port| -> Result<_, PortInfoError> {
-                        let name = midi_input.port_name(&port)?;
-                        Ok(EnumOption {
-                            name: name.clone(),
-                            description: None,
-                            value: name,
-                        })
+                    .iter()
+                    .flat_map(|midi_input| {
+                        midi_input
+                            .ports()
+                            .into_iter()
+                            .filter_map(|port| midi_input.port_name(&port).ok())
+                    })
+                    .map(|name| EnumOption {
+                        name: name.clone(),
+                        description: None,
+                        value: name,
                     })
-                    .flat_map(|p| p.ok())
                     .collect(),
             },
         }]
@@ -10,6 +10,7 @@ use crate::{
     LightClient, LightClientError, LightsConfig, LightsEndpoint,
     backoff_decorator::WithBackoff,
     config::{ByteOrder, TtyLightsConfig},
+    power_limiter::{PowerLimit, PowerLimiter, PowerMeter},
     protocols::{
         HttpLightClient, ProtocolLightClient, TcpLightClient, TtyLightClient, UdpLightClient,
     },
@@ -60,6 +61,7 @@ impl LightClient for CombinedLightClient {
 #[derive(Default)]
 pub struct CombinedLightClientBuilder {
     clients: Vec<Box<dyn LightClient + Send + Sync>>,
+    power_meters: Vec<PowerMeter>,
 }
 
 impl CombinedLightClientBuilder {
@@ -89,10 +91,33 @@ impl CombinedLightClientBuilder {
                 }
                 LightsEndpoint::Tty(TtyLightsConfig::Path(_path)) => unimplemented!(),
             }
+            if let Some(limit) = config.power_limit {
+                let name = match &config.endpoint {
+                    LightsEndpoint::Remote(url) => url.to_string(),
+                    LightsEndpoint::Tty(_) => "tty".to_owned(),
+                };
+                self = self.limit_power_of_last(limit, name);
+            }
         }
         Ok(self)
     }
 
+    /// Applies a power limit to the most recently added lights.
+    fn limit_power_of_last(mut self, limit: PowerLimit, name: String) -> Self {
+        if let Some(client) = self.clients.pop() {
+            info!("Limiting power of {name} to {:.2} A", limit.max_amps);
+            let limiter = PowerLimiter::new(client, limit, name);
+            self.power_meters.push(limiter.meter());
+            self.clients.push(Box::new(limiter));
+        }
+        self
+    }
+
+    /// Returns meters of all power limited lights added so far.
+    pub fn power_meters(&self) -> Vec<PowerMeter> {
+        self.power_meters.clone()
+    }
+
     pub fn http_lights(self, url: &Url, byte_order: ByteOrder) -> Self {
         info!("Using http light client with endpoint {}", url);
         self.with(
@@ -3,6 +3,8 @@ use std::path::PathBuf;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
+use crate::power_limiter::PowerLimit;
+
 #[derive(Debug, Copy, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum ByteOrder {
@@ -18,6 +20,8 @@ pub struct LightsConfig {
     pub endpoint: LightsEndpoint,
     #[serde(default)]
     pub byte_order: ByteOrder,
+    #[serde(default)]
+    pub power_limit: Option<PowerLimit>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
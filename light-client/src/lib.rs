@@ -2,7 +2,9 @@ pub mod backoff_decorator;
 pub mod combined;
 mod config;
 pub mod feedback;
+pub mod power_limiter;
 mod protocols;
+pub mod recorder;
 
 pub use config::{ByteOrder, LightsConfig, LightsEndpoint, TtyLightsConfig};
 
@@ -0,0 +1,184 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use lightfx::{Color, Frame};
+use serde::{Deserialize, Serialize};
+
+use crate::{LightClient, LightClientError};
+
+/// Current drawn by each color channel of a single LED at full brightness.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LedCurrent {
+    pub red_milliamps: f64,
+    pub green_milliamps: f64,
+    pub blue_milliamps: f64,
+}
+
+impl Default for LedCurrent {
+    fn default() -> Self {
+        // Typical for WS2811/WS2812 LEDs
+        Self {
+            red_milliamps: 20.0,
+            green_milliamps: 20.0,
+            blue_milliamps: 20.0,
+        }
+    }
+}
+
+impl LedCurrent {
+    fn color_milliamps(&self, color: &Color) -> f64 {
+        (color.r as f64 * self.red_milliamps
+            + color.g as f64 * self.green_milliamps
+            + color.b as f64 * self.blue_milliamps)
+            / 255.0
+    }
+
+    /// Estimates the current drawn while displaying the frame, in amps.
+    pub fn frame_amps(&self, frame: &Frame) -> f64 {
+        frame
+            .pixels_iter()
+            .map(|c| self.color_milliamps(c))
+            .sum::<f64>()
+            / 1000.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PowerLimit {
+    /// Maximum total current drawn by the lights, in amps
+    pub max_amps: f64,
+    #[serde(default)]
+    pub current: LedCurrent,
+}
+
+impl PowerLimit {
+    /// Dims the frame uniformly, so that it stays within the limit.
+    pub fn apply(&self, frame: &Frame) -> (Frame, PowerReading) {
+        let estimated_amps = self.current.frame_amps(frame);
+        if estimated_amps <= self.max_amps {
+            let reading = PowerReading {
+                estimated_amps,
+                output_amps: estimated_amps,
+            };
+            return (frame.clone(), reading);
+        }
+
+        // Current is proportional to the PWM duty cycle, so scaling is linear
+        let scale = (self.max_amps / estimated_amps).max(0.0);
+        let dim = |c: u8| (c as f64 * scale).floor() as u8;
+        let frame: Frame = frame
+            .pixels_iter()
+            .map(|c| Color::rgb(dim(c.r), dim(c.g), dim(c.b)))
+            .collect();
+        let reading = PowerReading {
+            estimated_amps,
+            output_amps: self.current.frame_amps(&frame),
+        };
+        (frame, reading)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerReading {
+    /// Current the last frame would draw without limiting
+    pub estimated_amps: f64,
+    /// Current the last frame draws after limiting
+    pub output_amps: f64,
+}
+
+/// Shared view of the power drawn by lights behind a power limiter.
+#[derive(Clone)]
+pub struct PowerMeter {
+    name: String,
+    max_amps: f64,
+    reading: Arc<Mutex<Option<PowerReading>>>,
+}
+
+impl PowerMeter {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn max_amps(&self) -> f64 {
+        self.max_amps
+    }
+
+    /// Returns the reading for the most recently displayed frame, if any.
+    pub fn reading(&self) -> Option<PowerReading> {
+        *self.reading.lock().unwrap()
+    }
+}
+
+pub struct PowerLimiter {
+    inner: Box<dyn LightClient + Send + Sync>,
+    limit: PowerLimit,
+    meter: PowerMeter,
+}
+
+impl PowerLimiter {
+    pub fn new(
+        light_client: Box<dyn LightClient + Send + Sync>,
+        limit: PowerLimit,
+        name: impl Into<String>,
+    ) -> Self {
+        Self {
+            inner: light_client,
+            limit,
+            meter: PowerMeter {
+                name: name.into(),
+                max_amps: limit.max_amps,
+                reading: Arc::new(Mutex::new(None)),
+            },
+        }
+    }
+
+    pub fn meter(&self) -> PowerMeter {
+        self.meter.clone()
+    }
+}
+
+#[async_trait]
+impl LightClient for PowerLimiter {
+    async fn display_frame(&self, frame: &Frame) -> Result<(), LightClientError> {
+        let (frame, reading) = self.limit.apply(frame);
+        *self.meter.reading.lock().unwrap() = Some(reading);
+        self.inner.display_frame(&frame).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_within_limit_are_unchanged() {
+        let limit = PowerLimit {
+            max_amps: 1.0,
+            current: Default::default(),
+        };
+        let frame = Frame::new(10, Color::rgb(255, 0, 0));
+
+        let (limited, reading) = limit.apply(&frame);
+        assert_eq!(
+            limited.pixels_iter().collect::<Vec<_>>(),
+            frame.pixels_iter().collect::<Vec<_>>()
+        );
+        assert!((reading.estimated_amps - 0.2).abs() < 1e-9);
+        assert_eq!(reading.estimated_amps, reading.output_amps);
+    }
+
+    #[test]
+    fn frames_over_limit_are_dimmed() {
+        let limit = PowerLimit {
+            max_amps: 1.5,
+            current: Default::default(),
+        };
+        let frame = Frame::new(50, Color::white());
+
+        let (limited, reading) = limit.apply(&frame);
+        assert!((reading.estimated_amps - 3.0).abs() < 1e-9);
+        assert!(reading.output_amps <= 1.5);
+        assert!(reading.output_amps > 1.45);
+        assert!(limited.pixels_iter().all(|c| *c == Color::gray(127)));
+    }
+}
@@ -0,0 +1,248 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use lightfx::{Color, Frame};
+
+use crate::{LightClient, LightClientError};
+
+/// Identifies recording files, followed by the format version.
+const MAGIC: &[u8; 4] = b"RFRM";
+const VERSION: u8 = 1;
+/// Size of the time and pixel count preceding the pixels of each frame.
+const FRAME_HEADER_SIZE: u64 = 12;
+
+/// Source of the time frames are recorded at.
+pub type RecordingClock = Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>;
+
+/// Writes frames to a recording file.
+///
+/// The file starts with a header made of [`MAGIC`] and a version byte. Each
+/// frame is stored as the number of milliseconds since the start of the
+/// recording (u64), the number of pixels (u32), both little endian, followed
+/// by RGB values of the pixels.
+pub struct FrameWriter {
+    file: BufWriter<File>,
+    start: DateTime<Utc>,
+}
+
+impl FrameWriter {
+    /// Creates a recording starting at the given time.
+    pub fn create(path: &Path, start: DateTime<Utc>) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(MAGIC)?;
+        file.write_all(&[VERSION])?;
+        Ok(Self { file, start })
+    }
+
+    pub fn write(&mut self, time: Duration, frame: &Frame) -> io::Result<()> {
+        let pixels = frame.pixels_iter().collect::<Vec<_>>();
+        self.file
+            .write_all(&(time.num_milliseconds().max(0) as u64).to_le_bytes())?;
+        self.file.write_all(&(pixels.len() as u32).to_le_bytes())?;
+        for pixel in pixels {
+            self.file.write_all(&[pixel.r, pixel.g, pixel.b])?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Reads all frames from a recording file, together with their offsets from
+/// the start of the recording.
+pub fn read_recording(path: &Path) -> io::Result<Vec<(Duration, Frame)>> {
+    let file = File::open(path)?;
+    let mut remaining = file.metadata()?.len();
+    let mut file = BufReader::new(file);
+    let mut header = [0u8; 5];
+    file.read_exact(&mut header)?;
+    if &header[..4] != MAGIC || header[4] != VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a supported frame recording",
+        ));
+    }
+    remaining -= header.len() as u64;
+
+    let mut frames = Vec::new();
+    loop {
+        let mut time = [0u8; 8];
+        match file.read_exact(&mut time) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e),
+        }
+        let mut count = [0u8; 4];
+        file.read_exact(&mut count)?;
+        // Check the size before allocating, so that a corrupted count cannot
+        // exhaust memory
+        let size = u64::from(u32::from_le_bytes(count)) * 3;
+        remaining = remaining.saturating_sub(FRAME_HEADER_SIZE);
+        if size > remaining {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "frame is larger than the rest of the recording",
+            ));
+        }
+        remaining -= size;
+        let mut pixels = vec![0u8; size as usize];
+        file.read_exact(&mut pixels)?;
+
+        let frame = pixels
+            .chunks_exact(3)
+            .map(|c| Color::rgb(c[0], c[1], c[2]))
+            .collect();
+        frames.push((
+            Duration::milliseconds(u64::from_le_bytes(time) as i64),
+            frame,
+        ));
+    }
+    Ok(frames)
+}
+
+/// Handle for starting and stopping recording of frames passing through a
+/// [`RecordingLightClient`].
+#[derive(Clone)]
+pub struct Recorder {
+    writer: Arc<Mutex<Option<FrameWriter>>>,
+    clock: RecordingClock,
+}
+
+impl Recorder {
+    /// Creates a recorder timing frames with the given clock.
+    pub fn new(clock: RecordingClock) -> Self {
+        Self {
+            writer: Arc::default(),
+            clock,
+        }
+    }
+
+    /// Starts recording into a new file, finishing the previous recording, if any.
+    pub fn start(&self, path: &Path) -> io::Result<()> {
+        let writer = FrameWriter::create(path, (self.clock)())?;
+        if let Some(previous) = self.writer.lock().unwrap().replace(writer) {
+            previous.finish()?;
+        }
+        Ok(())
+    }
+
+    /// Stops recording, returning whether a recording was in progress.
+    pub fn stop(&self) -> io::Result<bool> {
+        match self.writer.lock().unwrap().take() {
+            Some(writer) => writer.finish().map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.writer.lock().unwrap().is_some()
+    }
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self::new(Arc::new(Utc::now))
+    }
+}
+
+/// Records frames while the recorder is started, without displaying them.
+pub struct RecordingLightClient {
+    recorder: Recorder,
+}
+
+impl RecordingLightClient {
+    pub fn new(recorder: Recorder) -> Self {
+        Self { recorder }
+    }
+}
+
+#[async_trait]
+impl LightClient for RecordingLightClient {
+    async fn display_frame(&self, frame: &Frame) -> Result<(), LightClientError> {
+        let mut writer = self.recorder.writer.lock().unwrap();
+        let Some(recording) = writer.as_mut() else {
+            return Ok(());
+        };
+
+        let time = (self.recorder.clock)() - recording.start;
+        recording
+            .write(time, frame)
+            .map_err(|e| LightClientError::ConnectionLost {
+                reason: format!("failed to record frame: {e}"),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recording.rfrm");
+        let frames = [
+            Frame::new(3, Color::rgb(1, 2, 3)),
+            Frame::new(3, Color::white()),
+        ];
+
+        let mut writer = FrameWriter::create(&path, Utc::now()).unwrap();
+        writer.write(Duration::zero(), &frames[0]).unwrap();
+        writer
+            .write(Duration::milliseconds(33), &frames[1])
+            .unwrap();
+        writer.finish().unwrap();
+
+        let recording = read_recording(&path).unwrap();
+        assert_eq!(recording.len(), 2);
+        assert_eq!(recording[1].0, Duration::milliseconds(33));
+        for ((_, read), written) in recording.iter().zip(frames.iter()) {
+            assert!(read.pixels_iter().eq(written.pixels_iter()));
+        }
+    }
+
+    #[test]
+    fn frames_larger_than_the_file_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recording.rfrm");
+        let mut data = MAGIC.to_vec();
+        data.push(VERSION);
+        data.extend(0u64.to_le_bytes());
+        data.extend(u32::MAX.to_le_bytes());
+        data.extend([0u8; 30]);
+        std::fs::write(&path, data).unwrap();
+
+        assert!(matches!(
+            read_recording(&path),
+            Err(e) if e.kind() == io::ErrorKind::InvalidData
+        ));
+    }
+
+    #[tokio::test]
+    async fn frames_are_timed_by_the_recorder_clock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recording.rfrm");
+        let start = DateTime::from_timestamp(1_000_000, 0).unwrap();
+        let now = Arc::new(Mutex::new(start));
+        let clock_now = now.clone();
+        let recorder = Recorder::new(Arc::new(move || *clock_now.lock().unwrap()));
+        let client = RecordingLightClient::new(recorder.clone());
+
+        recorder.start(&path).unwrap();
+        *now.lock().unwrap() += Duration::seconds(5);
+        client.display_frame(&Frame::new_black(2)).await.unwrap();
+        recorder.stop().unwrap();
+
+        let recording = read_recording(&path).unwrap();
+        assert_eq!(recording.len(), 1);
+        assert_eq!(recording[0].0, Duration::seconds(5));
+    }
+}
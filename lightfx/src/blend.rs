@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Color, Frame};
+
+/// Describes how a layer is combined with the layers underneath it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlendMode {
+    /// Component-wise sum of both layers, saturating at full brightness.
+    Add,
+    /// Component-wise product of both layers, useful for masking.
+    Multiply,
+    /// Inverse of multiplying inverted layers, brightens without saturating as quickly as add.
+    Screen,
+    /// Component-wise maximum of both layers.
+    Max,
+    /// The top layer covers the layers underneath it.
+    #[default]
+    AlphaOver,
+}
+
+impl BlendMode {
+    /// Blends a color of the top layer onto a color of the base layer. Opacity
+    /// is expected to be between 0.0 (only base visible) and 1.0 (full effect
+    /// of the blend mode).
+    pub fn blend(self, base: Color, top: &Color, opacity: f64) -> Color {
+        let component = |a: u8, b: u8| -> u8 {
+            let (a, b) = (a as f64 / 255.0, b as f64 / 255.0);
+            let c = match self {
+                BlendMode::Add => a + b,
+                BlendMode::Multiply => a * b,
+                BlendMode::Screen => 1.0 - (1.0 - a) * (1.0 - b),
+                BlendMode::Max => a.max(b),
+                BlendMode::AlphaOver => b,
+            };
+            (c.clamp(0.0, 1.0) * 255.0).round() as u8
+        };
+
+        let blended = Color {
+            r: component(base.r, top.r),
+            g: component(base.g, top.g),
+            b: component(base.b, top.b),
+        };
+        mix(base, &blended, opacity)
+    }
+
+    /// Blends a frame of the top layer onto a frame of the base layer. The
+    /// result has the size of the base frame, and missing pixels of the top
+    /// frame leave the base unchanged.
+    pub fn blend_frames(self, base: &Frame, top: &Frame, opacity: f64) -> Frame {
+        let mut top_pixels = top.pixels_iter();
+        base.pixels_iter()
+            .map(|b| match top_pixels.next() {
+                Some(t) => self.blend(*b, t, opacity),
+                None => *b,
+            })
+            .collect()
+    }
+}
+
+/// Interpolates between two colors, returning the exact endpoints for factors
+/// outside of the (0.0, 1.0) range.
+pub(crate) fn mix(a: Color, b: &Color, t: f64) -> Color {
+    // Gamma-corrected lerp does not land exactly on the endpoints due to rounding
+    if t <= 0.0 {
+        a
+    } else if t >= 1.0 {
+        *b
+    } else {
+        a.lerp(b, t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blend_modes() {
+        let base = Color::rgb(100, 200, 0);
+        let top = Color::rgb(200, 100, 255);
+
+        assert_eq!(
+            BlendMode::Add.blend(base, &top, 1.0),
+            Color::rgb(255, 255, 255)
+        );
+        assert_eq!(
+            BlendMode::Multiply.blend(base, &top, 1.0),
+            Color::rgb(78, 78, 0)
+        );
+        assert_eq!(
+            BlendMode::Screen.blend(base, &top, 1.0),
+            Color::rgb(222, 222, 255)
+        );
+        assert_eq!(
+            BlendMode::Max.blend(base, &top, 1.0),
+            Color::rgb(200, 200, 255)
+        );
+        assert_eq!(BlendMode::AlphaOver.blend(base, &top, 1.0), top);
+    }
+
+    #[test]
+    fn zero_opacity_keeps_base() {
+        let base = Color::rgb(100, 200, 0);
+        let top = Color::rgb(200, 100, 255);
+
+        for mode in [
+            BlendMode::Add,
+            BlendMode::Multiply,
+            BlendMode::Screen,
+            BlendMode::Max,
+            BlendMode::AlphaOver,
+        ] {
+            assert_eq!(mode.blend(base, &top, 0.0), base, "{mode:?}");
+        }
+    }
+}
@@ -1,5 +1,14 @@
+use serde::{Deserialize, Serialize};
+
 use crate::Color;
 
+/// Color at a position between 0.0 and 1.0 of a gradient.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct GradientStop {
+    pub position: f64,
+    pub color: Color,
+}
+
 pub struct Gradient {
     lut: Vec<Color>,
 }
@@ -12,6 +21,15 @@ impl Gradient {
         Self { lut }
     }
 
+    /// Produces a gradient passing through the colors of the stops at their
+    /// positions. The stops have to be sorted by position.
+    pub fn from_stops(stops: &[GradientStop]) -> Self {
+        let lut = (0..=255)
+            .map(|d| Self::compute_stops(stops, d as f64 / 255.0))
+            .collect();
+        Self { lut }
+    }
+
     fn compute(colors: &[Color], d: f64) -> Color {
         let d = d.clamp(0.0, 1.0) * (colors.len() as f64 - 1.0);
 
@@ -21,6 +39,18 @@ impl Gradient {
         colors[left].lerp(&colors[right], d)
     }
 
+    fn compute_stops(stops: &[GradientStop], d: f64) -> Color {
+        let right = stops.partition_point(|stop| stop.position < d);
+        match (stops.get(right.wrapping_sub(1)), stops.get(right)) {
+            (Some(left), Some(right)) if right.position > left.position => {
+                let d = (d - left.position) / (right.position - left.position);
+                left.color.lerp(&right.color, d)
+            }
+            (_, Some(stop)) | (Some(stop), None) => stop.color,
+            (None, None) => Color::black(),
+        }
+    }
+
     pub fn at(&self, d: f64) -> Color {
         let d = (d.clamp(0.0, 1.0) * 255.0) as usize;
         self.lut[d]
@@ -1,7 +1,13 @@
+mod blend;
 mod color;
 mod frame;
 mod gradient;
+mod post_processing;
+mod transition;
 
+pub use blend::BlendMode;
 pub use color::{Color, ColorWithAlpha};
 pub use frame::Frame;
-pub use gradient::Gradient;
+pub use gradient::{Gradient, GradientStop};
+pub use post_processing::PostProcessing;
+pub use transition::{Axis, Transition, TransitionKind};
@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Color, Frame};
+
+/// White point at which color temperature adjustment leaves colors unchanged.
+const NEUTRAL_TEMPERATURE: f64 = 6500.0;
+const NIGHT_MODE_BRIGHTNESS: f64 = 0.3;
+const NIGHT_MODE_TEMPERATURE: f64 = 2700.0;
+
+/// Adjustments applied to every frame right before it is displayed,
+/// regardless of the animations running.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PostProcessing {
+    /// Brightness factor, from 0.0 (off) to 1.0 (unchanged).
+    pub brightness: f64,
+    /// White point in kelvin. 6500K leaves colors unchanged, lower values make
+    /// them warmer and higher values make them cooler.
+    pub color_temperature: f64,
+    /// Saturation factor, from 0.0 (grayscale) through 1.0 (unchanged) up to
+    /// 2.0 (oversaturated).
+    pub saturation: f64,
+    /// Dims the lights and makes them warmer, on top of other adjustments.
+    pub night_mode: bool,
+}
+
+impl Default for PostProcessing {
+    fn default() -> Self {
+        Self {
+            brightness: 1.0,
+            color_temperature: NEUTRAL_TEMPERATURE,
+            saturation: 1.0,
+            night_mode: false,
+        }
+    }
+}
+
+impl PostProcessing {
+    /// Returns the settings with all values within their supported ranges.
+    pub fn clamped(self) -> Self {
+        Self {
+            brightness: self.brightness.clamp(0.0, 1.0),
+            color_temperature: self.color_temperature.clamp(1000.0, 40000.0),
+            saturation: self.saturation.clamp(0.0, 2.0),
+            night_mode: self.night_mode,
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn apply(&self, frame: &Frame) -> Frame {
+        if self.is_identity() {
+            return frame.clone();
+        }
+
+        let (brightness, temperature) = if self.night_mode {
+            (
+                self.brightness.min(NIGHT_MODE_BRIGHTNESS),
+                self.color_temperature.min(NIGHT_MODE_TEMPERATURE),
+            )
+        } else {
+            (self.brightness, self.color_temperature)
+        };
+        let white_balance = white_balance(temperature);
+
+        frame
+            .pixels_iter()
+            .map(|color| {
+                let (r, g, b) = saturate(*color, self.saturation);
+                let channel = |c: f64, balance: f64| {
+                    (c * balance * brightness).round().clamp(0.0, 255.0) as u8
+                };
+                Color::rgb(
+                    channel(r, white_balance.0),
+                    channel(g, white_balance.1),
+                    channel(b, white_balance.2),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Returns per channel factors shifting the white point to the given temperature.
+fn white_balance(temperature: f64) -> (f64, f64, f64) {
+    let target = Color::kelvin(temperature as i32);
+    let neutral = Color::kelvin(NEUTRAL_TEMPERATURE as i32);
+    let factor = |t: u8, n: u8| (t as f64 / n as f64).min(1.0);
+    (
+        factor(target.r, neutral.r),
+        factor(target.g, neutral.g),
+        factor(target.b, neutral.b),
+    )
+}
+
+fn saturate(color: Color, saturation: f64) -> (f64, f64, f64) {
+    let (r, g, b) = (color.r as f64, color.g as f64, color.b as f64);
+    let luma = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let channel = |c: f64| luma + (c - luma) * saturation;
+    (channel(r), channel(g), channel(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colors() -> Frame {
+        Frame::from_vec(vec![
+            Color::rgb(255, 0, 0),
+            Color::rgb(10, 200, 30),
+            Color::white(),
+        ])
+    }
+
+    #[test]
+    fn default_leaves_frame_unchanged() {
+        let frame = PostProcessing::default().apply(&colors());
+        assert!(frame.pixels_iter().eq(colors().pixels_iter()));
+    }
+
+    #[test]
+    fn zero_saturation_produces_grays() {
+        let settings = PostProcessing {
+            saturation: 0.0,
+            ..Default::default()
+        };
+        for color in settings.apply(&colors()).pixels_iter() {
+            assert_eq!(color.r, color.g);
+            assert_eq!(color.g, color.b);
+        }
+    }
+
+    #[test]
+    fn night_mode_dims_and_warms() {
+        let settings = PostProcessing {
+            night_mode: true,
+            ..Default::default()
+        };
+        let frame = settings.apply(&Frame::new(1, Color::white()));
+        let color = frame.pixels_iter().next().unwrap();
+        assert!(color.r <= (255.0 * NIGHT_MODE_BRIGHTNESS).round() as u8);
+        assert!(color.b < color.r);
+    }
+}
@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+
+use crate::blend::mix;
+use crate::{Color, Frame};
+
+/// Axis along which a wipe transition travels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Axis {
+    X,
+    #[default]
+    Y,
+    Z,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TransitionKind {
+    /// Linear fade of all lights at once.
+    Fade,
+    /// A soft edge travelling along the given axis, from lowest to highest
+    /// coordinate. Set `reverse` to travel in the opposite direction.
+    Wipe {
+        #[serde(default)]
+        axis: Axis,
+        #[serde(default)]
+        reverse: bool,
+    },
+    /// Lights switch over one by one in a pseudo-random order.
+    Dissolve,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Transition {
+    #[serde(flatten)]
+    pub kind: TransitionKind,
+    pub duration_seconds: f64,
+}
+
+const EDGE_WIDTH: f64 = 0.2;
+
+fn soft_step(progress: f64, threshold: f64) -> f64 {
+    if progress >= 1.0 {
+        // Avoid rounding errors leaving the last lights slightly short of the target
+        return 1.0;
+    }
+    ((progress * (1.0 + EDGE_WIDTH) - threshold) / EDGE_WIDTH).clamp(0.0, 1.0)
+}
+
+impl Transition {
+    /// Blends two frames according to the transition kind. Progress is expected
+    /// to be between 0.0 (only `from` visible) and 1.0 (only `to` visible).
+    ///
+    /// Points are only used by position-dependent transitions, like wipe.
+    /// If the frames have different sizes, the result has the size of `to`,
+    /// and missing pixels of `from` are treated as black.
+    pub fn blend(
+        &self,
+        from: &Frame,
+        to: &Frame,
+        progress: f64,
+        points: &[(f64, f64, f64)],
+    ) -> Frame {
+        let progress = progress.clamp(0.0, 1.0);
+        let from_pixels = from
+            .pixels_iter()
+            .copied()
+            .chain(std::iter::repeat(Color::black()));
+
+        match self.kind {
+            TransitionKind::Fade => from_pixels
+                .zip(to.pixels_iter())
+                .map(|(a, b)| mix(a, b, progress))
+                .collect(),
+            TransitionKind::Wipe { axis, reverse } => {
+                let coordinate = |(x, y, z): &(f64, f64, f64)| match axis {
+                    Axis::X => *x,
+                    Axis::Y => *y,
+                    Axis::Z => *z,
+                };
+                let (min, max) = points
+                    .iter()
+                    .map(coordinate)
+                    .fold((f64::MAX, f64::MIN), |(min, max), c| {
+                        (min.min(c), max.max(c))
+                    });
+                let range = (max - min).max(f64::EPSILON);
+
+                from_pixels
+                    .zip(to.pixels_iter())
+                    .enumerate()
+                    .map(|(i, (a, b))| {
+                        let position = points
+                            .get(i)
+                            .map(|p| (coordinate(p) - min) / range)
+                            .unwrap_or(0.0);
+                        let position = if reverse { 1.0 - position } else { position };
+                        mix(a, b, soft_step(progress, position))
+                    })
+                    .collect()
+            }
+            TransitionKind::Dissolve => from_pixels
+                .zip(to.pixels_iter())
+                .enumerate()
+                .map(|(i, (a, b))| {
+                    // Golden ratio sequence spreads thresholds evenly, while
+                    // keeping neighbouring lights far apart from each other
+                    let threshold = (i as f64 * 0.618_033_988_749_895).fract();
+                    mix(a, b, soft_step(progress, threshold))
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames() -> (Frame, Frame) {
+        (
+            Frame::new(4, Color::rgb(255, 0, 0)),
+            Frame::new(4, Color::rgb(0, 0, 255)),
+        )
+    }
+
+    #[test]
+    fn transition_endpoints() {
+        let (from, to) = frames();
+        let points = [
+            (0.0, 0.0, 0.0),
+            (0.0, 1.0, 0.0),
+            (0.0, 2.0, 0.0),
+            (0.0, 3.0, 0.0),
+        ];
+        for kind in [
+            TransitionKind::Fade,
+            TransitionKind::Wipe {
+                axis: Axis::Y,
+                reverse: false,
+            },
+            TransitionKind::Dissolve,
+        ] {
+            let transition = Transition {
+                kind,
+                duration_seconds: 1.0,
+            };
+            assert!(
+                transition
+                    .blend(&from, &to, 0.0, &points)
+                    .pixels_iter()
+                    .all(|c| *c == Color::rgb(255, 0, 0)),
+                "{kind:?} at start"
+            );
+            assert!(
+                transition
+                    .blend(&from, &to, 1.0, &points)
+                    .pixels_iter()
+                    .all(|c| *c == Color::rgb(0, 0, 255)),
+                "{kind:?} at end"
+            );
+        }
+    }
+
+    #[test]
+    fn wipe_follows_axis() {
+        let (from, to) = frames();
+        let points = [
+            (0.0, 0.0, 0.0),
+            (0.0, 1.0, 0.0),
+            (0.0, 2.0, 0.0),
+            (0.0, 3.0, 0.0),
+        ];
+        let transition = Transition {
+            kind: TransitionKind::Wipe {
+                axis: Axis::Y,
+                reverse: false,
+            },
+            duration_seconds: 1.0,
+        };
+        let pixels = transition
+            .blend(&from, &to, 0.5, &points)
+            .pixels_iter()
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(pixels[0], Color::rgb(0, 0, 255));
+        assert_eq!(pixels[3], Color::rgb(255, 0, 0));
+    }
+}
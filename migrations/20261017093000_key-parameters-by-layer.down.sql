@@ -0,0 +1,21 @@
+-- Add down migration script here
+CREATE TABLE
+    animation_parameters_old (
+        animation TEXT PRIMARY KEY,
+        parameters TEXT
+    );
+
+INSERT INTO
+    animation_parameters_old (animation, parameters)
+SELECT
+    animation,
+    parameters
+FROM
+    animation_parameters
+WHERE
+    layer = '';
+
+DROP TABLE animation_parameters;
+
+ALTER TABLE animation_parameters_old
+RENAME TO animation_parameters;
\ No newline at end of file
@@ -0,0 +1,21 @@
+-- Add up migration script here
+CREATE TABLE
+    animation_parameters_new (
+        animation TEXT NOT NULL,
+        layer TEXT NOT NULL DEFAULT '',
+        parameters TEXT,
+        PRIMARY KEY (animation, layer)
+    );
+
+INSERT INTO
+    animation_parameters_new (animation, parameters)
+SELECT
+    animation,
+    parameters
+FROM
+    animation_parameters;
+
+DROP TABLE animation_parameters;
+
+ALTER TABLE animation_parameters_new
+RENAME TO animation_parameters;
\ No newline at end of file
@@ -0,0 +1,24 @@
+-- Add down migration script here
+CREATE TABLE
+    animation_parameters_old (
+        animation TEXT NOT NULL,
+        layer TEXT NOT NULL DEFAULT '',
+        parameters TEXT,
+        PRIMARY KEY (animation, layer)
+    );
+
+INSERT INTO
+    animation_parameters_old (animation, layer, parameters)
+SELECT
+    animation,
+    layer,
+    parameters
+FROM
+    animation_parameters
+WHERE
+    zone = '';
+
+DROP TABLE animation_parameters;
+
+ALTER TABLE animation_parameters_old
+RENAME TO animation_parameters;
\ No newline at end of file
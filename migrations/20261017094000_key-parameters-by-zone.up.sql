@@ -0,0 +1,23 @@
+-- Add up migration script here
+CREATE TABLE
+    animation_parameters_new (
+        animation TEXT NOT NULL,
+        zone TEXT NOT NULL DEFAULT '',
+        layer TEXT NOT NULL DEFAULT '',
+        parameters TEXT,
+        PRIMARY KEY (animation, zone, layer)
+    );
+
+INSERT INTO
+    animation_parameters_new (animation, layer, parameters)
+SELECT
+    animation,
+    layer,
+    parameters
+FROM
+    animation_parameters;
+
+DROP TABLE animation_parameters;
+
+ALTER TABLE animation_parameters_new
+RENAME TO animation_parameters;
\ No newline at end of file
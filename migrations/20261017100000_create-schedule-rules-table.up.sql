@@ -0,0 +1,9 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS
+    schedule_rules (
+        id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        time BLOB NOT NULL,
+        days BLOB NOT NULL,
+        action BLOB NOT NULL
+    );
\ No newline at end of file
@@ -0,0 +1,6 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS
+    output_settings (
+        id INTEGER NOT NULL PRIMARY KEY CHECK (id = 0),
+        post_processing BLOB NOT NULL
+    );
\ No newline at end of file
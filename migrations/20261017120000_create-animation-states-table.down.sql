@@ -0,0 +1,2 @@
+-- Add down migration script here
+DROP TABLE animation_states;
\ No newline at end of file
@@ -0,0 +1,6 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS
+    animation_states (
+        animation TEXT NOT NULL PRIMARY KEY,
+        state BLOB NOT NULL
+    );
\ No newline at end of file
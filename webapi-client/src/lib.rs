@@ -5,13 +5,18 @@ use serde::{Serialize, de::DeserializeOwned};
 use url::Url;
 
 use web_sys::FormData;
-pub use webapi_model::{
-    Animation, Configuration, GetEventGeneratorSchemaResponse, GetParametersResponse,
-    GetPointsResponse, ListAnimationsResponse, ParameterValue, SwitchAnimationRequest,
-};
 use webapi_model::{
-    ApiResponse, Event, RemoveAnimationRequest, SendEventRequest, SetAnimationParametersRequest,
-    SetEventGeneratorParametersRequest, SwitchAnimationResponse,
+    AddLayerRequest, ApiResponse, CreatePlaylistRequest, Event, GetPowerUsageResponse,
+    ParametersQuery, PlaylistRequest, RecordingRequest, RemoveAnimationRequest, RemoveLayerRequest,
+    ReplayRequest, SendEventRequest, SetAnimationParametersRequest,
+    SetEventGeneratorParametersRequest, SetLayerBlendingRequest, StepRequest,
+    SwitchAnimationResponse,
+};
+pub use webapi_model::{
+    Animation, AnimationFailure, BlendMode, Configuration, GetEventGeneratorSchemaResponse,
+    GetParametersResponse, GetPointsResponse, Layer, ListAnimationsResponse, ListLayersResponse,
+    ListPlaylistsResponse, ListRecordingsResponse, ListZonesResponse, ParameterValue, Playlist,
+    PlaylistEntry, PlaylistMode, PostProcessing, PowerUsage, SwitchAnimationRequest, Zone,
 };
 
 #[derive(Debug, thiserror::Error)]
@@ -115,6 +120,61 @@ impl RustmasApiClient {
         self.post("events/send/", &SendEventRequest { event }).await
     }
 
+    pub async fn post_processing(&self) -> Result<PostProcessing> {
+        self.get::<PostProcessing>("output/").await
+    }
+
+    pub async fn set_post_processing(
+        &self,
+        post_processing: &PostProcessing,
+    ) -> Result<PostProcessing> {
+        self.post::<PostProcessing>("output/", post_processing)
+            .await
+    }
+
+    pub async fn list_recordings(&self) -> Result<ListRecordingsResponse> {
+        self.get::<ListRecordingsResponse>("output/recordings/")
+            .await
+    }
+
+    pub async fn start_recording(&self, name: &str) -> Result<ListRecordingsResponse> {
+        self.post::<ListRecordingsResponse>(
+            "output/recordings/start/",
+            &RecordingRequest {
+                name: name.to_owned(),
+            },
+        )
+        .await
+    }
+
+    pub async fn stop_recording(&self) -> Result<ListRecordingsResponse> {
+        self.post::<ListRecordingsResponse>("output/recordings/stop/", &())
+            .await
+    }
+
+    pub async fn start_replay(&self, name: &str, looped: bool) -> Result<ListRecordingsResponse> {
+        self.post::<ListRecordingsResponse>(
+            "output/replay/start/",
+            &ReplayRequest {
+                name: name.to_owned(),
+                looped,
+            },
+        )
+        .await
+    }
+
+    pub async fn stop_replay(&self) -> Result<ListRecordingsResponse> {
+        self.post::<ListRecordingsResponse>("output/replay/stop/", &())
+            .await
+    }
+
+    pub async fn power_usage(&self) -> Result<Vec<PowerUsage>> {
+        Ok(self
+            .get::<GetPowerUsageResponse>("output/power/")
+            .await?
+            .power)
+    }
+
     pub async fn list_animations(&self) -> Result<ListAnimationsResponse> {
         self.get::<ListAnimationsResponse>("animations/list/").await
     }
@@ -133,6 +193,8 @@ impl RustmasApiClient {
                 &SwitchAnimationRequest {
                     animation_id,
                     params: None,
+                    transition: None,
+                    zone: None,
                 },
             )
             .await?
@@ -173,6 +235,20 @@ impl RustmasApiClient {
         self.post("animations/turn_off/", &()).await
     }
 
+    pub async fn pause(&self) -> Result<()> {
+        self.post("animations/pause/", &()).await
+    }
+
+    pub async fn resume(&self) -> Result<()> {
+        self.post("animations/resume/", &()).await
+    }
+
+    /// Advances the animation by the given number of seconds, pausing it first if needed.
+    pub async fn step(&self, delta_seconds: f64) -> Result<()> {
+        self.post("animations/step/", &StepRequest { delta_seconds })
+            .await
+    }
+
     pub async fn get_params(&self) -> Result<Option<Configuration>> {
         Ok(self
             .get::<GetParametersResponse>("params/")
@@ -190,6 +266,37 @@ impl RustmasApiClient {
         .await
     }
 
+    pub async fn get_layer_params(&self, layer: String) -> Result<Option<Configuration>> {
+        let request = self
+            .client
+            .get(self.url("params/"))
+            .query(&ParametersQuery {
+                zone: None,
+                layer: Some(layer),
+            });
+        Ok(Self::send_request::<GetParametersResponse>(request)
+            .await?
+            .animation)
+    }
+
+    pub async fn set_layer_params(
+        &self,
+        layer: String,
+        params: &HashMap<String, ParameterValue>,
+    ) -> Result<()> {
+        let request = self
+            .client
+            .post(self.url("params/"))
+            .query(&ParametersQuery {
+                zone: None,
+                layer: Some(layer),
+            })
+            .json(&SetAnimationParametersRequest {
+                values: params.clone(),
+            });
+        Self::send_request(request).await
+    }
+
     pub async fn save_params(&self) -> Result<()> {
         let _ = self.post::<()>("params/save/", &()).await;
         Ok(())
@@ -208,6 +315,102 @@ impl RustmasApiClient {
             .await?
             .animation)
     }
+
+    pub async fn list_zones(&self) -> Result<ListZonesResponse> {
+        self.get::<ListZonesResponse>("animations/zones/").await
+    }
+
+    pub async fn list_layers(&self) -> Result<ListLayersResponse> {
+        self.get::<ListLayersResponse>("animations/layers/").await
+    }
+
+    pub async fn add_layer(
+        &self,
+        layer: String,
+        animation_id: String,
+        opacity: f64,
+        blend_mode: BlendMode,
+    ) -> Result<Configuration> {
+        Ok(self
+            .post::<SwitchAnimationResponse>(
+                "animations/layers/add/",
+                &AddLayerRequest {
+                    zone: None,
+                    layer,
+                    animation_id,
+                    opacity,
+                    blend_mode,
+                    params: None,
+                },
+            )
+            .await?
+            .animation)
+    }
+
+    pub async fn set_layer_blending(
+        &self,
+        layer: String,
+        opacity: f64,
+        blend_mode: BlendMode,
+    ) -> Result<ListLayersResponse> {
+        self.post(
+            "animations/layers/blending/",
+            &SetLayerBlendingRequest {
+                zone: None,
+                layer,
+                opacity,
+                blend_mode,
+            },
+        )
+        .await
+    }
+
+    pub async fn remove_layer(&self, layer: String) -> Result<ListLayersResponse> {
+        self.post(
+            "animations/layers/remove/",
+            &RemoveLayerRequest { zone: None, layer },
+        )
+        .await
+    }
+
+    pub async fn list_playlists(&self) -> Result<ListPlaylistsResponse> {
+        self.get::<ListPlaylistsResponse>("playlists/list/").await
+    }
+
+    pub async fn create_playlist(
+        &self,
+        name: String,
+        mode: PlaylistMode,
+        entries: Vec<PlaylistEntry>,
+    ) -> Result<ListPlaylistsResponse> {
+        self.post(
+            "playlists/create/",
+            &CreatePlaylistRequest {
+                name,
+                mode,
+                entries,
+            },
+        )
+        .await
+    }
+
+    pub async fn remove_playlist(&self, playlist_id: i64) -> Result<ListPlaylistsResponse> {
+        self.post("playlists/remove/", &PlaylistRequest { playlist_id })
+            .await
+    }
+
+    pub async fn start_playlist(&self, playlist_id: i64) -> Result<()> {
+        self.post("playlists/start/", &PlaylistRequest { playlist_id })
+            .await
+    }
+
+    pub async fn stop_playlist(&self) -> Result<()> {
+        self.post("playlists/stop/", &()).await
+    }
+
+    pub async fn skip_playlist_entry(&self) -> Result<()> {
+        self.post("playlists/skip/", &()).await
+    }
 }
 
 impl Default for RustmasApiClient {
@@ -4,6 +4,7 @@ pub use animation_api::event::Event;
 pub use animation_api::schema::{
     Configuration, ConfigurationSchema, ParameterSchema, ParameterValue, ValueSchema,
 };
+pub use lightfx::{Axis, BlendMode, PostProcessing, Transition, TransitionKind};
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize)]
@@ -32,6 +33,10 @@ pub struct Parameter {
 pub struct SwitchAnimationRequest {
     pub animation_id: String,
     pub params: Option<HashMap<String, ParameterValue>>,
+    #[serde(default)]
+    pub transition: Option<Transition>,
+    #[serde(default)]
+    pub zone: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -69,19 +74,283 @@ pub struct SetAnimationParametersRequest {
     pub values: HashMap<String, ParameterValue>,
 }
 
+/// Selects the zone and layer addressed by a parameters request. Requests
+/// without a zone refer to the main zone, and requests without a layer refer
+/// to the base animation of the zone.
+#[derive(Serialize, Deserialize, Default)]
+pub struct ParametersQuery {
+    #[serde(default)]
+    pub zone: Option<String>,
+    #[serde(default)]
+    pub layer: Option<String>,
+}
+
+/// Selects the zone addressed by a request. Requests without a zone refer
+/// to the main zone, covering all lights.
+#[derive(Serialize, Deserialize, Default)]
+pub struct ZoneQuery {
+    #[serde(default)]
+    pub zone: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Zone {
+    pub name: String,
+    pub point_count: usize,
+    pub animation_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ListZonesResponse {
+    pub zones: Vec<Zone>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Layer {
+    pub layer: String,
+    pub animation_id: String,
+    pub opacity: f64,
+    pub blend_mode: BlendMode,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ListLayersResponse {
+    pub layers: Vec<Layer>,
+}
+
+fn default_opacity() -> f64 {
+    1.0
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AddLayerRequest {
+    #[serde(default)]
+    pub zone: Option<String>,
+    pub layer: String,
+    pub animation_id: String,
+    #[serde(default = "default_opacity")]
+    pub opacity: f64,
+    #[serde(default)]
+    pub blend_mode: BlendMode,
+    #[serde(default)]
+    pub params: Option<HashMap<String, ParameterValue>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SetLayerBlendingRequest {
+    #[serde(default)]
+    pub zone: Option<String>,
+    pub layer: String,
+    pub opacity: f64,
+    pub blend_mode: BlendMode,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RemoveLayerRequest {
+    #[serde(default)]
+    pub zone: Option<String>,
+    pub layer: String,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Animation {
     pub id: String,
     pub name: String,
 }
 
+/// Animation replaced by a fallback after repeatedly failing to render.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AnimationFailure {
+    pub zone: Option<String>,
+    /// Key of the layer, which is removed instead of being replaced
+    #[serde(default)]
+    pub layer: Option<String>,
+    pub animation_id: String,
+    pub error: String,
+    pub fallback_animation_id: Option<String>,
+    pub time: String,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ListAnimationsResponse {
     pub animations: Vec<Animation>,
     pub current_animation_id: Option<String>,
+    #[serde(default)]
+    pub failures: Vec<AnimationFailure>,
+    #[serde(default)]
+    pub paused: bool,
+}
+
+/// Advances paused animations by the given time.
+#[derive(Serialize, Deserialize)]
+pub struct StepRequest {
+    pub delta_seconds: f64,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct GetPointsResponse {
     pub points: Vec<(f32, f32, f32)>,
 }
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaylistMode {
+    Once,
+    #[default]
+    Loop,
+    Shuffle,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PlaylistEntry {
+    pub animation_id: String,
+    #[serde(default)]
+    pub params: Option<HashMap<String, ParameterValue>>,
+    pub duration_seconds: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Playlist {
+    pub id: i64,
+    pub name: String,
+    pub mode: PlaylistMode,
+    pub entries: Vec<PlaylistEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CreatePlaylistRequest {
+    pub name: String,
+    #[serde(default)]
+    pub mode: PlaylistMode,
+    pub entries: Vec<PlaylistEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PlaylistRequest {
+    pub playlist_id: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ListPlaylistsResponse {
+    pub playlists: Vec<Playlist>,
+    pub current_playlist_id: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+/// Time of day at which a schedule rule fires.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduleTime {
+    /// Fixed local time.
+    At { hour: u32, minute: u32 },
+    /// Sunrise at the configured location, shifted by up to 12 hours worth of minutes.
+    Sunrise {
+        #[serde(default)]
+        offset_minutes: i64,
+    },
+    /// Sunset at the configured location, shifted by up to 12 hours worth of minutes.
+    Sunset {
+        #[serde(default)]
+        offset_minutes: i64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduleAction {
+    Switch {
+        animation_id: String,
+        #[serde(default)]
+        params: Option<HashMap<String, ParameterValue>>,
+        #[serde(default)]
+        zone: Option<String>,
+    },
+    TurnOff {
+        #[serde(default)]
+        zone: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ScheduleRule {
+    pub id: i64,
+    pub name: String,
+    pub time: ScheduleTime,
+    /// Days on which the rule is active, empty means every day.
+    pub days: Vec<Weekday>,
+    pub action: ScheduleAction,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateScheduleRuleRequest {
+    pub name: String,
+    pub time: ScheduleTime,
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    pub action: ScheduleAction,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ScheduleRuleRequest {
+    pub rule_id: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ScheduledEvent {
+    pub rule_id: i64,
+    /// Local time of the event, in RFC 3339 format.
+    pub time: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ListScheduleResponse {
+    pub rules: Vec<ScheduleRule>,
+    pub next_event: Option<ScheduledEvent>,
+}
+
+/// Current drawn by power limited lights, as estimated from the last frame.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PowerUsage {
+    pub lights: String,
+    pub max_amps: f64,
+    /// Current the frame would draw without limiting, missing if nothing was displayed yet
+    pub estimated_amps: Option<f64>,
+    /// Current the frame draws after limiting, missing if nothing was displayed yet
+    pub output_amps: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetPowerUsageResponse {
+    pub power: Vec<PowerUsage>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RecordingRequest {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReplayRequest {
+    pub name: String,
+    /// Whether to start over when the recording ends, instead of resuming animations
+    #[serde(default)]
+    pub looped: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ListRecordingsResponse {
+    pub recordings: Vec<String>,
+    pub recording: bool,
+    pub replaying: bool,
+}
@@ -1,9 +1,15 @@
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
-use log::warn;
-use rustmas_animator::{AnimationFactory, AnimationFactoryError};
-use webapi_model::{Animation, Configuration, ListAnimationsResponse, ParameterValue};
+use animation_api::plugin_config::PluginConfig;
+use animation_wrapper::{PluginConfigError, unwrap};
+use log::{info, warn};
+use rustmas_animator::{AnimationFactory, AnimationFactoryError, ControllerError};
+use webapi_model::{
+    AddLayerRequest, Animation, AnimationFailure, Configuration, Layer, ListAnimationsResponse,
+    ListLayersResponse, ListZonesResponse, ParameterValue, RemoveLayerRequest,
+    SetLayerBlendingRequest, Transition, Zone,
+};
 
 use crate::animations;
 use crate::config::RustmasConfig;
@@ -23,6 +29,30 @@ pub enum LogicError {
 
     #[error("no animation selected")]
     NoAnimationSelected,
+
+    #[error("no such layer: {0}")]
+    NoSuchLayer(String),
+
+    #[error("no such zone: {0}")]
+    NoSuchZone(String),
+}
+
+impl From<ControllerError> for LogicError {
+    fn from(value: ControllerError) -> Self {
+        match value {
+            ControllerError::NoAnimationSelected => Self::NoAnimationSelected,
+            ControllerError::NoSuchLayer(layer) => Self::NoSuchLayer(layer),
+            ControllerError::NoSuchZone(zone) => Self::NoSuchZone(zone),
+            e => Self::InternalError(e.to_string()),
+        }
+    }
+}
+
+fn factory_error(e: AnimationFactoryError) -> LogicError {
+    match e {
+        AnimationFactoryError::ZoneNotFound(zone) => LogicError::NoSuchZone(zone),
+        e => LogicError::InvalidAnimation(e),
+    }
 }
 
 pub struct Logic {
@@ -55,50 +85,284 @@ impl Logic {
             .await
             .ok_or(LogicError::NoAnimationSelected)?;
 
-        self.switch(&animation_id, None, controller, parameters)
+        self.switch(None, &animation_id, None, None, controller, parameters)
             .await
     }
 
+    /// Registers a changed plugin file, and restarts the plugin in every zone
+    /// it is running in, keeping its current parameter values.
+    pub async fn reload_plugin(
+        &self,
+        path: &Path,
+        controller: &mut rustmas_animator::Controller,
+        parameters: &parameters::Logic,
+    ) -> Result<(), LogicError> {
+        self.animation_factory.verify(path)?;
+        let manifest = unwrap::unwrap_plugin(path)
+            .map_err(|e| LogicError::InvalidAnimation(PluginConfigError::InvalidCrab(e).into()))?;
+        let animation_id = manifest.id.clone();
+        self.storage
+            .upsert(&PluginConfig {
+                manifest,
+                path: path.to_owned(),
+            })
+            .await
+            .map_err(|e| LogicError::InternalError(e.to_string()))?;
+
+        let zones = std::iter::once(None).chain(
+            controller
+                .zones()
+                .await
+                .into_iter()
+                .map(|zone| Some(zone.name)),
+        );
+        for zone in zones {
+            let zone = zone.as_deref();
+            if controller.animation_id(zone, None).await.as_ref() != Some(&animation_id) {
+                continue;
+            }
+
+            info!(
+                "Reloading animation {animation_id} in zone {}",
+                zone.unwrap_or("main")
+            );
+            let values = controller.get_parameter_values(zone, None).await?;
+            self.switch(
+                zone,
+                &animation_id,
+                Some(values),
+                None,
+                controller,
+                parameters,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
     pub async fn switch(
         &self,
+        zone: Option<&str>,
         animation_id: &str,
         initial_parameters: Option<HashMap<String, ParameterValue>>,
+        transition: Option<Transition>,
         controller: &mut rustmas_animator::Controller,
         parameters: &parameters::Logic,
     ) -> Result<Configuration, LogicError> {
-        let db_plugin = self
-            .storage
-            .fetch_by_id(animation_id)
+        let path = self.plugin_path(animation_id).await?;
+        let plugin = self
+            .animation_factory
+            .make_from_path(&path, zone)
             .await
-            .map_err(|e| LogicError::InternalError(e.to_string()))?
-            .ok_or_else(|| LogicError::NoSuchAnimation(animation_id.to_owned()))?;
+            .map_err(factory_error)?;
+
+        // The outgoing animation may be the same one, e.g. when reloading
+        if let Err(e) = self.save_state(zone, controller).await {
+            warn!("Failed to save state of the previous animation: {e}");
+        }
+        match self.storage.fetch_state(animation_id).await {
+            Ok(Some(state)) => {
+                if let Err(e) = plugin.load_state(&state).await {
+                    warn!("Failed to restore state of animation {animation_id}: {e}");
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to fetch state of animation {animation_id}: {e}"),
+        }
+
+        let configuration = controller
+            .switch_animation(zone, plugin, transition)
+            .await?;
 
+        self.apply_parameters(
+            zone,
+            None,
+            initial_parameters,
+            configuration,
+            controller,
+            parameters,
+        )
+        .await
+    }
+
+    /// Stores the state of the animation running in the zone, so that it can
+    /// be restored the next time the animation starts.
+    async fn save_state(
+        &self,
+        zone: Option<&str>,
+        controller: &rustmas_animator::Controller,
+    ) -> Result<(), LogicError> {
+        let Some(animation_id) = controller.animation_id(zone, None).await else {
+            return Ok(());
+        };
+        let Some(state) = controller.animation_state(zone, None).await? else {
+            return Ok(());
+        };
+
+        self.storage
+            .save_state(&animation_id, &state)
+            .await
+            .map_err(|e| LogicError::InternalError(e.to_string()))
+    }
+
+    /// Stores the state of animations running in all zones.
+    pub async fn save_states(&self, controller: &rustmas_animator::Controller) {
+        let zones = std::iter::once(None).chain(
+            controller
+                .zones()
+                .await
+                .into_iter()
+                .map(|zone| Some(zone.name)),
+        );
+        for zone in zones {
+            if let Err(e) = self.save_state(zone.as_deref(), controller).await {
+                warn!(
+                    "Failed to save state of animation in zone {}: {e}",
+                    zone.as_deref().unwrap_or("main")
+                );
+            }
+        }
+    }
+
+    pub async fn list_zones(&self, controller: &rustmas_animator::Controller) -> ListZonesResponse {
+        ListZonesResponse {
+            zones: controller
+                .zones()
+                .await
+                .into_iter()
+                .map(|zone| Zone {
+                    name: zone.name,
+                    point_count: zone.point_count,
+                    animation_id: zone.animation_id,
+                })
+                .collect(),
+        }
+    }
+
+    pub async fn add_layer(
+        &self,
+        request: AddLayerRequest,
+        controller: &mut rustmas_animator::Controller,
+        parameters: &parameters::Logic,
+    ) -> Result<Configuration, LogicError> {
+        let zone = request.zone.as_deref();
+        let path = self.plugin_path(&request.animation_id).await?;
         let plugin = self
             .animation_factory
-            .make_from_path(&db_plugin.path)
-            .await?;
+            .make_from_path(&path, zone)
+            .await
+            .map_err(factory_error)?;
 
         let configuration = controller
-            .switch_animation(plugin)
+            .add_layer(
+                zone,
+                &request.layer,
+                plugin,
+                request.opacity,
+                request.blend_mode,
+            )
+            .await?;
+
+        self.apply_parameters(
+            zone,
+            Some(&request.layer),
+            request.params,
+            configuration,
+            controller,
+            parameters,
+        )
+        .await
+    }
+
+    pub async fn remove_layer(
+        &self,
+        request: &RemoveLayerRequest,
+        controller: &rustmas_animator::Controller,
+    ) -> Result<ListLayersResponse, LogicError> {
+        let zone = request.zone.as_deref();
+        controller.remove_layer(zone, &request.layer).await?;
+        self.list_layers(zone, controller).await
+    }
+
+    pub async fn set_layer_blending(
+        &self,
+        request: &SetLayerBlendingRequest,
+        controller: &rustmas_animator::Controller,
+    ) -> Result<ListLayersResponse, LogicError> {
+        let zone = request.zone.as_deref();
+        controller
+            .set_layer_blending(zone, &request.layer, request.opacity, request.blend_mode)
+            .await?;
+        self.list_layers(zone, controller).await
+    }
+
+    pub async fn list_layers(
+        &self,
+        zone: Option<&str>,
+        controller: &rustmas_animator::Controller,
+    ) -> Result<ListLayersResponse, LogicError> {
+        Ok(ListLayersResponse {
+            layers: controller
+                .layers(zone)
+                .await?
+                .into_iter()
+                .map(|layer| Layer {
+                    layer: layer.key,
+                    animation_id: layer.animation_id,
+                    opacity: layer.opacity,
+                    blend_mode: layer.blend_mode,
+                })
+                .collect(),
+        })
+    }
+
+    pub fn plugin_dir(&self) -> &Path {
+        self.animation_factory.plugin_dir()
+    }
+
+    async fn plugin_path(&self, animation_id: &str) -> Result<PathBuf, LogicError> {
+        Ok(self
+            .storage
+            .fetch_by_id(animation_id)
             .await
-            .map_err(|e| LogicError::InternalError(e.to_string()))?;
+            .map_err(|e| LogicError::InternalError(e.to_string()))?
+            .ok_or_else(|| LogicError::NoSuchAnimation(animation_id.to_owned()))?
+            .path)
+    }
 
+    async fn apply_parameters(
+        &self,
+        zone: Option<&str>,
+        layer: Option<&str>,
+        initial_parameters: Option<HashMap<String, ParameterValue>>,
+        configuration: Configuration,
+        controller: &mut rustmas_animator::Controller,
+        parameters: &parameters::Logic,
+    ) -> Result<Configuration, LogicError> {
         if let Some(values) = initial_parameters {
-            let _ = controller.set_parameters(&values).await;
+            let _ = controller.set_parameters(zone, layer, &values).await;
             Ok(Configuration {
                 values,
                 ..configuration
             })
         } else {
             parameters
-                .restore_from_db(controller, configuration)
+                .restore_from_db(controller, zone, layer, configuration)
                 .await
                 .map_err(|e| LogicError::InternalError(e.to_string()))
         }
     }
 
-    pub async fn turn_off(&self, controller: &mut rustmas_animator::Controller) {
-        controller.turn_off().await;
+    pub async fn turn_off(
+        &self,
+        zone: Option<&str>,
+        controller: &mut rustmas_animator::Controller,
+    ) -> Result<(), LogicError> {
+        if let Err(e) = self.save_state(zone, controller).await {
+            warn!("Failed to save state of the animation: {e}");
+        }
+        Ok(controller.turn_off(zone).await?)
     }
 
     pub async fn discover(
@@ -153,6 +417,16 @@ impl Logic {
         self.list(controller).await
     }
 
+    pub async fn animation_ids(&self) -> anyhow::Result<HashSet<String>> {
+        Ok(self
+            .storage
+            .fetch_all()
+            .await?
+            .into_iter()
+            .map(|db_plugin| db_plugin.animation_id)
+            .collect())
+    }
+
     pub async fn list(
         &self,
         controller: &rustmas_animator::Controller,
@@ -170,6 +444,160 @@ impl Logic {
                 })
                 .collect(),
             current_animation_id: controller.current_animation_id().await,
+            failures: controller
+                .failures()
+                .await
+                .into_iter()
+                .map(|failure| AnimationFailure {
+                    zone: failure.zone,
+                    layer: failure.layer,
+                    animation_id: failure.animation_id,
+                    error: failure.error,
+                    fallback_animation_id: failure.fallback_animation_id,
+                    time: failure.time.to_rfc3339(),
+                })
+                .collect(),
+            paused: controller.is_paused().await,
         })
     }
+
+    pub async fn pause(&self, controller: &rustmas_animator::Controller) {
+        controller.pause().await;
+    }
+
+    pub async fn resume(&self, controller: &rustmas_animator::Controller) {
+        controller.resume().await;
+    }
+
+    pub async fn step(&self, delta_seconds: f64, controller: &rustmas_animator::Controller) {
+        controller.step(delta_seconds).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use animation_wasm_bindings::testing::TestPlugin;
+    use rustmas_animator::{Controller, ControllerConfig, ManualClock};
+
+    use super::*;
+
+    struct TestSetup {
+        logic: Logic,
+        parameters: parameters::Logic,
+        controller: Controller,
+        _plugin_dir: tempfile::TempDir,
+    }
+
+    /// Sets up logic with a single installed plugin, whose state is the time
+    /// it has been running for.
+    async fn setup() -> TestSetup {
+        let plugin_dir = tempfile::tempdir().unwrap();
+        let points_path = plugin_dir.path().join("lights.csv");
+        std::fs::write(&points_path, "0,0,0\n1,0,0\n").unwrap();
+        let config = ControllerConfig {
+            points_path,
+            lights: Vec::new(),
+            power_limit: None,
+            plugin_path: plugin_dir.path().to_owned(),
+            default_transition: None,
+            zones: Vec::new(),
+            output_fps: None,
+            plugin_limits: Default::default(),
+            watchdog: Default::default(),
+            sync: None,
+            plugin_signing: Default::default(),
+        };
+
+        let conn = SharedDbConnection::in_memory().await.unwrap();
+        let storage = animations::Storage::new(conn.clone());
+        let path = TestPlugin::new("clock").write_crab(plugin_dir.path());
+        storage
+            .install(&PluginConfig {
+                manifest: unwrap::unwrap_plugin(&path).unwrap(),
+                path,
+            })
+            .await
+            .unwrap();
+
+        // Time stands still, so rendered frames do not change the plugin state
+        let clock = Arc::new(ManualClock::new(chrono::Utc::now()));
+        TestSetup {
+            logic: Logic::new(storage, AnimationFactory::from_config(&config).unwrap()),
+            parameters: parameters::Logic::from(conn),
+            controller: Controller::from_config_with_clock(&config, None, clock).unwrap(),
+            _plugin_dir: plugin_dir,
+        }
+    }
+
+    fn time_state(seconds: f64) -> Vec<u8> {
+        seconds.to_le_bytes().to_vec()
+    }
+
+    #[tokio::test]
+    async fn state_is_restored_on_switch() {
+        let mut setup = setup().await;
+        setup
+            .logic
+            .storage
+            .save_state("clock", &time_state(2.5))
+            .await
+            .unwrap();
+
+        setup
+            .logic
+            .switch(
+                None,
+                "clock",
+                None,
+                None,
+                &mut setup.controller,
+                &setup.parameters,
+            )
+            .await
+            .unwrap();
+
+        let state = setup.controller.animation_state(None, None).await.unwrap();
+        assert_eq!(state, Some(time_state(2.5)));
+    }
+
+    #[tokio::test]
+    async fn state_is_saved_on_turn_off() {
+        let mut setup = setup().await;
+        setup
+            .logic
+            .storage
+            .save_state("clock", &time_state(2.5))
+            .await
+            .unwrap();
+        setup
+            .logic
+            .switch(
+                None,
+                "clock",
+                None,
+                None,
+                &mut setup.controller,
+                &setup.parameters,
+            )
+            .await
+            .unwrap();
+
+        // Only turning off can store the restored state again
+        setup
+            .logic
+            .storage
+            .save_state("clock", &time_state(0.0))
+            .await
+            .unwrap();
+        setup
+            .logic
+            .turn_off(None, &mut setup.controller)
+            .await
+            .unwrap();
+
+        let state = setup.logic.storage.fetch_state("clock").await.unwrap();
+        assert_eq!(state, Some(time_state(2.5)));
+    }
 }
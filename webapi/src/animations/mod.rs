@@ -1,7 +1,11 @@
 mod logic;
 mod service;
+mod state_saver;
 mod storage;
+mod watcher;
 
 pub use logic::{Logic, LogicError};
 pub use service::service;
+pub use state_saver::save_states_periodically;
 use storage::Storage;
+pub use watcher::watch_plugins;
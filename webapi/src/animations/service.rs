@@ -2,7 +2,10 @@ use actix_multipart::form::{MultipartForm, tempfile::TempFile};
 use actix_web::{HttpResponse, Scope, get, post, web};
 use log::error;
 use serde_json::json;
-use webapi_model::{RemoveAnimationRequest, SwitchAnimationRequest, SwitchAnimationResponse};
+use webapi_model::{
+    AddLayerRequest, RemoveAnimationRequest, RemoveLayerRequest, SetLayerBlendingRequest,
+    StepRequest, SwitchAnimationRequest, SwitchAnimationResponse, ZoneQuery,
+};
 
 use crate::{AnimationController, animations, parameters};
 
@@ -26,6 +29,12 @@ async fn reload(
         Err(e @ animations::LogicError::NoAnimationSelected) => {
             HttpResponse::BadRequest().json(json!({ "error": e.to_string() }))
         }
+        Err(
+            e @ (animations::LogicError::NoSuchLayer(_) | animations::LogicError::NoSuchZone(_)),
+        ) => {
+            error!("Unexpected logic error in /reload/ call: {e}");
+            HttpResponse::InternalServerError().json(json!({"error": "unexpected failure"}))
+        }
     }
 }
 
@@ -40,8 +49,10 @@ async fn switch(
     let initial_parameters = form.params.take();
     match animations
         .switch(
+            form.zone.as_deref(),
             &form.animation_id,
             initial_parameters,
+            form.transition,
             &mut controller,
             &parameters,
         )
@@ -56,7 +67,13 @@ async fn switch(
         }
         Err(animations::LogicError::NoSuchAnimation(animation_id)) => HttpResponse::NotFound()
             .json(json!({ "error": format!("no such animation: {animation_id}") })),
-        Err(e @ animations::LogicError::NoAnimationSelected) => {
+        Err(e @ animations::LogicError::NoSuchZone(_)) => {
+            HttpResponse::NotFound().json(json!({ "error": e.to_string() }))
+        }
+        Err(
+            e @ (animations::LogicError::NoAnimationSelected
+            | animations::LogicError::NoSuchLayer(_)),
+        ) => {
             error!("Unexpected logic error in /switch/ call: {e}");
             HttpResponse::InternalServerError().json(json!({"error": "unexpected failure"}))
         }
@@ -65,11 +82,55 @@ async fn switch(
 
 #[post("/turn_off/")]
 async fn turn_off(
+    query: web::Query<ZoneQuery>,
     animations: web::Data<animations::Logic>,
     controller: web::Data<AnimationController>,
 ) -> HttpResponse {
     let mut controller = controller.lock().await;
-    animations.turn_off(&mut controller).await;
+    match animations
+        .turn_off(query.zone.as_deref(), &mut controller)
+        .await
+    {
+        Ok(_) => HttpResponse::Ok().json(()),
+        Err(e @ animations::LogicError::NoSuchZone(_)) => {
+            HttpResponse::NotFound().json(json!({ "error": e.to_string() }))
+        }
+        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e.to_string() })),
+    }
+}
+
+#[post("/pause/")]
+async fn pause(
+    animations: web::Data<animations::Logic>,
+    controller: web::Data<AnimationController>,
+) -> HttpResponse {
+    let controller = controller.lock().await;
+    animations.pause(&controller).await;
+    HttpResponse::Ok().json(())
+}
+
+#[post("/resume/")]
+async fn resume(
+    animations: web::Data<animations::Logic>,
+    controller: web::Data<AnimationController>,
+) -> HttpResponse {
+    let controller = controller.lock().await;
+    animations.resume(&controller).await;
+    HttpResponse::Ok().json(())
+}
+
+#[post("/step/")]
+async fn step(
+    form: web::Json<StepRequest>,
+    animations: web::Data<animations::Logic>,
+    controller: web::Data<AnimationController>,
+) -> HttpResponse {
+    if !form.delta_seconds.is_finite() || form.delta_seconds < 0.0 {
+        return HttpResponse::BadRequest()
+            .json(json!({ "error": "step must be a non-negative number of seconds" }));
+    }
+    let controller = controller.lock().await;
+    animations.step(form.delta_seconds, &controller).await;
     HttpResponse::Ok().json(())
 }
 
@@ -159,13 +220,108 @@ async fn remove(
     }
 }
 
+fn layer_error_response(e: animations::LogicError) -> HttpResponse {
+    match e {
+        animations::LogicError::InternalError(e) => {
+            HttpResponse::InternalServerError().json(json!({ "error": e }))
+        }
+        animations::LogicError::InvalidAnimation(e) => {
+            HttpResponse::NotAcceptable().json(json!({ "error": e.to_string() }))
+        }
+        e @ (animations::LogicError::NoSuchAnimation(_)
+        | animations::LogicError::NoSuchLayer(_)
+        | animations::LogicError::NoSuchZone(_)) => {
+            HttpResponse::NotFound().json(json!({ "error": e.to_string() }))
+        }
+        e @ animations::LogicError::NoAnimationSelected => {
+            HttpResponse::PreconditionFailed().json(json!({ "error": e.to_string() }))
+        }
+    }
+}
+
+#[get("/zones/")]
+async fn list_zones(
+    animations: web::Data<animations::Logic>,
+    controller: web::Data<AnimationController>,
+) -> HttpResponse {
+    let controller = controller.lock().await;
+    HttpResponse::Ok().json(animations.list_zones(&controller).await)
+}
+
+#[get("/layers/")]
+async fn list_layers(
+    query: web::Query<ZoneQuery>,
+    animations: web::Data<animations::Logic>,
+    controller: web::Data<AnimationController>,
+) -> HttpResponse {
+    let controller = controller.lock().await;
+    match animations
+        .list_layers(query.zone.as_deref(), &controller)
+        .await
+    {
+        Ok(layers) => HttpResponse::Ok().json(layers),
+        Err(e) => layer_error_response(e),
+    }
+}
+
+#[post("/layers/add/")]
+async fn add_layer(
+    form: web::Json<AddLayerRequest>,
+    animations: web::Data<animations::Logic>,
+    controller: web::Data<AnimationController>,
+    parameters: web::Data<parameters::Logic>,
+) -> HttpResponse {
+    let mut controller = controller.lock().await;
+    match animations
+        .add_layer(form.into_inner(), &mut controller, &parameters)
+        .await
+    {
+        Ok(animation) => HttpResponse::Ok().json(SwitchAnimationResponse { animation }),
+        Err(e) => layer_error_response(e),
+    }
+}
+
+#[post("/layers/blending/")]
+async fn set_layer_blending(
+    form: web::Json<SetLayerBlendingRequest>,
+    animations: web::Data<animations::Logic>,
+    controller: web::Data<AnimationController>,
+) -> HttpResponse {
+    let controller = controller.lock().await;
+    match animations.set_layer_blending(&form, &controller).await {
+        Ok(layers) => HttpResponse::Ok().json(layers),
+        Err(e) => layer_error_response(e),
+    }
+}
+
+#[post("/layers/remove/")]
+async fn remove_layer(
+    form: web::Json<RemoveLayerRequest>,
+    animations: web::Data<animations::Logic>,
+    controller: web::Data<AnimationController>,
+) -> HttpResponse {
+    let controller = controller.lock().await;
+    match animations.remove_layer(&form, &controller).await {
+        Ok(layers) => HttpResponse::Ok().json(layers),
+        Err(e) => layer_error_response(e),
+    }
+}
+
 pub fn service() -> Scope {
     web::scope("/animations")
         .service(reload)
         .service(switch)
         .service(turn_off)
+        .service(pause)
+        .service(resume)
+        .service(step)
         .service(discover)
         .service(list)
         .service(install)
         .service(remove)
+        .service(list_zones)
+        .service(list_layers)
+        .service(add_layer)
+        .service(set_layer_blending)
+        .service(remove_layer)
 }
@@ -0,0 +1,23 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{AnimationController, animations};
+
+/// Interval at which states of running animations are stored, so that little
+/// is lost when the server stops unexpectedly.
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Periodically stores the state of running animations.
+pub fn save_states_periodically(
+    animations: Arc<animations::Logic>,
+    controller: Arc<AnimationController>,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SAVE_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            animations.save_states(&*controller.lock().await).await;
+        }
+    });
+}
@@ -37,6 +37,23 @@ impl Storage {
         Ok(())
     }
 
+    /// Registers the plugin, replacing the path and manifest of an already
+    /// registered plugin with the same ID.
+    pub async fn upsert(&self, config: &PluginConfig) -> anyhow::Result<()> {
+        let path = config.path.to_string_lossy();
+        let manifest = serde_json::to_string(&config.manifest)?;
+        sqlx::query!(
+            "INSERT INTO animation_plugins(id, path, manifest) VALUES ($1, $2, $3) ON CONFLICT(id) DO UPDATE SET path = excluded.path, manifest = excluded.manifest",
+            config.manifest.id,
+            path,
+            manifest
+        )
+        .execute(&mut *self.conn.lock().await)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn fetch_by_id(&self, animation_id: &str) -> anyhow::Result<Option<DbAnimation>> {
         sqlx::query!(
             "SELECT id, path, manifest FROM animation_plugins WHERE id = $1",
@@ -81,7 +98,37 @@ impl Storage {
         Ok(animations)
     }
 
+    pub async fn save_state(&self, animation_id: &str, state: &[u8]) -> anyhow::Result<()> {
+        sqlx::query!(
+            "INSERT INTO animation_states(animation, state) VALUES ($1, $2) ON CONFLICT(animation) DO UPDATE SET state = excluded.state",
+            animation_id,
+            state
+        )
+        .execute(&mut *self.conn.lock().await)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn fetch_state(&self, animation_id: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(sqlx::query!(
+            "SELECT state FROM animation_states WHERE animation = $1",
+            animation_id
+        )
+        .fetch_optional(&mut *self.conn.lock().await)
+        .await?
+        .map(|r| r.state))
+    }
+
+    /// Removes the plugin, along with its saved state, returning the path of the plugin.
     pub async fn delete(&self, animation_id: &str) -> anyhow::Result<String> {
+        sqlx::query!(
+            "DELETE FROM animation_states WHERE animation = $1",
+            animation_id
+        )
+        .execute(&mut *self.conn.lock().await)
+        .await?;
+
         let path = sqlx::query!(
             "DELETE FROM animation_plugins WHERE id = $1 RETURNING path",
             animation_id
@@ -93,3 +140,43 @@ impl Storage {
         Ok(path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use animation_api::plugin_config::PluginApiVersion;
+
+    use super::*;
+
+    fn plugin(id: &str) -> PluginConfig {
+        PluginConfig {
+            manifest: PluginManifest {
+                id: id.to_owned(),
+                display_name: id.to_owned(),
+                author: "Test".to_owned(),
+                api_version: PluginApiVersion::CURRENT,
+                version: "1.0".to_owned(),
+                tags: Vec::new(),
+            },
+            path: PathBuf::from(format!("{id}.crab")),
+        }
+    }
+
+    #[tokio::test]
+    async fn states_are_replaced_and_removed_with_the_plugin() {
+        let storage = Storage::new(SharedDbConnection::in_memory().await.unwrap());
+        storage.install(&plugin("draw")).await.unwrap();
+        assert_eq!(storage.fetch_state("draw").await.unwrap(), None);
+
+        storage.save_state("draw", b"first").await.unwrap();
+        storage.save_state("draw", b"second").await.unwrap();
+        storage.save_state("other", b"other").await.unwrap();
+        assert_eq!(
+            storage.fetch_state("draw").await.unwrap().as_deref(),
+            Some(b"second".as_slice())
+        );
+
+        storage.delete("draw").await.unwrap();
+        assert_eq!(storage.fetch_state("draw").await.unwrap(), None);
+        assert!(storage.fetch_state("other").await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{info, warn};
+use notify::{EventKind, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+
+use crate::{AnimationController, animations, parameters};
+
+/// Time without further changes after which a plugin file is considered
+/// completely written.
+const SETTLE_TIME: Duration = Duration::from_millis(500);
+
+/// Watches the plugin directory, registering `.crab` files as they change and
+/// reloading the ones currently running.
+pub fn watch_plugins(
+    animations: Arc<animations::Logic>,
+    controller: Arc<AnimationController>,
+    parameters: Arc<parameters::Logic>,
+) -> notify::Result<()> {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let mut watcher =
+        notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+            Ok(event) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) => {
+                for path in event.paths {
+                    let _ = sender.send(path);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Plugin watcher error: {e}"),
+        })?;
+    let plugin_dir = animations.plugin_dir().to_owned();
+    watcher.watch(&plugin_dir, RecursiveMode::NonRecursive)?;
+    info!("Watching {} for plugin changes", plugin_dir.display());
+
+    tokio::spawn(async move {
+        // The watcher stops when dropped, so it has to live as long as the task
+        let _watcher = watcher;
+
+        while let Some(path) = receiver.recv().await {
+            let mut changed = HashSet::from([path]);
+            while let Ok(Some(path)) = tokio::time::timeout(SETTLE_TIME, receiver.recv()).await {
+                changed.insert(path);
+            }
+
+            for path in changed.into_iter().filter(|path| is_plugin_file(path)) {
+                info!("Plugin {} changed", path.display());
+                let mut controller = controller.lock().await;
+                if let Err(e) = animations
+                    .reload_plugin(&path, &mut controller, &parameters)
+                    .await
+                {
+                    warn!("Failed to reload plugin {}: {e}", path.display());
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn is_plugin_file(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "crab") && path.is_file()
+}
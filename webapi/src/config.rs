@@ -7,4 +7,21 @@ use serde::Deserialize;
 pub struct RustmasConfig {
     pub database_path: PathBuf,
     pub controller: ControllerConfig,
+    #[serde(default)]
+    pub location: Option<Location>,
+    /// Directory for recordings of displayed frames
+    #[serde(default = "default_recordings_path")]
+    pub recordings_path: PathBuf,
+}
+
+fn default_recordings_path() -> PathBuf {
+    PathBuf::from("recordings")
+}
+
+/// Geographic location of the installation, used to compute sunrise and sunset times.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Location {
+    pub latitude: f64,
+    /// Degrees east of Greenwich, negative for the western hemisphere
+    pub longitude: f64,
 }
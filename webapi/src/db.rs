@@ -12,10 +12,19 @@ pub struct SharedDbConnection {
 
 impl SharedDbConnection {
     pub async fn from_config(config: &RustmasConfig) -> anyhow::Result<Self> {
-        let mut conn = SqliteConnectOptions::from_str(&config.database_path.to_string_lossy())?
-            .disable_statement_logging()
-            .connect()
-            .await?;
+        Self::connect(SqliteConnectOptions::from_str(
+            &config.database_path.to_string_lossy(),
+        )?)
+        .await
+    }
+
+    #[cfg(test)]
+    pub async fn in_memory() -> anyhow::Result<Self> {
+        Self::connect(SqliteConnectOptions::from_str("sqlite::memory:")?).await
+    }
+
+    async fn connect(options: SqliteConnectOptions) -> anyhow::Result<Self> {
+        let mut conn = options.disable_statement_logging().connect().await?;
 
         sqlx::migrate!("../migrations").run(&mut conn).await?;
 
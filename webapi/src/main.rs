@@ -2,7 +2,10 @@ mod animations;
 mod config;
 mod db;
 mod events;
+mod output;
 mod parameters;
+mod playlists;
+mod schedule;
 mod visualizer;
 
 use ::config::Config;
@@ -10,7 +13,7 @@ use actix_cors::Cors;
 use config::RustmasConfig;
 use db::SharedDbConnection;
 use itertools::Itertools;
-use log::info;
+use log::{info, warn};
 use rustmas_animator::points_from_path;
 use std::error::Error;
 use tokio::sync::{Mutex, mpsc};
@@ -34,6 +37,9 @@ async fn main() -> Result<(), Box<dyn Error>> {
     info!("Setting up database");
     let shared_db = SharedDbConnection::from_config(&config).await?;
     let parameters = web::Data::new(parameters::Logic::from(shared_db.clone()));
+    let playlists = web::Data::new(playlists::Logic::from(shared_db.clone()));
+    let schedule = web::Data::new(schedule::Logic::from(shared_db.clone(), &config));
+    let output = web::Data::new(output::Logic::from(shared_db.clone(), &config));
     let animations = web::Data::new(animations::Logic::from(shared_db, &config)?);
 
     let (sender, receiver) = mpsc::channel::<lightfx::Frame>(1);
@@ -44,38 +50,73 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let points = web::Data::new(
         points_from_path(&config.controller.points_path)?
             .into_iter()
-            .map(|(x, y, z)| (x as f32, y as f32, z as f32))
+            .map(|p| (p.x as f32, p.y as f32, p.z as f32))
             .collect_vec(),
     );
 
     let controller = {
-        let controller = rustmas_animator::Controller::from_config(
-            &config.controller,
-            Some(sender),
-            points.len(),
-        )
-        .expect("Could not start animations controller");
+        let controller =
+            rustmas_animator::Controller::from_config(&config.controller, Some(sender))
+                .expect("Could not start animations controller");
 
         web::Data::new(Mutex::new(controller))
     };
 
+    if let Err(e) = output.restore(&*controller.lock().await).await {
+        warn!("Failed to restore output settings: {e}");
+    }
+
+    schedule
+        .start(
+            controller.clone().into_inner(),
+            animations.clone().into_inner(),
+            parameters.clone().into_inner(),
+        )
+        .await;
+
+    if let Err(e) = animations::watch_plugins(
+        animations.clone().into_inner(),
+        controller.clone().into_inner(),
+        parameters.clone().into_inner(),
+    ) {
+        warn!("Plugin hot reload is disabled, cannot watch plugin directory: {e}");
+    }
+
+    animations::save_states_periodically(
+        animations.clone().into_inner(),
+        controller.clone().into_inner(),
+    );
+
     let visualizer_service = visualizer::service_factory(receiver);
+    let shutdown_animations = animations.clone();
+    let shutdown_controller = controller.clone();
 
     HttpServer::new(move || {
         App::new()
             .wrap(Cors::permissive())
             .service(events::service())
+            .service(output::service())
             .service(animations::service())
             .service(parameters::service())
+            .service(playlists::service())
+            .service(schedule::service())
             .service(visualizer_service())
             .app_data(controller.clone())
             .app_data(parameters.clone())
             .app_data(animations.clone())
+            .app_data(playlists.clone())
+            .app_data(schedule.clone())
+            .app_data(output.clone())
             .app_data(points.clone())
     })
     .bind(("0.0.0.0", 8081))?
     .run()
     .await?;
 
+    // Keep states of running animations for the next start
+    shutdown_animations
+        .save_states(&*shutdown_controller.lock().await)
+        .await;
+
     Ok(())
 }
@@ -0,0 +1,177 @@
+use std::path::PathBuf;
+
+use rustmas_animator::ControllerError;
+use webapi_model::{GetPowerUsageResponse, ListRecordingsResponse, PostProcessing, PowerUsage};
+
+use crate::config::RustmasConfig;
+use crate::db::SharedDbConnection;
+use crate::output;
+
+const RECORDING_EXTENSION: &str = "rfrm";
+
+#[derive(Debug, thiserror::Error)]
+pub enum LogicError {
+    #[error("failed to perform operation: {0}")]
+    InternalError(String),
+
+    #[error("invalid recording name: {0}")]
+    InvalidRecordingName(String),
+
+    #[error("no such recording: {0}")]
+    NoSuchRecording(String),
+}
+
+impl From<ControllerError> for LogicError {
+    fn from(value: ControllerError) -> Self {
+        Self::InternalError(value.to_string())
+    }
+}
+
+pub struct Logic {
+    storage: output::Storage,
+    recordings_path: PathBuf,
+}
+
+impl Logic {
+    fn new(storage: output::Storage, recordings_path: PathBuf) -> Self {
+        Self {
+            storage,
+            recordings_path,
+        }
+    }
+
+    pub fn from(conn: SharedDbConnection, config: &RustmasConfig) -> Self {
+        Self::new(output::Storage::new(conn), config.recordings_path.clone())
+    }
+
+    /// Applies post-processing settings saved in the database, if any.
+    pub async fn restore(
+        &self,
+        controller: &rustmas_animator::Controller,
+    ) -> Result<PostProcessing, LogicError> {
+        let post_processing = self
+            .storage
+            .fetch()
+            .await
+            .map_err(|e| LogicError::InternalError(e.to_string()))?
+            .unwrap_or_default();
+        Ok(controller.set_post_processing(post_processing).await)
+    }
+
+    pub async fn post_processing(
+        &self,
+        controller: &rustmas_animator::Controller,
+    ) -> PostProcessing {
+        controller.post_processing().await
+    }
+
+    pub async fn set_post_processing(
+        &self,
+        post_processing: PostProcessing,
+        controller: &rustmas_animator::Controller,
+    ) -> Result<PostProcessing, LogicError> {
+        let post_processing = controller.set_post_processing(post_processing).await;
+        self.storage
+            .save(&post_processing)
+            .await
+            .map_err(|e| LogicError::InternalError(e.to_string()))?;
+        Ok(post_processing)
+    }
+
+    pub fn power_usage(&self, controller: &rustmas_animator::Controller) -> GetPowerUsageResponse {
+        GetPowerUsageResponse {
+            power: controller
+                .power_meters()
+                .iter()
+                .map(|meter| {
+                    let reading = meter.reading();
+                    PowerUsage {
+                        lights: meter.name().to_owned(),
+                        max_amps: meter.max_amps(),
+                        estimated_amps: reading.map(|r| r.estimated_amps),
+                        output_amps: reading.map(|r| r.output_amps),
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    pub async fn list_recordings(
+        &self,
+        controller: &rustmas_animator::Controller,
+    ) -> Result<ListRecordingsResponse, LogicError> {
+        let mut recordings = match self.recordings_path.read_dir() {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|e| e == RECORDING_EXTENSION))
+                .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(LogicError::InternalError(e.to_string())),
+        };
+        recordings.sort();
+
+        Ok(ListRecordingsResponse {
+            recordings,
+            recording: controller.is_recording(),
+            replaying: controller.is_replaying().await,
+        })
+    }
+
+    pub async fn start_recording(
+        &self,
+        name: &str,
+        controller: &rustmas_animator::Controller,
+    ) -> Result<ListRecordingsResponse, LogicError> {
+        let path = self.recording_path(name)?;
+        std::fs::create_dir_all(&self.recordings_path)
+            .map_err(|e| LogicError::InternalError(e.to_string()))?;
+        controller.start_recording(&path)?;
+        self.list_recordings(controller).await
+    }
+
+    pub async fn stop_recording(
+        &self,
+        controller: &rustmas_animator::Controller,
+    ) -> Result<ListRecordingsResponse, LogicError> {
+        controller.stop_recording()?;
+        self.list_recordings(controller).await
+    }
+
+    pub async fn start_replay(
+        &self,
+        name: &str,
+        looped: bool,
+        controller: &rustmas_animator::Controller,
+    ) -> Result<ListRecordingsResponse, LogicError> {
+        let path = self.recording_path(name)?;
+        if !path.exists() {
+            return Err(LogicError::NoSuchRecording(name.to_owned()));
+        }
+        controller.start_replay(&path, looped).await?;
+        self.list_recordings(controller).await
+    }
+
+    pub async fn stop_replay(
+        &self,
+        controller: &rustmas_animator::Controller,
+    ) -> Result<ListRecordingsResponse, LogicError> {
+        controller.stop_replay().await;
+        self.list_recordings(controller).await
+    }
+
+    fn recording_path(&self, name: &str) -> Result<PathBuf, LogicError> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(LogicError::InvalidRecordingName(name.to_owned()));
+        }
+        Ok(self
+            .recordings_path
+            .join(name)
+            .with_extension(RECORDING_EXTENSION))
+    }
+}
@@ -0,0 +1,130 @@
+use actix_web::{HttpResponse, Scope, get, post, web};
+use serde_json::json;
+use webapi_model::{PostProcessing, RecordingRequest, ReplayRequest};
+
+use crate::{AnimationController, output};
+
+fn error_response(e: output::LogicError) -> HttpResponse {
+    match e {
+        output::LogicError::InternalError(e) => {
+            HttpResponse::InternalServerError().json(json!({ "error": e.to_string() }))
+        }
+        e @ output::LogicError::InvalidRecordingName(_) => {
+            HttpResponse::BadRequest().json(json!({ "error": e.to_string() }))
+        }
+        e @ output::LogicError::NoSuchRecording(_) => {
+            HttpResponse::NotFound().json(json!({ "error": e.to_string() }))
+        }
+    }
+}
+
+#[get("/")]
+async fn get(
+    controller: web::Data<AnimationController>,
+    output: web::Data<output::Logic>,
+) -> HttpResponse {
+    let controller = controller.lock().await;
+    HttpResponse::Ok().json(output.post_processing(&controller).await)
+}
+
+#[post("/")]
+async fn set(
+    post_processing: web::Json<PostProcessing>,
+    controller: web::Data<AnimationController>,
+    output: web::Data<output::Logic>,
+) -> HttpResponse {
+    let controller = controller.lock().await;
+    match output
+        .set_post_processing(post_processing.into_inner(), &controller)
+        .await
+    {
+        Ok(post_processing) => HttpResponse::Ok().json(post_processing),
+        Err(e) => error_response(e),
+    }
+}
+
+#[get("/power/")]
+async fn power(
+    controller: web::Data<AnimationController>,
+    output: web::Data<output::Logic>,
+) -> HttpResponse {
+    let controller = controller.lock().await;
+    HttpResponse::Ok().json(output.power_usage(&controller))
+}
+
+#[get("/recordings/")]
+async fn list_recordings(
+    controller: web::Data<AnimationController>,
+    output: web::Data<output::Logic>,
+) -> HttpResponse {
+    let controller = controller.lock().await;
+    match output.list_recordings(&controller).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => error_response(e),
+    }
+}
+
+#[post("/recordings/start/")]
+async fn start_recording(
+    request: web::Json<RecordingRequest>,
+    controller: web::Data<AnimationController>,
+    output: web::Data<output::Logic>,
+) -> HttpResponse {
+    let controller = controller.lock().await;
+    match output.start_recording(&request.name, &controller).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => error_response(e),
+    }
+}
+
+#[post("/recordings/stop/")]
+async fn stop_recording(
+    controller: web::Data<AnimationController>,
+    output: web::Data<output::Logic>,
+) -> HttpResponse {
+    let controller = controller.lock().await;
+    match output.stop_recording(&controller).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => error_response(e),
+    }
+}
+
+#[post("/replay/start/")]
+async fn start_replay(
+    request: web::Json<ReplayRequest>,
+    controller: web::Data<AnimationController>,
+    output: web::Data<output::Logic>,
+) -> HttpResponse {
+    let controller = controller.lock().await;
+    match output
+        .start_replay(&request.name, request.looped, &controller)
+        .await
+    {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => error_response(e),
+    }
+}
+
+#[post("/replay/stop/")]
+async fn stop_replay(
+    controller: web::Data<AnimationController>,
+    output: web::Data<output::Logic>,
+) -> HttpResponse {
+    let controller = controller.lock().await;
+    match output.stop_replay(&controller).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => error_response(e),
+    }
+}
+
+pub fn service() -> Scope {
+    web::scope("/output")
+        .service(get)
+        .service(set)
+        .service(power)
+        .service(list_recordings)
+        .service(start_recording)
+        .service(stop_recording)
+        .service(start_replay)
+        .service(stop_replay)
+}
@@ -0,0 +1,34 @@
+use webapi_model::PostProcessing;
+
+use crate::db::SharedDbConnection;
+
+#[derive(Debug, Clone)]
+pub struct Storage {
+    conn: SharedDbConnection,
+}
+
+impl Storage {
+    pub fn new(conn: SharedDbConnection) -> Self {
+        Self { conn }
+    }
+
+    pub async fn save(&self, post_processing: &PostProcessing) -> anyhow::Result<()> {
+        let post_processing = serde_json::to_vec(post_processing)?;
+        sqlx::query!(
+            "INSERT INTO output_settings(id, post_processing) VALUES (0, $1) ON CONFLICT(id) DO UPDATE SET post_processing = excluded.post_processing",
+            post_processing
+        )
+        .execute(&mut *self.conn.lock().await)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn fetch(&self) -> anyhow::Result<Option<PostProcessing>> {
+        sqlx::query!("SELECT post_processing FROM output_settings WHERE id = 0")
+            .fetch_optional(&mut *self.conn.lock().await)
+            .await?
+            .map(|r| Ok(serde_json::from_slice(&r.post_processing)?))
+            .transpose()
+    }
+}
@@ -13,6 +13,23 @@ pub enum LogicError {
 
     #[error("no animation selected")]
     NoAnimationSelected,
+
+    #[error("no such layer: {0}")]
+    NoSuchLayer(String),
+
+    #[error("no such zone: {0}")]
+    NoSuchZone(String),
+}
+
+impl From<rustmas_animator::ControllerError> for LogicError {
+    fn from(value: rustmas_animator::ControllerError) -> Self {
+        match value {
+            rustmas_animator::ControllerError::NoAnimationSelected => Self::NoAnimationSelected,
+            rustmas_animator::ControllerError::NoSuchLayer(layer) => Self::NoSuchLayer(layer),
+            rustmas_animator::ControllerError::NoSuchZone(zone) => Self::NoSuchZone(zone),
+            e => Self::InternalError(e.to_string()),
+        }
+    }
 }
 
 pub struct Logic {
@@ -27,18 +44,23 @@ impl Logic {
     pub async fn restore_from_db(
         &self,
         controller: &mut rustmas_animator::Controller,
+        zone: Option<&str>,
+        layer: Option<&str>,
         configuration: Configuration,
     ) -> Result<Configuration, LogicError> {
         let db_params = self
             .storage
-            .fetch(&configuration.id)
+            .fetch(&configuration.id, zone, layer)
             .await
             .map_err(|e| LogicError::InternalError(e.to_string()))?;
 
         let Some(values) = db_params else {
-            match controller.get_parameter_values().await {
+            match controller.get_parameter_values(zone, layer).await {
                 Ok(params) => {
-                    let _ = self.storage.save(&configuration.id, &params).await;
+                    let _ = self
+                        .storage
+                        .save(&configuration.id, zone, layer, &params)
+                        .await;
                 }
                 Err(e) => {
                     warn!("Failed to set parameters in DB: {}", e);
@@ -47,32 +69,28 @@ impl Logic {
             return Ok(configuration);
         };
 
-        let defaults = controller
-            .get_parameter_values()
-            .await
-            .map_err(|e| LogicError::InternalError(e.to_string()))?;
+        let defaults = controller.get_parameter_values(zone, layer).await?;
 
         let values = reconcile_parameters(defaults, values, &configuration.schema);
-        let _ = controller.set_parameters(&values).await;
+        let _ = controller.set_parameters(zone, layer, &values).await;
         Ok(Configuration {
             values,
             ..configuration
         })
     }
 
-    pub async fn save(&self, controller: &rustmas_animator::Controller) -> Result<(), LogicError> {
-        let parameter_values = controller
-            .get_parameter_values()
-            .await
-            .map_err(|e| LogicError::InternalError(e.to_string()))?;
+    pub async fn save(
+        &self,
+        controller: &rustmas_animator::Controller,
+        zone: Option<&str>,
+        layer: Option<&str>,
+    ) -> Result<(), LogicError> {
+        let parameter_values = controller.get_parameter_values(zone, layer).await?;
 
-        let animation_id = controller
-            .current_animation_id()
-            .await
-            .ok_or(LogicError::NoAnimationSelected)?;
+        let animation_id = animation_id(controller, zone, layer).await?;
 
         self.storage
-            .save(&animation_id, &parameter_values)
+            .save(&animation_id, zone, layer, &parameter_values)
             .await
             .map_err(|e| LogicError::InternalError(e.to_string()))
     }
@@ -80,17 +98,13 @@ impl Logic {
     pub async fn reset(
         &self,
         controller: &mut rustmas_animator::Controller,
+        zone: Option<&str>,
+        layer: Option<&str>,
     ) -> Result<Configuration, LogicError> {
-        let animation_id = controller
-            .current_animation_id()
-            .await
-            .ok_or(LogicError::NoAnimationSelected)?;
+        let animation_id = animation_id(controller, zone, layer).await?;
 
-        match self.storage.fetch(&animation_id).await {
-            Ok(Some(params)) => controller
-                .set_parameters(&params)
-                .await
-                .map_err(|e| LogicError::InternalError(e.to_string())),
+        match self.storage.fetch(&animation_id, zone, layer).await {
+            Ok(Some(params)) => Ok(controller.set_parameters(zone, layer, &params).await?),
             Ok(None) => Err(LogicError::InternalError(
                 "No parameters stored for this animation".to_string(),
             )),
@@ -105,6 +119,25 @@ impl From<SharedDbConnection> for Logic {
     }
 }
 
+async fn animation_id(
+    controller: &rustmas_animator::Controller,
+    zone: Option<&str>,
+    layer: Option<&str>,
+) -> Result<String, LogicError> {
+    if let Some(animation_id) = controller.animation_id(zone, layer).await {
+        return Ok(animation_id);
+    }
+
+    // Find out which part of the request was invalid
+    match controller.layers(zone).await {
+        Err(e) => Err(e.into()),
+        Ok(_) => Err(match layer {
+            Some(layer) => LogicError::NoSuchLayer(layer.to_owned()),
+            None => LogicError::NoAnimationSelected,
+        }),
+    }
+}
+
 fn reconcile_parameters(
     defaults: HashMap<String, ParameterValue>,
     mut values: HashMap<String, ParameterValue>,
@@ -131,13 +164,42 @@ fn reconcile_parameters(
                         )
                     }
                     (c @ ParameterValue::Color(_), ValueSchema::Color) => c,
-                    (ParameterValue::EnumOption(e), ValueSchema::Enum { values }) => {
+                    // Strings are received as enum options, whatever the parameter
+                    (
+                        ParameterValue::EnumOption(e) | ParameterValue::Text(e),
+                        ValueSchema::Enum { values },
+                    ) => {
                         if values.into_iter().any(|en| en.value == e) {
                             ParameterValue::EnumOption(e)
                         } else {
                             v
                         }
                     }
+                    (b @ ParameterValue::Boolean(_), ValueSchema::Boolean) => b,
+                    (
+                        ParameterValue::EnumOption(t) | ParameterValue::Text(t),
+                        ValueSchema::Text { max_length },
+                    ) => ParameterValue::Text(t.chars().take(max_length).collect()),
+                    (ParameterValue::Number(n), ValueSchema::Duration { min, max }) => {
+                        ParameterValue::Number(n.clamp(min, max))
+                    }
+                    (ParameterValue::Vector3(v), ValueSchema::Vector3 { min, max }) => {
+                        ParameterValue::Vector3(v.map(|c| c.clamp(min, max)))
+                    }
+                    (ParameterValue::Palette(colors), ValueSchema::Palette)
+                        if !colors.is_empty() =>
+                    {
+                        ParameterValue::Palette(colors)
+                    }
+                    (ParameterValue::Gradient(mut stops), ValueSchema::Gradient)
+                        if !stops.is_empty() =>
+                    {
+                        for stop in &mut stops {
+                            stop.position = stop.position.clamp(0.0, 1.0);
+                        }
+                        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+                        ParameterValue::Gradient(stops)
+                    }
                     _ => v,
                 }
             } else {
@@ -147,3 +209,79 @@ fn reconcile_parameters(
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use animation_api::schema::EnumOption;
+    use webapi_model::ParameterSchema;
+
+    use super::*;
+
+    fn schema() -> ConfigurationSchema {
+        let parameter = |id: &str, value| ParameterSchema {
+            id: id.to_owned(),
+            name: id.to_owned(),
+            description: None,
+            value,
+            visible_when: None,
+            group: None,
+        };
+        ConfigurationSchema {
+            parameters: vec![
+                parameter("message", ValueSchema::Text { max_length: 5 }),
+                parameter(
+                    "mode",
+                    ValueSchema::Enum {
+                        values: vec![EnumOption {
+                            name: "Fast".to_owned(),
+                            description: None,
+                            value: "fast".to_owned(),
+                        }],
+                    },
+                ),
+            ],
+            custom_triggers: Vec::new(),
+        }
+    }
+
+    fn values(message: ParameterValue, mode: ParameterValue) -> HashMap<String, ParameterValue> {
+        HashMap::from([("message".to_owned(), message), ("mode".to_owned(), mode)])
+    }
+
+    #[test]
+    fn strings_become_values_of_their_parameter_kind() {
+        let defaults = values(
+            ParameterValue::Text(String::new()),
+            ParameterValue::EnumOption("slow".to_owned()),
+        );
+        let received = values(
+            ParameterValue::EnumOption("hello world".to_owned()),
+            ParameterValue::Text("fast".to_owned()),
+        );
+
+        assert_eq!(
+            reconcile_parameters(defaults, received, &schema()),
+            values(
+                ParameterValue::Text("hello".to_owned()),
+                ParameterValue::EnumOption("fast".to_owned()),
+            )
+        );
+    }
+
+    #[test]
+    fn unknown_enum_options_keep_defaults() {
+        let defaults = values(
+            ParameterValue::Text("hi".to_owned()),
+            ParameterValue::EnumOption("slow".to_owned()),
+        );
+        let received = values(
+            ParameterValue::Number(1.0),
+            ParameterValue::EnumOption("faster".to_owned()),
+        );
+
+        assert_eq!(
+            reconcile_parameters(defaults.clone(), received, &schema()),
+            defaults
+        );
+    }
+}
@@ -1,63 +1,97 @@
 use actix_web::{HttpResponse, Scope, get, post, web};
 use serde_json::json;
-use webapi_model::{GetParametersResponse, SetAnimationParametersRequest};
+use webapi_model::{GetParametersResponse, ParametersQuery, SetAnimationParametersRequest};
 
 use crate::{AnimationController, parameters};
 
+fn error_response(e: parameters::LogicError) -> HttpResponse {
+    match e {
+        parameters::LogicError::InternalError(e) => {
+            HttpResponse::InternalServerError().json(json!({ "error": e.to_string() }))
+        }
+        e @ parameters::LogicError::NoAnimationSelected => {
+            HttpResponse::PreconditionFailed().json(json!({ "error": e.to_string() }))
+        }
+        e @ (parameters::LogicError::NoSuchLayer(_) | parameters::LogicError::NoSuchZone(_)) => {
+            HttpResponse::NotFound().json(json!({ "error": e.to_string() }))
+        }
+    }
+}
+
 #[get("/")]
-async fn get(controller: web::Data<AnimationController>) -> HttpResponse {
-    match controller.lock().await.get_parameters().await {
+async fn get(
+    query: web::Query<ParametersQuery>,
+    controller: web::Data<AnimationController>,
+) -> HttpResponse {
+    match controller
+        .lock()
+        .await
+        .get_parameters(query.zone.as_deref(), query.layer.as_deref())
+        .await
+    {
         Ok(animation) => HttpResponse::Ok().json(GetParametersResponse {
             animation: Some(animation),
         }),
-        Err(e) => HttpResponse::InternalServerError().json(json!({ "error": e.to_string() })),
+        Err(e) => error_response(e.into()),
     }
 }
 
 #[post("/")]
 async fn post(
+    query: web::Query<ParametersQuery>,
     params: web::Json<SetAnimationParametersRequest>,
     controller: web::Data<AnimationController>,
 ) -> HttpResponse {
-    match controller.lock().await.set_parameters(&params.values).await {
+    match controller
+        .lock()
+        .await
+        .set_parameters(
+            query.zone.as_deref(),
+            query.layer.as_deref(),
+            &params.values,
+        )
+        .await
+    {
         Ok(_) => HttpResponse::Ok().json(()),
-        Err(e) => HttpResponse::InternalServerError().json(json!({"error": e.to_string()})),
+        Err(e) => error_response(e.into()),
     }
 }
 
 #[post("/save/")]
 async fn save(
+    query: web::Query<ParametersQuery>,
     controller: web::Data<AnimationController>,
     parameters: web::Data<parameters::Logic>,
 ) -> HttpResponse {
     let controller = controller.lock().await;
-    match parameters.save(&controller).await {
+    match parameters
+        .save(&controller, query.zone.as_deref(), query.layer.as_deref())
+        .await
+    {
         Ok(_) => HttpResponse::Ok().json(()),
-        Err(parameters::LogicError::InternalError(e)) => {
-            HttpResponse::InternalServerError().json(json!({ "error": e.to_string() }))
-        }
-        Err(e @ parameters::LogicError::NoAnimationSelected) => {
-            HttpResponse::PreconditionFailed().json(json!({ "error": e.to_string() }))
-        }
+        Err(e) => error_response(e),
     }
 }
 
 #[post("/reset/")]
 async fn reset(
+    query: web::Query<ParametersQuery>,
     controller: web::Data<AnimationController>,
     parameters: web::Data<parameters::Logic>,
 ) -> HttpResponse {
     let mut controller = controller.lock().await;
-    match parameters.reset(&mut controller).await {
+    match parameters
+        .reset(
+            &mut controller,
+            query.zone.as_deref(),
+            query.layer.as_deref(),
+        )
+        .await
+    {
         Ok(animation) => HttpResponse::Ok().json(GetParametersResponse {
             animation: Some(animation),
         }),
-        Err(parameters::LogicError::InternalError(e)) => {
-            HttpResponse::InternalServerError().json(json!({ "error": e.to_string() }))
-        }
-        Err(e @ parameters::LogicError::NoAnimationSelected) => {
-            HttpResponse::PreconditionFailed().json(json!({ "error": e.to_string() }))
-        }
+        Err(e) => error_response(e),
     }
 }
 
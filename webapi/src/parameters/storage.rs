@@ -15,14 +15,20 @@ impl Storage {
         Self { conn }
     }
 
+    /// Values are stored separately for every zone and layer running the
+    /// animation, `None` meaning the main zone and the base animation.
     pub async fn save(
         &self,
         animation_id: &str,
+        zone: Option<&str>,
+        layer: Option<&str>,
         parameters: &HashMap<String, ParameterValue>,
     ) -> Result<(), Box<dyn Error>> {
         let query =
-            sqlx::query("INSERT INTO animation_parameters(animation, parameters) VALUES (?, ?) ON CONFLICT(animation) DO UPDATE SET parameters=excluded.parameters;")
+            sqlx::query("INSERT INTO animation_parameters(animation, zone, layer, parameters) VALUES (?, ?, ?, ?) ON CONFLICT(animation, zone, layer) DO UPDATE SET parameters=excluded.parameters;")
                 .bind(animation_id)
+                .bind(zone.unwrap_or_default())
+                .bind(layer.unwrap_or_default())
                 .bind(serde_json::to_string(parameters).unwrap());
         self.conn.lock().await.execute(query).await?;
         Ok(())
@@ -31,9 +37,15 @@ impl Storage {
     pub async fn fetch(
         &self,
         animation_id: &str,
+        zone: Option<&str>,
+        layer: Option<&str>,
     ) -> Result<Option<HashMap<String, ParameterValue>>, Box<dyn Error>> {
-        let query = sqlx::query("SELECT parameters FROM animation_parameters WHERE animation = ?;")
-            .bind(animation_id);
+        let query = sqlx::query(
+            "SELECT parameters FROM animation_parameters WHERE animation = ? AND zone = ? AND layer = ?;",
+        )
+        .bind(animation_id)
+        .bind(zone.unwrap_or_default())
+        .bind(layer.unwrap_or_default());
 
         let result = self
             .conn
@@ -49,3 +61,43 @@ impl Storage {
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn values_are_stored_per_zone_and_layer() {
+        let storage = Storage::new(SharedDbConnection::in_memory().await.unwrap());
+        let values = |n: f64| HashMap::from([("speed".to_owned(), ParameterValue::Number(n))]);
+
+        storage
+            .save("rainbow", None, None, &values(1.0))
+            .await
+            .unwrap();
+        storage
+            .save("rainbow", None, Some("layer-1"), &values(2.0))
+            .await
+            .unwrap();
+        storage
+            .save("rainbow", Some("tree"), Some("layer-1"), &values(3.0))
+            .await
+            .unwrap();
+        storage
+            .save("rainbow", None, Some("layer-1"), &values(4.0))
+            .await
+            .unwrap();
+
+        let fetch = |zone, layer| storage.fetch("rainbow", zone, layer);
+        assert_eq!(fetch(None, None).await.unwrap(), Some(values(1.0)));
+        assert_eq!(
+            fetch(None, Some("layer-1")).await.unwrap(),
+            Some(values(4.0))
+        );
+        assert_eq!(
+            fetch(Some("tree"), Some("layer-1")).await.unwrap(),
+            Some(values(3.0))
+        );
+        assert_eq!(fetch(Some("tree"), None).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,370 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{info, warn};
+use rand::seq::SliceRandom;
+use tokio::sync::{Mutex, mpsc};
+use tokio::task::JoinHandle;
+use webapi_model::{ListPlaylistsResponse, Playlist, PlaylistEntry, PlaylistMode};
+
+use crate::db::SharedDbConnection;
+use crate::{AnimationController, animations, parameters, playlists};
+
+#[derive(Debug, thiserror::Error)]
+pub enum LogicError {
+    #[error("failed to perform operation: {0}")]
+    InternalError(String),
+
+    #[error("no such playlist: {0}")]
+    NoSuchPlaylist(i64),
+
+    #[error("invalid playlist: {0}")]
+    InvalidPlaylist(String),
+
+    #[error("no playlist is running")]
+    NoPlaylistRunning,
+}
+
+/// Longest time a single entry may play, one week.
+const MAX_DURATION_SECONDS: f64 = 7.0 * 24.0 * 60.0 * 60.0;
+
+struct Runner {
+    playlist_id: i64,
+    skip_sender: mpsc::Sender<()>,
+    join_handle: JoinHandle<()>,
+}
+
+pub struct Logic {
+    storage: playlists::Storage,
+    runner: Mutex<Option<Runner>>,
+}
+
+impl Logic {
+    fn new(storage: playlists::Storage) -> Self {
+        Self {
+            storage,
+            runner: Mutex::new(None),
+        }
+    }
+
+    pub async fn create(
+        &self,
+        name: &str,
+        mode: PlaylistMode,
+        entries: &[PlaylistEntry],
+        animations: &animations::Logic,
+    ) -> Result<ListPlaylistsResponse, LogicError> {
+        validate_entries(entries, &known_animations(animations).await?)?;
+        self.storage
+            .create(name, mode, entries)
+            .await
+            .map_err(|e| LogicError::InternalError(e.to_string()))?;
+        self.list().await
+    }
+
+    pub async fn remove(&self, playlist_id: i64) -> Result<ListPlaylistsResponse, LogicError> {
+        if self.current_playlist_id().await == Some(playlist_id) {
+            self.stop().await?;
+        }
+
+        let removed = self
+            .storage
+            .delete(playlist_id)
+            .await
+            .map_err(|e| LogicError::InternalError(e.to_string()))?;
+        if !removed {
+            return Err(LogicError::NoSuchPlaylist(playlist_id));
+        }
+
+        self.list().await
+    }
+
+    pub async fn list(&self) -> Result<ListPlaylistsResponse, LogicError> {
+        Ok(ListPlaylistsResponse {
+            playlists: self
+                .storage
+                .fetch_all()
+                .await
+                .map_err(|e| LogicError::InternalError(e.to_string()))?,
+            current_playlist_id: self.current_playlist_id().await,
+        })
+    }
+
+    pub async fn current_playlist_id(&self) -> Option<i64> {
+        self.runner
+            .lock()
+            .await
+            .as_ref()
+            .filter(|runner| !runner.join_handle.is_finished())
+            .map(|runner| runner.playlist_id)
+    }
+
+    pub async fn start(
+        &self,
+        playlist_id: i64,
+        controller: Arc<AnimationController>,
+        animations: Arc<animations::Logic>,
+        parameters: Arc<parameters::Logic>,
+    ) -> Result<(), LogicError> {
+        let playlist = self
+            .storage
+            .fetch_by_id(playlist_id)
+            .await
+            .map_err(|e| LogicError::InternalError(e.to_string()))?
+            .ok_or(LogicError::NoSuchPlaylist(playlist_id))?;
+        validate_entries(&playlist.entries, &known_animations(&animations).await?)?;
+
+        let mut runner = self.runner.lock().await;
+        if let Some(previous) = runner.take() {
+            previous.join_handle.abort();
+        }
+
+        info!("Starting playlist {} ({})", playlist.name, playlist.id);
+        let (skip_sender, skip_receiver) = mpsc::channel(1);
+        *runner = Some(Runner {
+            playlist_id,
+            skip_sender,
+            join_handle: tokio::spawn(run_playlist(
+                playlist,
+                skip_receiver,
+                controller,
+                animations,
+                parameters,
+            )),
+        });
+
+        Ok(())
+    }
+
+    pub async fn stop(&self) -> Result<(), LogicError> {
+        let runner = self
+            .runner
+            .lock()
+            .await
+            .take()
+            .filter(|runner| !runner.join_handle.is_finished())
+            .ok_or(LogicError::NoPlaylistRunning)?;
+
+        info!("Stopping playlist {}", runner.playlist_id);
+        runner.join_handle.abort();
+        Ok(())
+    }
+
+    pub async fn skip(&self) -> Result<(), LogicError> {
+        let runner = self.runner.lock().await;
+        let runner = runner
+            .as_ref()
+            .filter(|runner| !runner.join_handle.is_finished())
+            .ok_or(LogicError::NoPlaylistRunning)?;
+
+        // A full channel means a skip is already pending, so there is nothing more to do
+        let _ = runner.skip_sender.try_send(());
+        Ok(())
+    }
+}
+
+impl From<SharedDbConnection> for Logic {
+    fn from(value: SharedDbConnection) -> Self {
+        Self::new(playlists::Storage::new(value))
+    }
+}
+
+async fn known_animations(animations: &animations::Logic) -> Result<HashSet<String>, LogicError> {
+    animations
+        .animation_ids()
+        .await
+        .map_err(|e| LogicError::InternalError(e.to_string()))
+}
+
+fn validate_entries(
+    entries: &[PlaylistEntry],
+    known_animations: &HashSet<String>,
+) -> Result<(), LogicError> {
+    if entries.is_empty() {
+        return Err(LogicError::InvalidPlaylist(
+            "playlist needs at least one entry".to_owned(),
+        ));
+    }
+
+    if let Some(entry) = entries
+        .iter()
+        .find(|e| !(e.duration_seconds > 0.0 && e.duration_seconds <= MAX_DURATION_SECONDS))
+    {
+        return Err(LogicError::InvalidPlaylist(format!(
+            "entry for animation {} has invalid duration: {}",
+            entry.animation_id, entry.duration_seconds
+        )));
+    }
+
+    if let Some(entry) = entries
+        .iter()
+        .find(|e| !known_animations.contains(&e.animation_id))
+    {
+        return Err(LogicError::InvalidPlaylist(format!(
+            "no such animation: {}",
+            entry.animation_id
+        )));
+    }
+
+    Ok(())
+}
+
+fn play_order(mode: PlaylistMode, len: usize) -> Vec<usize> {
+    let mut order = (0..len).collect::<Vec<_>>();
+    if mode == PlaylistMode::Shuffle {
+        order.shuffle(&mut rand::rng());
+    }
+    order
+}
+
+async fn play_entry(
+    entry: &PlaylistEntry,
+    controller: &AnimationController,
+    animations: &animations::Logic,
+    parameters: &parameters::Logic,
+) -> Result<(), animations::LogicError> {
+    let mut controller = controller.lock().await;
+    let configuration = animations
+        .switch(
+            None,
+            &entry.animation_id,
+            None,
+            None,
+            &mut controller,
+            parameters,
+        )
+        .await?;
+
+    if let Some(overrides) = &entry.params {
+        let mut values = configuration.values;
+        values.extend(overrides.clone());
+        controller
+            .set_parameters(None, None, &values)
+            .await
+            .map_err(|e| animations::LogicError::InternalError(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
+async fn run_playlist(
+    playlist: Playlist,
+    mut skip_receiver: mpsc::Receiver<()>,
+    controller: Arc<AnimationController>,
+    animations: Arc<animations::Logic>,
+    parameters: Arc<parameters::Logic>,
+) {
+    loop {
+        for index in play_order(playlist.mode, playlist.entries.len()) {
+            let entry = &playlist.entries[index];
+            info!(
+                "Playlist {}: switching to {} for {:.1} seconds",
+                playlist.id, entry.animation_id, entry.duration_seconds
+            );
+            if let Err(e) = play_entry(entry, &controller, &animations, &parameters).await {
+                warn!(
+                    "Playlist {}: failed to switch to {}: {e}",
+                    playlist.id, entry.animation_id
+                );
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs_f64(entry.duration_seconds)) => {},
+                _ = skip_receiver.recv() => {
+                    info!("Playlist {}: skipping {}", playlist.id, entry.animation_id);
+                },
+            }
+        }
+
+        if playlist.mode == PlaylistMode::Once {
+            info!("Playlist {} finished", playlist.id);
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(animation_id: &str, duration_seconds: f64) -> PlaylistEntry {
+        PlaylistEntry {
+            animation_id: animation_id.to_owned(),
+            params: None,
+            duration_seconds,
+        }
+    }
+
+    fn known() -> HashSet<String> {
+        HashSet::from(["rainbow".to_owned(), "blank".to_owned()])
+    }
+
+    #[test]
+    fn valid_entries_are_accepted() {
+        let entries = [
+            entry("rainbow", 30.0),
+            entry("blank", 0.5),
+            entry("blank", MAX_DURATION_SECONDS),
+        ];
+        assert!(validate_entries(&entries, &known()).is_ok());
+    }
+
+    #[test]
+    fn invalid_entries_are_rejected() {
+        let invalid = [
+            vec![],
+            vec![entry("rainbow", 30.0), entry("blank", 0.0)],
+            vec![entry("rainbow", -1.0)],
+            vec![entry("rainbow", f64::NAN)],
+            vec![entry("rainbow", f64::INFINITY)],
+            vec![entry("rainbow", 1e300)],
+            vec![entry("rainbow", MAX_DURATION_SECONDS + 1.0)],
+            vec![entry("rainbow", 30.0), entry("unknown", 30.0)],
+        ];
+        for entries in invalid {
+            assert!(
+                matches!(
+                    validate_entries(&entries, &known()),
+                    Err(LogicError::InvalidPlaylist(_))
+                ),
+                "{entries:?} should be rejected"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn finished_playlist_cannot_be_stopped() {
+        let logic = Logic::from(SharedDbConnection::in_memory().await.unwrap());
+        let (skip_sender, _) = mpsc::channel(1);
+        let join_handle = tokio::spawn(async {});
+        while !join_handle.is_finished() {
+            tokio::task::yield_now().await;
+        }
+        *logic.runner.lock().await = Some(Runner {
+            playlist_id: 1,
+            skip_sender,
+            join_handle,
+        });
+
+        assert!(matches!(
+            logic.stop().await,
+            Err(LogicError::NoPlaylistRunning)
+        ));
+        assert!(logic.runner.lock().await.is_none());
+    }
+
+    #[test]
+    fn once_and_loop_play_entries_in_order() {
+        assert_eq!(play_order(PlaylistMode::Once, 4), vec![0, 1, 2, 3]);
+        assert_eq!(play_order(PlaylistMode::Loop, 4), vec![0, 1, 2, 3]);
+        assert!(play_order(PlaylistMode::Loop, 0).is_empty());
+    }
+
+    #[test]
+    fn shuffle_plays_every_entry_once() {
+        let mut order = play_order(PlaylistMode::Shuffle, 10);
+        order.sort();
+        assert_eq!(order, (0..10).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,104 @@
+use actix_web::{HttpResponse, Scope, get, post, web};
+use serde_json::json;
+use webapi_model::{CreatePlaylistRequest, PlaylistRequest};
+
+use crate::{AnimationController, animations, parameters, playlists};
+
+fn error_response(e: playlists::LogicError) -> HttpResponse {
+    match e {
+        playlists::LogicError::InternalError(e) => {
+            HttpResponse::InternalServerError().json(json!({ "error": e }))
+        }
+        e @ playlists::LogicError::NoSuchPlaylist(_) => {
+            HttpResponse::NotFound().json(json!({ "error": e.to_string() }))
+        }
+        e @ playlists::LogicError::InvalidPlaylist(_) => {
+            HttpResponse::BadRequest().json(json!({ "error": e.to_string() }))
+        }
+        e @ playlists::LogicError::NoPlaylistRunning => {
+            HttpResponse::PreconditionFailed().json(json!({ "error": e.to_string() }))
+        }
+    }
+}
+
+#[get("/list/")]
+async fn list(playlists: web::Data<playlists::Logic>) -> HttpResponse {
+    match playlists.list().await {
+        Ok(playlists) => HttpResponse::Ok().json(playlists),
+        Err(e) => error_response(e),
+    }
+}
+
+#[post("/create/")]
+async fn create(
+    form: web::Json<CreatePlaylistRequest>,
+    playlists: web::Data<playlists::Logic>,
+    animations: web::Data<animations::Logic>,
+) -> HttpResponse {
+    match playlists
+        .create(&form.name, form.mode, &form.entries, &animations)
+        .await
+    {
+        Ok(playlists) => HttpResponse::Ok().json(playlists),
+        Err(e) => error_response(e),
+    }
+}
+
+#[post("/remove/")]
+async fn remove(
+    form: web::Json<PlaylistRequest>,
+    playlists: web::Data<playlists::Logic>,
+) -> HttpResponse {
+    match playlists.remove(form.playlist_id).await {
+        Ok(playlists) => HttpResponse::Ok().json(playlists),
+        Err(e) => error_response(e),
+    }
+}
+
+#[post("/start/")]
+async fn start(
+    form: web::Json<PlaylistRequest>,
+    playlists: web::Data<playlists::Logic>,
+    controller: web::Data<AnimationController>,
+    animations: web::Data<animations::Logic>,
+    parameters: web::Data<parameters::Logic>,
+) -> HttpResponse {
+    match playlists
+        .start(
+            form.playlist_id,
+            controller.into_inner(),
+            animations.into_inner(),
+            parameters.into_inner(),
+        )
+        .await
+    {
+        Ok(_) => HttpResponse::Ok().json(()),
+        Err(e) => error_response(e),
+    }
+}
+
+#[post("/stop/")]
+async fn stop(playlists: web::Data<playlists::Logic>) -> HttpResponse {
+    match playlists.stop().await {
+        Ok(_) => HttpResponse::Ok().json(()),
+        Err(e) => error_response(e),
+    }
+}
+
+#[post("/skip/")]
+async fn skip(playlists: web::Data<playlists::Logic>) -> HttpResponse {
+    match playlists.skip().await {
+        Ok(_) => HttpResponse::Ok().json(()),
+        Err(e) => error_response(e),
+    }
+}
+
+pub fn service() -> Scope {
+    web::scope("/playlists")
+        .service(list)
+        .service(create)
+        .service(remove)
+        .service(start)
+        .service(stop)
+        .service(skip)
+}
@@ -0,0 +1,104 @@
+use anyhow::anyhow;
+use log::warn;
+use webapi_model::{Playlist, PlaylistEntry, PlaylistMode};
+
+use crate::db::SharedDbConnection;
+
+#[derive(Debug, Clone)]
+pub struct Storage {
+    conn: SharedDbConnection,
+}
+
+fn mode_to_str(mode: PlaylistMode) -> &'static str {
+    match mode {
+        PlaylistMode::Once => "once",
+        PlaylistMode::Loop => "loop",
+        PlaylistMode::Shuffle => "shuffle",
+    }
+}
+
+fn mode_from_str(mode: &str) -> anyhow::Result<PlaylistMode> {
+    match mode {
+        "once" => Ok(PlaylistMode::Once),
+        "loop" => Ok(PlaylistMode::Loop),
+        "shuffle" => Ok(PlaylistMode::Shuffle),
+        mode => Err(anyhow!("Unknown playlist mode: {mode}")),
+    }
+}
+
+fn playlist_from_row(
+    id: i64,
+    name: String,
+    mode: &str,
+    entries: &[u8],
+) -> anyhow::Result<Playlist> {
+    let entries = serde_json::from_slice::<Vec<PlaylistEntry>>(entries)
+        .map_err(|_| anyhow!("Invalid entries for playlist with id {id}"))?;
+    Ok(Playlist {
+        id,
+        name,
+        mode: mode_from_str(mode)?,
+        entries,
+    })
+}
+
+impl Storage {
+    pub fn new(conn: SharedDbConnection) -> Self {
+        Self { conn }
+    }
+
+    pub async fn create(
+        &self,
+        name: &str,
+        mode: PlaylistMode,
+        entries: &[PlaylistEntry],
+    ) -> anyhow::Result<i64> {
+        let mode = mode_to_str(mode);
+        let entries = serde_json::to_vec(entries)?;
+        let id = sqlx::query!(
+            "INSERT INTO playlists(name, mode, entries) VALUES ($1, $2, $3) RETURNING id",
+            name,
+            mode,
+            entries
+        )
+        .fetch_one(&mut *self.conn.lock().await)
+        .await?
+        .id;
+
+        Ok(id)
+    }
+
+    pub async fn fetch_by_id(&self, playlist_id: i64) -> anyhow::Result<Option<Playlist>> {
+        sqlx::query!(
+            "SELECT id, name, mode, entries FROM playlists WHERE id = $1",
+            playlist_id
+        )
+        .fetch_optional(&mut *self.conn.lock().await)
+        .await?
+        .map(|r| playlist_from_row(r.id, r.name, &r.mode, &r.entries))
+        .transpose()
+    }
+
+    pub async fn fetch_all(&self) -> anyhow::Result<Vec<Playlist>> {
+        let playlists = sqlx::query!("SELECT id, name, mode, entries FROM playlists ORDER BY id")
+            .fetch_all(&mut *self.conn.lock().await)
+            .await?
+            .into_iter()
+            .filter_map(|r| {
+                playlist_from_row(r.id, r.name, &r.mode, &r.entries)
+                    .inspect_err(|e| warn!("Skipping invalid playlist: {e}"))
+                    .ok()
+            })
+            .collect();
+
+        Ok(playlists)
+    }
+
+    pub async fn delete(&self, playlist_id: i64) -> anyhow::Result<bool> {
+        let result = sqlx::query!("DELETE FROM playlists WHERE id = $1", playlist_id)
+            .execute(&mut *self.conn.lock().await)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
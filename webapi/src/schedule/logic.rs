@@ -0,0 +1,557 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone};
+use log::{info, warn};
+use tokio::sync::{Mutex, Notify};
+use tokio::task::JoinHandle;
+use webapi_model::{
+    ListScheduleResponse, ScheduleAction, ScheduleRule, ScheduleTime, ScheduledEvent, Weekday,
+};
+
+use crate::config::{Location, RustmasConfig};
+use crate::db::SharedDbConnection;
+use crate::schedule::sun::sunrise_sunset;
+use crate::{AnimationController, animations, parameters, schedule};
+
+/// Upper bound for the time between checks, so that changes of the system clock
+/// (e.g. daylight saving time) are picked up reasonably quickly.
+const MAX_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Largest offset from sunrise or sunset, which keeps occurrences within the
+/// neighbouring dates searched for them.
+const MAX_OFFSET_MINUTES: i64 = 12 * 60;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LogicError {
+    #[error("failed to perform operation: {0}")]
+    InternalError(String),
+
+    #[error("no such schedule rule: {0}")]
+    NoSuchRule(i64),
+
+    #[error("invalid schedule rule: {0}")]
+    InvalidRule(String),
+}
+
+pub struct Logic {
+    storage: schedule::Storage,
+    location: Option<Location>,
+    rules_changed: Arc<Notify>,
+    runner: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Logic {
+    fn new(storage: schedule::Storage, location: Option<Location>) -> Self {
+        Self {
+            storage,
+            location,
+            rules_changed: Arc::new(Notify::new()),
+            runner: Mutex::new(None),
+        }
+    }
+
+    pub fn from(conn: SharedDbConnection, config: &RustmasConfig) -> Self {
+        Self::new(schedule::Storage::new(conn), config.location)
+    }
+
+    pub async fn create(
+        &self,
+        name: &str,
+        time: ScheduleTime,
+        days: &[Weekday],
+        action: &ScheduleAction,
+    ) -> Result<ListScheduleResponse, LogicError> {
+        self.validate_time(time)?;
+        self.storage
+            .create(name, &time, days, action)
+            .await
+            .map_err(|e| LogicError::InternalError(e.to_string()))?;
+        self.rules_changed.notify_one();
+        self.list().await
+    }
+
+    pub async fn remove(&self, rule_id: i64) -> Result<ListScheduleResponse, LogicError> {
+        let removed = self
+            .storage
+            .delete(rule_id)
+            .await
+            .map_err(|e| LogicError::InternalError(e.to_string()))?;
+        if !removed {
+            return Err(LogicError::NoSuchRule(rule_id));
+        }
+
+        self.rules_changed.notify_one();
+        self.list().await
+    }
+
+    pub async fn list(&self) -> Result<ListScheduleResponse, LogicError> {
+        let rules = self
+            .storage
+            .fetch_all()
+            .await
+            .map_err(|e| LogicError::InternalError(e.to_string()))?;
+
+        let next_event =
+            next_event(&rules, Local::now(), self.location).map(|(rule, time)| ScheduledEvent {
+                rule_id: rule.id,
+                time: time.to_rfc3339(),
+            });
+
+        Ok(ListScheduleResponse { rules, next_event })
+    }
+
+    /// Starts executing schedule rules in the background.
+    pub async fn start(
+        &self,
+        controller: Arc<AnimationController>,
+        animations: Arc<animations::Logic>,
+        parameters: Arc<parameters::Logic>,
+    ) {
+        let mut runner = self.runner.lock().await;
+        if let Some(previous) = runner.take() {
+            previous.abort();
+        }
+
+        *runner = Some(tokio::spawn(run_schedule(
+            self.storage.clone(),
+            self.location,
+            self.rules_changed.clone(),
+            controller,
+            animations,
+            parameters,
+        )));
+    }
+
+    fn validate_time(&self, time: ScheduleTime) -> Result<(), LogicError> {
+        match time {
+            ScheduleTime::At { hour, minute } => {
+                if NaiveTime::from_hms_opt(hour, minute, 0).is_none() {
+                    return Err(LogicError::InvalidRule(format!(
+                        "invalid time of day: {hour}:{minute:02}"
+                    )));
+                }
+            }
+            ScheduleTime::Sunrise { offset_minutes } | ScheduleTime::Sunset { offset_minutes } => {
+                if self.location.is_none() {
+                    return Err(LogicError::InvalidRule(
+                        "sunrise and sunset rules require location to be configured".to_owned(),
+                    ));
+                }
+                if !(-MAX_OFFSET_MINUTES..=MAX_OFFSET_MINUTES).contains(&offset_minutes) {
+                    return Err(LogicError::InvalidRule(format!(
+                        "offset of {offset_minutes} minutes exceeds {MAX_OFFSET_MINUTES} minutes"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn to_chrono_weekday(day: Weekday) -> chrono::Weekday {
+    match day {
+        Weekday::Monday => chrono::Weekday::Mon,
+        Weekday::Tuesday => chrono::Weekday::Tue,
+        Weekday::Wednesday => chrono::Weekday::Wed,
+        Weekday::Thursday => chrono::Weekday::Thu,
+        Weekday::Friday => chrono::Weekday::Fri,
+        Weekday::Saturday => chrono::Weekday::Sat,
+        Weekday::Sunday => chrono::Weekday::Sun,
+    }
+}
+
+/// Returns the time at which the rule fires on the given date, if it does.
+///
+/// Times of day skipped by a change to daylight saving time fire an hour later,
+/// and times repeated by a change back fire the first time only.
+fn occurrence_on<Tz: TimeZone>(
+    rule: &ScheduleRule,
+    date: NaiveDate,
+    location: Option<Location>,
+    tz: &Tz,
+) -> Option<DateTime<Tz>> {
+    if !rule.days.is_empty()
+        && !rule
+            .days
+            .iter()
+            .any(|day| to_chrono_weekday(*day) == date.weekday())
+    {
+        return None;
+    }
+
+    match rule.time {
+        ScheduleTime::At { hour, minute } => {
+            let time = date.and_time(NaiveTime::from_hms_opt(hour, minute, 0)?);
+            tz.from_local_datetime(&time).earliest().or_else(|| {
+                tz.from_local_datetime(&(time + Duration::hours(1)))
+                    .earliest()
+            })
+        }
+        ScheduleTime::Sunrise { offset_minutes } => {
+            let location = location?;
+            let (sunrise, _) = sunrise_sunset(date, location.latitude, location.longitude)?;
+            Some(sunrise.with_timezone(tz) + Duration::minutes(offset_minutes))
+        }
+        ScheduleTime::Sunset { offset_minutes } => {
+            let location = location?;
+            let (_, sunset) = sunrise_sunset(date, location.latitude, location.longitude)?;
+            Some(sunset.with_timezone(tz) + Duration::minutes(offset_minutes))
+        }
+    }
+}
+
+/// Returns all occurrences of rules within the time range `(after, until]`, in chronological order.
+fn occurrences_between<Tz: TimeZone>(
+    rules: &[ScheduleRule],
+    after: DateTime<Tz>,
+    until: DateTime<Tz>,
+    location: Option<Location>,
+) -> Vec<(&ScheduleRule, DateTime<Tz>)> {
+    let tz = after.timezone();
+    // Offsets can move an occurrence to a neighbouring date, so check one extra day on each side
+    let first_date = after.date_naive() - Duration::days(1);
+    let last_date = until.date_naive() + Duration::days(1);
+
+    let mut occurrences = first_date
+        .iter_days()
+        .take_while(|date| *date <= last_date)
+        .flat_map(|date| {
+            let tz = tz.clone();
+            rules
+                .iter()
+                .filter_map(move |rule| Some(rule).zip(occurrence_on(rule, date, location, &tz)))
+        })
+        .filter(|(_, time)| *time > after && *time <= until)
+        .collect::<Vec<_>>();
+    occurrences.sort_by_key(|(_, time)| time.clone());
+    occurrences
+}
+
+fn next_event<Tz: TimeZone>(
+    rules: &[ScheduleRule],
+    after: DateTime<Tz>,
+    location: Option<Location>,
+) -> Option<(&ScheduleRule, DateTime<Tz>)> {
+    // Every rule fires at least once a week, unless the sun does not rise or set
+    let until = after.clone() + Duration::days(8);
+    occurrences_between(rules, after, until, location)
+        .into_iter()
+        .next()
+}
+
+async fn execute(
+    action: &ScheduleAction,
+    controller: &AnimationController,
+    animations: &animations::Logic,
+    parameters: &parameters::Logic,
+) -> Result<(), animations::LogicError> {
+    let mut controller = controller.lock().await;
+    match action {
+        ScheduleAction::Switch {
+            animation_id,
+            params,
+            zone,
+        } => animations
+            .switch(
+                zone.as_deref(),
+                animation_id,
+                params.clone(),
+                None,
+                &mut controller,
+                parameters,
+            )
+            .await
+            .map(|_| ()),
+        ScheduleAction::TurnOff { zone } => {
+            animations.turn_off(zone.as_deref(), &mut controller).await
+        }
+    }
+}
+
+async fn run_schedule(
+    storage: schedule::Storage,
+    location: Option<Location>,
+    rules_changed: Arc<Notify>,
+    controller: Arc<AnimationController>,
+    animations: Arc<animations::Logic>,
+    parameters: Arc<parameters::Logic>,
+) {
+    let mut last_check = Local::now();
+    loop {
+        let rules = match storage.fetch_all().await {
+            Ok(rules) => rules,
+            Err(e) => {
+                warn!("Failed to fetch schedule rules: {e}");
+                Vec::new()
+            }
+        };
+
+        let now = Local::now();
+        for (rule, _) in occurrences_between(&rules, last_check, now, location) {
+            info!("Executing schedule rule {} ({})", rule.name, rule.id);
+            if let Err(e) = execute(&rule.action, &controller, &animations, &parameters).await {
+                warn!("Schedule rule {} failed: {e}", rule.id);
+            }
+        }
+        last_check = now;
+
+        let wait = next_event(&rules, now, location)
+            .and_then(|(_, time)| (time - now).to_std().ok())
+            .unwrap_or(MAX_CHECK_INTERVAL)
+            .min(MAX_CHECK_INTERVAL);
+
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {},
+            _ = rules_changed.notified() => {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, LocalResult, NaiveDateTime, Utc};
+
+    use super::*;
+
+    const WARSAW: Location = Location {
+        latitude: 52.23,
+        longitude: 21.01,
+    };
+
+    /// Central European time, with summer time from 2026-03-29 01:00 UTC
+    /// until 2026-10-25 01:00 UTC.
+    #[derive(Clone, Copy, Debug)]
+    struct CentralEurope;
+
+    impl CentralEurope {
+        fn winter() -> FixedOffset {
+            FixedOffset::east_opt(3600).unwrap()
+        }
+
+        fn summer() -> FixedOffset {
+            FixedOffset::east_opt(7200).unwrap()
+        }
+    }
+
+    impl TimeZone for CentralEurope {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            Self
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_time(NaiveTime::MIN))
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            let offsets = [Self::winter(), Self::summer()]
+                .into_iter()
+                .filter(|offset| self.offset_from_utc_datetime(&(*local - *offset)) == *offset)
+                .collect::<Vec<_>>();
+            match offsets[..] {
+                [offset] => LocalResult::Single(offset),
+                [first, second] => LocalResult::Ambiguous(second, first),
+                _ => LocalResult::None,
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_time(NaiveTime::MIN))
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            let summer_start = datetime("2026-03-29 01:00");
+            let summer_end = datetime("2026-10-25 01:00");
+            if (summer_start..summer_end).contains(utc) {
+                Self::summer()
+            } else {
+                Self::winter()
+            }
+        }
+    }
+
+    fn datetime(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        datetime(text).and_utc()
+    }
+
+    fn rule(id: i64, time: ScheduleTime, days: &[Weekday]) -> ScheduleRule {
+        ScheduleRule {
+            id,
+            name: format!("rule {id}"),
+            time,
+            days: days.to_vec(),
+            action: ScheduleAction::TurnOff { zone: None },
+        }
+    }
+
+    fn at(hour: u32, minute: u32) -> ScheduleTime {
+        ScheduleTime::At { hour, minute }
+    }
+
+    /// Returns rule ids and UTC times of occurrences in the given UTC range.
+    fn occurrences<Tz: TimeZone>(
+        rules: &[ScheduleRule],
+        tz: &Tz,
+        after: &str,
+        until: &str,
+        location: Option<Location>,
+    ) -> Vec<(i64, DateTime<Utc>)> {
+        occurrences_between(
+            rules,
+            utc(after).with_timezone(tz),
+            utc(until).with_timezone(tz),
+            location,
+        )
+        .into_iter()
+        .map(|(rule, time)| (rule.id, time.with_timezone(&Utc)))
+        .collect()
+    }
+
+    #[test]
+    fn rules_fire_on_selected_weekdays_only() {
+        let rules = [
+            rule(1, at(18, 0), &[Weekday::Saturday, Weekday::Sunday]),
+            rule(2, at(7, 30), &[]),
+        ];
+
+        // 2026-10-14 is a Wednesday
+        assert_eq!(
+            occurrences(&rules, &Utc, "2026-10-14 12:00", "2026-10-19 00:00", None),
+            vec![
+                (2, utc("2026-10-15 07:30")),
+                (2, utc("2026-10-16 07:30")),
+                (2, utc("2026-10-17 07:30")),
+                (1, utc("2026-10-17 18:00")),
+                (2, utc("2026-10-18 07:30")),
+                (1, utc("2026-10-18 18:00")),
+            ]
+        );
+        let (rule, time) = next_event(&rules[..1], utc("2026-10-14 12:00"), None).unwrap();
+        assert_eq!((rule.id, time), (1, utc("2026-10-17 18:00")));
+    }
+
+    #[test]
+    fn rules_keep_local_time_across_daylight_saving_changes() {
+        let rules = [rule(1, at(18, 0), &[])];
+
+        assert_eq!(
+            occurrences(
+                &rules,
+                &CentralEurope,
+                "2026-03-28 12:00",
+                "2026-03-30 12:00",
+                None
+            ),
+            vec![(1, utc("2026-03-28 17:00")), (1, utc("2026-03-29 16:00"))]
+        );
+        assert_eq!(
+            occurrences(
+                &rules,
+                &CentralEurope,
+                "2026-10-24 12:00",
+                "2026-10-26 12:00",
+                None
+            ),
+            vec![(1, utc("2026-10-24 16:00")), (1, utc("2026-10-25 17:00"))]
+        );
+    }
+
+    #[test]
+    fn skipped_times_fire_later_and_repeated_times_fire_once() {
+        let rules = [rule(1, at(2, 30), &[])];
+
+        // Clocks go from 02:00 straight to 03:00
+        assert_eq!(
+            occurrences(
+                &rules,
+                &CentralEurope,
+                "2026-03-28 12:00",
+                "2026-03-29 12:00",
+                None
+            ),
+            vec![(1, utc("2026-03-29 01:30"))]
+        );
+        // Clocks go from 03:00 back to 02:00
+        assert_eq!(
+            occurrences(
+                &rules,
+                &CentralEurope,
+                "2026-10-24 12:00",
+                "2026-10-25 12:00",
+                None
+            ),
+            vec![(1, utc("2026-10-25 00:30"))]
+        );
+    }
+
+    #[test]
+    fn offsets_can_cross_midnight() {
+        let rules = [
+            rule(
+                1,
+                ScheduleTime::Sunset {
+                    offset_minutes: 600,
+                },
+                &[],
+            ),
+            rule(
+                2,
+                ScheduleTime::Sunrise {
+                    offset_minutes: -480,
+                },
+                &[],
+            ),
+        ];
+        let date = |day| NaiveDate::from_ymd_opt(2026, 12, day).unwrap();
+        let (_, sunset) = sunrise_sunset(date(21), WARSAW.latitude, WARSAW.longitude).unwrap();
+        let (sunrise, _) = sunrise_sunset(date(22), WARSAW.latitude, WARSAW.longitude).unwrap();
+
+        // Sunset of one day is followed by sunrise of the next one at about
+        // 14:25 and 06:45 UTC
+        assert_eq!(
+            occurrences(
+                &rules,
+                &Utc,
+                "2026-12-21 20:00",
+                "2026-12-22 03:00",
+                Some(WARSAW)
+            ),
+            vec![
+                (2, sunrise - Duration::hours(8)),
+                (1, sunset + Duration::hours(10)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn offsets_are_limited_to_half_a_day() {
+        let logic = Logic::new(
+            schedule::Storage::new(SharedDbConnection::in_memory().await.unwrap()),
+            Some(WARSAW),
+        );
+
+        for offset_minutes in [-MAX_OFFSET_MINUTES, 0, MAX_OFFSET_MINUTES] {
+            assert!(
+                logic
+                    .validate_time(ScheduleTime::Sunrise { offset_minutes })
+                    .is_ok()
+            );
+            assert!(
+                logic
+                    .validate_time(ScheduleTime::Sunset { offset_minutes })
+                    .is_ok()
+            );
+        }
+        for offset_minutes in [-MAX_OFFSET_MINUTES - 1, MAX_OFFSET_MINUTES + 1, i64::MIN] {
+            assert!(matches!(
+                logic.validate_time(ScheduleTime::Sunset { offset_minutes }),
+                Err(LogicError::InvalidRule(_))
+            ));
+        }
+    }
+}
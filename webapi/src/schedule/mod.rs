@@ -0,0 +1,8 @@
+mod logic;
+mod service;
+mod storage;
+mod sun;
+
+pub use logic::{Logic, LogicError};
+pub use service::service;
+use storage::Storage;
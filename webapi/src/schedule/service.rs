@@ -0,0 +1,59 @@
+use actix_web::{HttpResponse, Scope, get, post, web};
+use serde_json::json;
+use webapi_model::{CreateScheduleRuleRequest, ScheduleRuleRequest};
+
+use crate::schedule;
+
+fn error_response(e: schedule::LogicError) -> HttpResponse {
+    match e {
+        schedule::LogicError::InternalError(e) => {
+            HttpResponse::InternalServerError().json(json!({ "error": e }))
+        }
+        e @ schedule::LogicError::NoSuchRule(_) => {
+            HttpResponse::NotFound().json(json!({ "error": e.to_string() }))
+        }
+        e @ schedule::LogicError::InvalidRule(_) => {
+            HttpResponse::BadRequest().json(json!({ "error": e.to_string() }))
+        }
+    }
+}
+
+#[get("/list/")]
+async fn list(schedule: web::Data<schedule::Logic>) -> HttpResponse {
+    match schedule.list().await {
+        Ok(schedule) => HttpResponse::Ok().json(schedule),
+        Err(e) => error_response(e),
+    }
+}
+
+#[post("/create/")]
+async fn create(
+    form: web::Json<CreateScheduleRuleRequest>,
+    schedule: web::Data<schedule::Logic>,
+) -> HttpResponse {
+    match schedule
+        .create(&form.name, form.time, &form.days, &form.action)
+        .await
+    {
+        Ok(schedule) => HttpResponse::Ok().json(schedule),
+        Err(e) => error_response(e),
+    }
+}
+
+#[post("/remove/")]
+async fn remove(
+    form: web::Json<ScheduleRuleRequest>,
+    schedule: web::Data<schedule::Logic>,
+) -> HttpResponse {
+    match schedule.remove(form.rule_id).await {
+        Ok(schedule) => HttpResponse::Ok().json(schedule),
+        Err(e) => error_response(e),
+    }
+}
+
+pub fn service() -> Scope {
+    web::scope("/schedule")
+        .service(list)
+        .service(create)
+        .service(remove)
+}
@@ -0,0 +1,83 @@
+use anyhow::anyhow;
+use log::warn;
+use webapi_model::{ScheduleAction, ScheduleRule, ScheduleTime, Weekday};
+
+use crate::db::SharedDbConnection;
+
+#[derive(Debug, Clone)]
+pub struct Storage {
+    conn: SharedDbConnection,
+}
+
+fn rule_from_row(
+    id: i64,
+    name: String,
+    time: &[u8],
+    days: &[u8],
+    action: &[u8],
+) -> anyhow::Result<ScheduleRule> {
+    Ok(ScheduleRule {
+        id,
+        name,
+        time: serde_json::from_slice::<ScheduleTime>(time)
+            .map_err(|_| anyhow!("Invalid time for schedule rule with id {id}"))?,
+        days: serde_json::from_slice::<Vec<Weekday>>(days)
+            .map_err(|_| anyhow!("Invalid days for schedule rule with id {id}"))?,
+        action: serde_json::from_slice::<ScheduleAction>(action)
+            .map_err(|_| anyhow!("Invalid action for schedule rule with id {id}"))?,
+    })
+}
+
+impl Storage {
+    pub fn new(conn: SharedDbConnection) -> Self {
+        Self { conn }
+    }
+
+    pub async fn create(
+        &self,
+        name: &str,
+        time: &ScheduleTime,
+        days: &[Weekday],
+        action: &ScheduleAction,
+    ) -> anyhow::Result<i64> {
+        let time = serde_json::to_vec(time)?;
+        let days = serde_json::to_vec(days)?;
+        let action = serde_json::to_vec(action)?;
+        let id = sqlx::query!(
+            "INSERT INTO schedule_rules(name, time, days, action) VALUES ($1, $2, $3, $4) RETURNING id",
+            name,
+            time,
+            days,
+            action
+        )
+        .fetch_one(&mut *self.conn.lock().await)
+        .await?
+        .id;
+
+        Ok(id)
+    }
+
+    pub async fn fetch_all(&self) -> anyhow::Result<Vec<ScheduleRule>> {
+        let rules =
+            sqlx::query!("SELECT id, name, time, days, action FROM schedule_rules ORDER BY id")
+                .fetch_all(&mut *self.conn.lock().await)
+                .await?
+                .into_iter()
+                .filter_map(|r| {
+                    rule_from_row(r.id, r.name, &r.time, &r.days, &r.action)
+                        .inspect_err(|e| warn!("Skipping invalid schedule rule: {e}"))
+                        .ok()
+                })
+                .collect();
+
+        Ok(rules)
+    }
+
+    pub async fn delete(&self, rule_id: i64) -> anyhow::Result<bool> {
+        let result = sqlx::query!("DELETE FROM schedule_rules WHERE id = $1", rule_id)
+            .execute(&mut *self.conn.lock().await)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
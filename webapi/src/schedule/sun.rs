@@ -0,0 +1,89 @@
+use chrono::{DateTime, NaiveDate, Utc};
+
+const UNIX_EPOCH_JULIAN_DAY: f64 = 2440587.5;
+const J2000_JULIAN_DAY: f64 = 2451545.0;
+/// Sun altitude at sunrise and sunset, accounting for refraction and solar disc size
+const SUN_ALTITUDE_DEGREES: f64 = -0.833;
+const EARTH_AXIAL_TILT_DEGREES: f64 = 23.4397;
+
+fn to_datetime(julian_day: f64) -> Option<DateTime<Utc>> {
+    let seconds = (julian_day - UNIX_EPOCH_JULIAN_DAY) * 86400.0;
+    DateTime::from_timestamp(seconds.floor() as i64, 0)
+}
+
+/// Computes sunrise and sunset times for the given date and location, using the
+/// [sunrise equation](https://en.wikipedia.org/wiki/Sunrise_equation). Accuracy
+/// is within a couple of minutes, which is plenty for scheduling lights.
+///
+/// Returns `None` for polar day and polar night.
+pub fn sunrise_sunset(
+    date: NaiveDate,
+    latitude: f64,
+    longitude: f64,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let days_since_epoch = (date - NaiveDate::from_ymd_opt(1970, 1, 1)?).num_days() as f64;
+    let day_number = (days_since_epoch + UNIX_EPOCH_JULIAN_DAY + 0.5 - J2000_JULIAN_DAY).round();
+
+    let mean_solar_time = day_number - longitude / 360.0;
+    let mean_anomaly = (357.5291 + 0.98560028 * mean_solar_time).rem_euclid(360.0);
+    let m = mean_anomaly.to_radians();
+    let center = 1.9148 * m.sin() + 0.02 * (2.0 * m).sin() + 0.0003 * (3.0 * m).sin();
+    let ecliptic_longitude = (mean_anomaly + center + 180.0 + 102.9372)
+        .rem_euclid(360.0)
+        .to_radians();
+    let solar_transit = J2000_JULIAN_DAY + mean_solar_time + 0.0053 * m.sin()
+        - 0.0069 * (2.0 * ecliptic_longitude).sin();
+
+    let declination_sin = ecliptic_longitude.sin() * EARTH_AXIAL_TILT_DEGREES.to_radians().sin();
+    let declination_cos = declination_sin.asin().cos();
+    let latitude = latitude.to_radians();
+    let hour_angle_cos = (SUN_ALTITUDE_DEGREES.to_radians().sin()
+        - latitude.sin() * declination_sin)
+        / (latitude.cos() * declination_cos);
+    if !(-1.0..=1.0).contains(&hour_angle_cos) {
+        return None;
+    }
+    let hour_angle = hour_angle_cos.acos().to_degrees();
+
+    Some((
+        to_datetime(solar_transit - hour_angle / 360.0)?,
+        to_datetime(solar_transit + hour_angle / 360.0)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDateTime;
+
+    use super::*;
+
+    fn assert_close(actual: DateTime<Utc>, expected: &str) {
+        let expected = NaiveDateTime::parse_from_str(expected, "%Y-%m-%d %H:%M")
+            .unwrap()
+            .and_utc();
+        assert!(
+            (actual - expected).num_minutes().abs() <= 3,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn sunrise_sunset_warsaw() {
+        let (sunrise, sunset) =
+            sunrise_sunset(NaiveDate::from_ymd_opt(2024, 6, 21).unwrap(), 52.23, 21.01).unwrap();
+        assert_close(sunrise, "2024-06-21 02:14");
+        assert_close(sunset, "2024-06-21 19:01");
+
+        let (sunrise, sunset) =
+            sunrise_sunset(NaiveDate::from_ymd_opt(2024, 12, 21).unwrap(), 52.23, 21.01).unwrap();
+        assert_close(sunrise, "2024-12-21 06:43");
+        assert_close(sunset, "2024-12-21 14:25");
+    }
+
+    #[test]
+    fn polar_night() {
+        assert!(
+            sunrise_sunset(NaiveDate::from_ymd_opt(2024, 12, 21).unwrap(), 78.22, 15.65).is_none()
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use log::error;
-use rustmas_webapi_client::{Animation, RustmasApiClient};
+use rustmas_webapi_client::{Animation, AnimationFailure, RustmasApiClient};
 use wasm_bindgen::JsCast;
 use web_sys::HtmlAnchorElement;
 use yew::{Callback, Html, MouseEvent, html};
@@ -18,6 +18,7 @@ pub fn animation_list(props: &AnimationListProps) -> Html {
     let api = yew::use_context::<RustmasApiClient>().expect("gateway to be open");
     let animation_list = yew::use_state::<Option<Vec<Animation>>, _>(|| None);
     let animation_id = yew::use_state::<Option<String>, _>(|| None);
+    let failures = yew::use_state::<Vec<AnimationFailure>, _>(Vec::new);
 
     let turn_off = Callback::from({
         let api = api.clone();
@@ -87,6 +88,7 @@ pub fn animation_list(props: &AnimationListProps) -> Html {
         let api = api.clone();
         let animation_id = animation_id.clone();
         let animation_list = animation_list.clone();
+        let failures = failures.clone();
         let animation_switched_callback = props.animation_switched_callback.clone();
         wasm_bindgen_futures::spawn_local(async move {
             match api.list_animations().await {
@@ -94,6 +96,7 @@ pub fn animation_list(props: &AnimationListProps) -> Html {
                     response.animations.sort_by(|a, b| a.name.cmp(&b.name));
                     animation_list.set(Some(response.animations));
                     animation_id.set(response.current_animation_id.clone());
+                    failures.set(response.failures);
                     animation_switched_callback.emit(response.current_animation_id);
                 }
                 Err(e) => error!("Failed to load animations, reason: {}", e),
@@ -112,6 +115,25 @@ pub fn animation_list(props: &AnimationListProps) -> Html {
                     </Link<Route>>
                 </li>
                 <hr />
+                {
+                    failures.iter().map(|failure| html! {
+                        <li class="warning" title={failure.error.clone()}>
+                            {
+                                match &failure.layer {
+                                    Some(layer) => format!(
+                                        "⚠ Layer {layer} ({}) removed after repeated failures",
+                                        failure.animation_id,
+                                    ),
+                                    None => format!(
+                                        "⚠ {} stopped after repeated failures, showing {} instead",
+                                        failure.animation_id,
+                                        failure.fallback_animation_id.as_deref().unwrap_or("black"),
+                                    ),
+                                }
+                            }
+                        </li>
+                    }).collect::<Html>()
+                }
                 {
                     if let Some(ref animations) = *animation_list {
                         animations.iter().map(|animation| html! {
@@ -0,0 +1,46 @@
+use animation_api::schema::{ParameterSchema, ValueSchema};
+use web_sys::HtmlInputElement;
+use yew::{Callback, Html, html};
+
+use super::ParameterControlProps;
+
+#[yew::function_component(CheckboxParameterControl)]
+pub fn checkbox_parameter_control(props: &ParameterControlProps) -> Html {
+    let checkbox_ref = yew::use_node_ref();
+    let hidden_ref = yew::use_node_ref();
+
+    let onchange = Callback::from({
+        let checkbox_ref = checkbox_ref.clone();
+        let hidden_ref = hidden_ref.clone();
+        move |_| {
+            if let (Some(checkbox), Some(hidden)) = (
+                checkbox_ref.cast::<HtmlInputElement>(),
+                hidden_ref.cast::<HtmlInputElement>(),
+            ) {
+                hidden.set_value(&checkbox.checked().to_string());
+            }
+        }
+    });
+
+    if let ParameterSchema {
+        id,
+        value: ValueSchema::Boolean,
+        ..
+    } = &props.schema
+    {
+        let checked = props
+            .value
+            .as_ref()
+            .and_then(|v| v.boolean())
+            .unwrap_or(false);
+
+        html! {
+            <>
+                <input type="checkbox" ref={checkbox_ref} {checked} {onchange} />
+                <input name={id.clone()} type="hidden" ref={hidden_ref} value={checked.to_string()} />
+            </>
+        }
+    } else {
+        html!()
+    }
+}
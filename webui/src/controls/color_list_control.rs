@@ -0,0 +1,181 @@
+use animation_api::schema::{ParameterSchema, ParameterValue, ValueSchema};
+use lightfx::{Color, GradientStop};
+use web_sys::{Event, EventInit, HtmlInputElement};
+use yew::{Callback, Html, NodeRef, TargetCast, UseStateHandle, html};
+
+use super::ParameterControlProps;
+
+fn stops_from_value(value: Option<&ParameterValue>) -> Vec<GradientStop> {
+    match value {
+        Some(ParameterValue::Gradient(stops)) => stops.clone(),
+        Some(ParameterValue::Palette(colors)) => {
+            let last = (colors.len() as f64 - 1.0).max(1.0);
+            colors
+                .iter()
+                .enumerate()
+                .map(|(i, color)| GradientStop {
+                    position: i as f64 / last,
+                    color: *color,
+                })
+                .collect()
+        }
+        _ => vec![
+            GradientStop {
+                position: 0.0,
+                color: Color::black(),
+            },
+            GradientStop {
+                position: 1.0,
+                color: Color::white(),
+            },
+        ],
+    }
+}
+
+fn value_from_stops(stops: &[GradientStop], gradient: bool) -> ParameterValue {
+    if gradient {
+        ParameterValue::Gradient(stops.to_vec())
+    } else {
+        ParameterValue::Palette(stops.iter().map(|stop| stop.color).collect())
+    }
+}
+
+/// Updates the list and the form input, returning the form input, so that
+/// changes not caused by input events can be announced to the form.
+fn update_stops(
+    stops: &UseStateHandle<Vec<GradientStop>>,
+    hidden_ref: &NodeRef,
+    gradient: bool,
+    new_stops: Vec<GradientStop>,
+) -> Option<HtmlInputElement> {
+    let hidden = hidden_ref.cast::<HtmlInputElement>();
+    if let Some(hidden) = &hidden {
+        hidden.set_value(&serde_json::to_string(&value_from_stops(&new_stops, gradient)).unwrap());
+    }
+    stops.set(new_stops);
+    hidden
+}
+
+fn announce_change(input: &HtmlInputElement) {
+    let init = EventInit::new();
+    init.set_bubbles(true);
+    if let Ok(event) = Event::new_with_event_init_dict("change", &init) {
+        let _ = input.dispatch_event(&event);
+    }
+}
+
+/// Edits palettes and gradients, which are both lists of colors, but colors
+/// of gradients also have positions.
+#[yew::function_component(ColorListParameterControl)]
+pub fn color_list_parameter_control(props: &ParameterControlProps) -> Html {
+    let stops = yew::use_state(|| stops_from_value(props.value.as_ref()));
+    let hidden_ref = yew::use_node_ref();
+    let gradient = matches!(props.schema.value, ValueSchema::Gradient);
+
+    yew::use_effect_with(props.value.clone(), {
+        let stops = stops.clone();
+        move |value| stops.set(stops_from_value(value.as_ref()))
+    });
+
+    let on_color_change = Callback::from({
+        let stops = stops.clone();
+        let hidden_ref = hidden_ref.clone();
+        move |(index, event): (usize, yew::Event)| {
+            let input = event.target_unchecked_into::<HtmlInputElement>();
+            let Some(color) = Color::from_hex_str(&input.value()) else {
+                return;
+            };
+            let mut new_stops = (*stops).clone();
+            new_stops[index].color = color;
+            update_stops(&stops, &hidden_ref, gradient, new_stops);
+        }
+    });
+
+    let on_position_change = Callback::from({
+        let stops = stops.clone();
+        let hidden_ref = hidden_ref.clone();
+        move |(index, event): (usize, yew::Event)| {
+            let input = event.target_unchecked_into::<HtmlInputElement>();
+            let Ok(position) = input.value().parse::<f64>() else {
+                return;
+            };
+            let mut new_stops = (*stops).clone();
+            new_stops[index].position = position.clamp(0.0, 1.0);
+            update_stops(&stops, &hidden_ref, gradient, new_stops);
+        }
+    });
+
+    let on_remove = Callback::from({
+        let stops = stops.clone();
+        let hidden_ref = hidden_ref.clone();
+        move |index: usize| {
+            let mut new_stops = (*stops).clone();
+            new_stops.remove(index);
+            if let Some(hidden) = update_stops(&stops, &hidden_ref, gradient, new_stops) {
+                announce_change(&hidden);
+            }
+        }
+    });
+
+    let on_add = Callback::from({
+        let stops = stops.clone();
+        let hidden_ref = hidden_ref.clone();
+        move |_| {
+            let mut new_stops = (*stops).clone();
+            let color = new_stops.last().map_or(Color::white(), |stop| stop.color);
+            new_stops.push(GradientStop {
+                position: 1.0,
+                color,
+            });
+            if let Some(hidden) = update_stops(&stops, &hidden_ref, gradient, new_stops) {
+                announce_change(&hidden);
+            }
+        }
+    });
+
+    if let ParameterSchema {
+        id,
+        value: ValueSchema::Gradient | ValueSchema::Palette,
+        ..
+    } = &props.schema
+    {
+        let value_json = serde_json::to_string(&value_from_stops(&stops, gradient)).unwrap();
+        let removable = stops.len() > 1;
+
+        html! {
+            <div class="color-list-control">
+                {
+                    stops.iter().enumerate().map(|(index, stop)| html! {
+                        <div class="color-list-item">
+                            <input
+                                type="color"
+                                value={stop.color.to_hex_string()}
+                                onchange={on_color_change.reform(move |e| (index, e))} />
+                            {
+                                if gradient {
+                                    html! {
+                                        <input
+                                            type="number" min="0" max="1" step="0.05"
+                                            value={stop.position.to_string()}
+                                            onchange={on_position_change.reform(move |e| (index, e))} />
+                                    }
+                                } else {
+                                    html!()
+                                }
+                            }
+                            <input
+                                type="button"
+                                value="Remove"
+                                disabled={!removable}
+                                onclick={on_remove.reform(move |_| index)} />
+                        </div>
+                    }).collect::<Html>()
+                }
+                <input type="button" value="Add color" onclick={on_add} />
+                <input name={id.clone()} type="hidden" ref={hidden_ref} value={value_json} />
+            </div>
+        }
+    } else {
+        html!()
+    }
+}
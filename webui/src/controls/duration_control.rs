@@ -0,0 +1,59 @@
+use animation_api::schema::{ParameterSchema, ValueSchema};
+use web_sys::HtmlInputElement;
+use yew::{Callback, Html, html};
+
+use super::ParameterControlProps;
+
+#[yew::function_component(DurationParameterControl)]
+pub fn duration_parameter_control(props: &ParameterControlProps) -> Html {
+    let input_ref = yew::use_node_ref();
+    let hidden_ref = yew::use_node_ref();
+
+    let oninput = Callback::from({
+        let input_ref = input_ref.clone();
+        let hidden_ref = hidden_ref.clone();
+        move |_| {
+            let Some(seconds) = input_ref
+                .cast::<HtmlInputElement>()
+                .and_then(|input| input.value().parse::<f64>().ok())
+            else {
+                return;
+            };
+
+            if let Some(hidden) = hidden_ref.cast::<HtmlInputElement>() {
+                hidden.set_value(&seconds.to_string());
+            }
+        }
+    });
+
+    if let ParameterSchema {
+        id,
+        value: ValueSchema::Duration { min, max },
+        ..
+    } = &props.schema
+    {
+        let seconds = props
+            .value
+            .as_ref()
+            .and_then(|v| v.number())
+            .unwrap_or(*min);
+
+        html! {
+            <div class="duration-control">
+                <input
+                    ref={input_ref}
+                    type="number" min={min.to_string()} max={max.to_string()} step="0.1"
+                    value={seconds.to_string()}
+                    {oninput} />
+                <span>{ "seconds" }</span>
+                <input
+                    ref={hidden_ref}
+                    name={id.clone()}
+                    type="hidden"
+                    value={seconds.to_string()} />
+            </div>
+        }
+    } else {
+        html!()
+    }
+}
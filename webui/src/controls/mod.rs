@@ -1,11 +1,16 @@
+mod checkbox_control;
 mod color_control;
+mod color_list_control;
 mod debouncer;
+mod duration_control;
 mod parameter_control;
 mod parameter_control_list;
 mod parameter_control_props;
 mod select_control;
 mod slider_control;
 mod speed_control;
+mod text_control;
+mod vector_control;
 
 pub use parameter_control::ParameterControl;
 pub use parameter_control_list::ParameterControlList;
@@ -2,8 +2,11 @@ use animation_api::schema::ValueSchema;
 use yew::{Html, html};
 
 use crate::controls::{
-    color_control::ColorParameterControl, select_control::SelectParameterControl,
-    slider_control::SliderParameterControl, speed_control::SpeedParameterControl,
+    checkbox_control::CheckboxParameterControl, color_control::ColorParameterControl,
+    color_list_control::ColorListParameterControl, duration_control::DurationParameterControl,
+    select_control::SelectParameterControl, slider_control::SliderParameterControl,
+    speed_control::SpeedParameterControl, text_control::TextParameterControl,
+    vector_control::VectorParameterControl,
 };
 
 use super::ParameterControlProps;
@@ -16,7 +19,7 @@ pub fn parameter_control(props: &ParameterControlProps) -> Html {
     };
 
     html! {
-        <div {class}>
+        <div {class} hidden={!props.visible}>
             <h3>{ &props.schema.name }</h3>
             {
                 if let Some(description) = &props.schema.description {
@@ -35,6 +38,13 @@ pub fn parameter_control(props: &ParameterControlProps) -> Html {
                         html!{<SliderParameterControl schema={props.schema.clone()} value={props.value.clone()} dummy_update={props.dummy_update} />}
                     },
                     ValueSchema::Speed => html!{<SpeedParameterControl schema={props.schema.clone()} value={props.value.clone()} dummy_update={props.dummy_update} />},
+                    ValueSchema::Boolean => html!{<CheckboxParameterControl schema={props.schema.clone()} value={props.value.clone()} dummy_update={props.dummy_update} />},
+                    ValueSchema::Text {..} => html!{<TextParameterControl schema={props.schema.clone()} value={props.value.clone()} dummy_update={props.dummy_update} />},
+                    ValueSchema::Gradient | ValueSchema::Palette => {
+                        html!{<ColorListParameterControl schema={props.schema.clone()} value={props.value.clone()} dummy_update={props.dummy_update} />}
+                    },
+                    ValueSchema::Vector3 {..} => html!{<VectorParameterControl schema={props.schema.clone()} value={props.value.clone()} dummy_update={props.dummy_update} />},
+                    ValueSchema::Duration {..} => html!{<DurationParameterControl schema={props.schema.clone()} value={props.value.clone()} dummy_update={props.dummy_update} />},
                 }
             }
         </div>
@@ -1,6 +1,9 @@
 use std::{collections::HashMap, time::Duration};
 
-use animation_api::{event::Event, schema::ConfigurationSchema};
+use animation_api::{
+    event::Event,
+    schema::{ConfigurationSchema, ParameterSchema},
+};
 use log::error;
 use rustmas_webapi_client::{Configuration, ParameterValue, RustmasApiClient};
 use wasm_bindgen::JsCast;
@@ -30,17 +33,37 @@ fn build_parameter_update(
             (
                 p.id.clone(),
                 serde_json::from_str::<ParameterValue>(&form_data.get(&p.id).as_string().unwrap())
-                    .unwrap(),
+                    .unwrap()
+                    .for_schema(&p.value),
             )
         })
         .collect()
 }
 
+/// Puts parameters of the same group together, at the position of the first
+/// parameter of the group.
+fn group_parameters(parameters: &[ParameterSchema]) -> Vec<(Option<&str>, Vec<&ParameterSchema>)> {
+    let mut groups: Vec<(Option<&str>, Vec<&ParameterSchema>)> = Vec::new();
+    for parameter in parameters {
+        let group = parameter.group.as_deref();
+        match groups
+            .iter_mut()
+            .find(|(g, _)| group.is_some() && *g == group)
+        {
+            Some((_, members)) => members.push(parameter),
+            None => groups.push((group, vec![parameter])),
+        }
+    }
+    groups
+}
+
 #[yew::function_component(ParameterControlList)]
 pub fn parameter_control_list(props: &ParameterControlListProps) -> Html {
     let api = yew::use_context::<RustmasApiClient>().expect("gateway to be created");
     let animation = yew::use_state::<Option<Configuration>, _>(|| None);
     let dummy_update = yew::use_mut_ref(|| 0);
+    // Values changed since the animation was loaded, deciding which parameters are visible
+    let changed_values = yew::use_state_eq(HashMap::<String, ParameterValue>::new);
 
     let save_changes = Callback::from({
         let api = api.clone();
@@ -64,6 +87,7 @@ pub fn parameter_control_list(props: &ParameterControlListProps) -> Html {
         let animation = animation.clone();
         let change_debouncer = yew::use_mut_ref(|| Debouncer::new(Duration::from_millis(100)));
         let parameters_dirty = props.parameters_dirty.clone();
+        let changed_values = changed_values.clone();
         move |form: Option<HtmlFormElement>, force: bool| {
             if !force && change_debouncer.borrow_mut().poll() {
                 return;
@@ -83,6 +107,7 @@ pub fn parameter_control_list(props: &ParameterControlListProps) -> Html {
 
             let api = api.clone();
             let params = build_parameter_update(&animation.schema, &form);
+            changed_values.set(params.clone());
             wasm_bindgen_futures::spawn_local(async move {
                 if let Err(e) = api.set_params(&params).await {
                     error!("Failed to update parameters, reason: {}", e);
@@ -109,6 +134,7 @@ pub fn parameter_control_list(props: &ParameterControlListProps) -> Html {
         let animation = animation.clone();
         let parameters_dirty = props.parameters_dirty.clone();
         let dummy_update = dummy_update.clone();
+        let changed_values = changed_values.clone();
         move |_| {
             let api = api.clone();
             let animation = animation.clone();
@@ -124,6 +150,7 @@ pub fn parameter_control_list(props: &ParameterControlListProps) -> Html {
             });
 
             *dummy_update.borrow_mut() += 1;
+            changed_values.set(HashMap::new());
         }
     });
 
@@ -132,6 +159,7 @@ pub fn parameter_control_list(props: &ParameterControlListProps) -> Html {
         let animation = animation.clone();
         let parameters_dirty = props.parameters_dirty.clone();
         let dummy_update = dummy_update.clone();
+        let changed_values = changed_values.clone();
         move |_| {
             let api = api.clone();
             let animation = animation.clone();
@@ -147,6 +175,7 @@ pub fn parameter_control_list(props: &ParameterControlListProps) -> Html {
             });
 
             *dummy_update.borrow_mut() += 1;
+            changed_values.set(HashMap::new());
         }
     });
 
@@ -171,6 +200,7 @@ pub fn parameter_control_list(props: &ParameterControlListProps) -> Html {
     if animation.as_ref().map(|a| &a.id) != props.animation_id.as_ref() {
         let api = api.clone();
         let animation = animation.clone();
+        changed_values.set(HashMap::new());
         wasm_bindgen_futures::spawn_local(async move {
             match api.get_params().await {
                 Ok(new_animation) => animation.set(new_animation),
@@ -195,6 +225,8 @@ pub fn parameter_control_list(props: &ParameterControlListProps) -> Html {
                         </>
                     }
                 } else if let Some(ref animation) = *animation {
+                    let mut values = animation.values.clone();
+                    values.extend((*changed_values).clone());
                     html! {
                         <>
                             <h2>{&animation.name}</h2>
@@ -203,12 +235,26 @@ pub fn parameter_control_list(props: &ParameterControlListProps) -> Html {
                                     html! {
                                         <form onsubmit={save_changes} {oninput} {onchange}>
                                             {
-                                                animation.schema.parameters.iter()
-                                                    .map(|schema| html! {
-                                                        <ParameterControl
-                                                            schema={schema.clone()}
-                                                            value={animation.values.get(&schema.id).cloned()}
-                                                            dummy_update={*dummy_update.borrow()} />
+                                                group_parameters(&animation.schema.parameters).into_iter()
+                                                    .map(|(group, parameters)| {
+                                                        let hidden = parameters.iter().all(|schema| !schema.is_visible(&values));
+                                                        let controls = parameters.into_iter()
+                                                            .map(|schema| html! {
+                                                                <ParameterControl
+                                                                    schema={schema.clone()}
+                                                                    value={animation.values.get(&schema.id).cloned()}
+                                                                    dummy_update={*dummy_update.borrow()}
+                                                                    visible={schema.is_visible(&values)} />
+                                                            }).collect::<Html>();
+                                                        match group {
+                                                            Some(group) => html! {
+                                                                <details class="parameter-group" {hidden}>
+                                                                    <summary>{ group }</summary>
+                                                                    { controls }
+                                                                </details>
+                                                            },
+                                                            None => controls,
+                                                        }
                                                     }).collect::<Html>()
                                             }
                                             <div class="parameter-control buttons">
@@ -7,4 +7,6 @@ pub struct ParameterControlProps {
     pub schema: ParameterSchema,
     pub value: Option<ParameterValue>,
     pub dummy_update: usize,
+    #[prop_or(true)]
+    pub visible: bool,
 }
@@ -0,0 +1,53 @@
+use animation_api::schema::{ParameterSchema, ValueSchema};
+use web_sys::HtmlInputElement;
+use yew::{Callback, Html, html};
+
+use super::ParameterControlProps;
+
+#[yew::function_component(TextParameterControl)]
+pub fn text_parameter_control(props: &ParameterControlProps) -> Html {
+    let input_ref = yew::use_node_ref();
+    let hidden_ref = yew::use_node_ref();
+
+    let oninput = Callback::from({
+        let input_ref = input_ref.clone();
+        let hidden_ref = hidden_ref.clone();
+        move |_| {
+            if let (Some(input), Some(hidden)) = (
+                input_ref.cast::<HtmlInputElement>(),
+                hidden_ref.cast::<HtmlInputElement>(),
+            ) {
+                hidden.set_value(&serde_json::to_string(&input.value()).unwrap());
+            }
+        }
+    });
+
+    if let ParameterSchema {
+        id,
+        value: ValueSchema::Text { max_length },
+        ..
+    } = &props.schema
+    {
+        let value = props
+            .value
+            .as_ref()
+            .and_then(|v| v.text())
+            .unwrap_or_default()
+            .to_owned();
+        let value_json = serde_json::to_string(&value).unwrap();
+
+        html! {
+            <>
+                <input
+                    type="text"
+                    ref={input_ref}
+                    maxlength={max_length.to_string()}
+                    {value}
+                    {oninput} />
+                <input name={id.clone()} type="hidden" ref={hidden_ref} value={value_json} />
+            </>
+        }
+    } else {
+        html!()
+    }
+}
@@ -0,0 +1,75 @@
+use animation_api::schema::{ParameterSchema, ValueSchema};
+use web_sys::HtmlInputElement;
+use yew::{Callback, Html, html};
+
+use super::ParameterControlProps;
+
+const AXES: [&str; 3] = ["x", "y", "z"];
+
+#[yew::function_component(VectorParameterControl)]
+pub fn vector_parameter_control(props: &ParameterControlProps) -> Html {
+    let input_refs = [
+        yew::use_node_ref(),
+        yew::use_node_ref(),
+        yew::use_node_ref(),
+    ];
+    let hidden_ref = yew::use_node_ref();
+
+    let oninput = Callback::from({
+        let input_refs = input_refs.clone();
+        let hidden_ref = hidden_ref.clone();
+        move |_| {
+            let coordinates = input_refs.iter().map(|input_ref| {
+                input_ref
+                    .cast::<HtmlInputElement>()
+                    .and_then(|input| input.value().parse::<f64>().ok())
+            });
+            let Some(coordinates) = coordinates.collect::<Option<Vec<_>>>() else {
+                return;
+            };
+
+            if let Some(hidden) = hidden_ref.cast::<HtmlInputElement>() {
+                hidden.set_value(&serde_json::to_string(&coordinates).unwrap());
+            }
+        }
+    });
+
+    if let ParameterSchema {
+        id,
+        value: ValueSchema::Vector3 { min, max },
+        ..
+    } = &props.schema
+    {
+        let vector = props
+            .value
+            .as_ref()
+            .and_then(|v| v.vector3())
+            .unwrap_or_default();
+
+        html! {
+            <div class="vector-control">
+                {
+                    input_refs.iter().zip(AXES).zip(vector)
+                        .map(|((input_ref, axis), value)| html! {
+                            <label>
+                                { axis }
+                                <input
+                                    ref={input_ref.clone()}
+                                    type="number" min={min.to_string()} max={max.to_string()} step="0.01"
+                                    value={value.to_string()}
+                                    oninput={oninput.clone()} />
+                            </label>
+                        })
+                        .collect::<Html>()
+                }
+                <input
+                    ref={hidden_ref}
+                    name={id.clone()}
+                    type="hidden"
+                    value={serde_json::to_string(&vector).unwrap()} />
+            </div>
+        }
+    } else {
+        html!()
+    }
+}